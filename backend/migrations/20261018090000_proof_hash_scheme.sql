@@ -0,0 +1,3 @@
+-- Version du schéma de hachage utilisé pour chaque preuve.
+-- Les preuves existantes ont été hachées avec l'ancien format non échappé.
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS hash_scheme TEXT NOT NULL DEFAULT 'legacy';
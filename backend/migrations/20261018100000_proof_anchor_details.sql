@@ -0,0 +1,6 @@
+-- Détails d'ancrage renvoyés par le consensus Hedera
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS topic_sequence_number BIGINT;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS consensus_timestamp TIMESTAMP WITH TIME ZONE;
+
+-- Recherche des preuves par empreinte (vérification publique)
+CREATE INDEX IF NOT EXISTS idx_proofs_hash ON proofs(hash);
@@ -0,0 +1,4 @@
+-- Ancrage par lots : chaque preuve garde sa position dans l'arbre de Merkle ancré
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS merkle_root TEXT;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS merkle_leaf_index INTEGER;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS merkle_path JSONB;
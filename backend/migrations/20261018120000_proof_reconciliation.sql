@@ -0,0 +1,9 @@
+-- Réconciliation des preuves avec le mirror node : horodatage de consensus, running hash et écarts
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS running_hash TEXT;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS reconciliation_status TEXT NOT NULL DEFAULT 'pending'
+    CHECK (reconciliation_status IN ('pending', 'confirmed', 'mismatch', 'not_found'));
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS reconciliation_detail TEXT;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS reconciliation_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_proofs_reconciliation_status ON proofs(reconciliation_status);
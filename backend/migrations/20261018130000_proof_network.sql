@@ -0,0 +1,2 @@
+-- Réseau Hedera sur lequel chaque preuve a été ancrée (les preuves existantes l'ont été sur testnet)
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'testnet';
@@ -0,0 +1,29 @@
+-- Une seule preuve par résumé. Les doublons créés avant cette contrainte sont archivés, pas supprimés.
+CREATE TABLE IF NOT EXISTS proofs_duplicates AS SELECT * FROM proofs WHERE false;
+
+INSERT INTO proofs_duplicates
+SELECT p.* FROM proofs p
+WHERE EXISTS (
+    SELECT 1 FROM proofs older
+    WHERE older.summary_id = p.summary_id
+      AND (older.created_at, older.id) < (p.created_at, p.id)
+);
+
+DELETE FROM proofs p
+USING proofs_duplicates d
+WHERE p.id = d.id;
+
+DROP INDEX IF EXISTS idx_proofs_summary_id;
+CREATE UNIQUE INDEX IF NOT EXISTS uq_proofs_summary_id ON proofs(summary_id);
+
+-- Demandes d'enregistrement : identifiant de transaction pré-généré et clé d'idempotence du client
+CREATE TABLE IF NOT EXISTS proof_requests (
+    summary_id UUID PRIMARY KEY REFERENCES summaries(id),
+    idempotency_key TEXT UNIQUE,
+    hash TEXT NOT NULL,
+    hash_scheme TEXT NOT NULL,
+    transaction_id TEXT,
+    locked_until TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
@@ -0,0 +1,17 @@
+-- Boîte d'envoi des ancrages : chaque demande d'enregistrement devient une tâche
+-- traitée par le worker de fond (pending -> anchored, ou failed après épuisement des tentatives).
+ALTER TABLE proof_requests RENAME TO proof_jobs;
+ALTER INDEX proof_requests_pkey RENAME TO proof_jobs_pkey;
+ALTER INDEX proof_requests_idempotency_key_key RENAME TO proof_jobs_idempotency_key_key;
+
+ALTER TABLE proof_jobs
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'pending',
+    ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    ADD COLUMN last_error TEXT;
+
+UPDATE proof_jobs j
+SET status = 'anchored'
+WHERE EXISTS (SELECT 1 FROM proofs p WHERE p.summary_id = j.summary_id);
+
+CREATE INDEX IF NOT EXISTS idx_proof_jobs_due ON proof_jobs(next_attempt_at) WHERE status = 'pending';
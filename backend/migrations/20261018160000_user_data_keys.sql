@@ -0,0 +1,11 @@
+-- Clés de données par utilisateur, enveloppées par la clé maître (chiffrement au repos
+-- de ideas.raw_idea et des colonnes texte de summaries, préfixe `enc:v1:`).
+CREATE TABLE IF NOT EXISTS user_data_keys (
+    user_id UUID PRIMARY KEY REFERENCES users(id),
+    master_key_id TEXT NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    rotated_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_data_keys_master_key_id ON user_data_keys(master_key_id);
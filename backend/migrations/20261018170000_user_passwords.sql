@@ -0,0 +1,2 @@
+-- Connexion par e-mail et mot de passe (hash Argon2id). NULL pour les comptes créés avant l'authentification.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_hash TEXT;
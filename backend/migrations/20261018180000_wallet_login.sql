@@ -0,0 +1,15 @@
+-- Connexion par signature de wallet : défis à usage unique et clé publique vérifiée du compte
+CREATE TABLE IF NOT EXISTS wallet_challenges (
+    id UUID PRIMARY KEY,
+    wallet_address TEXT NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    consumed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_challenges_expires_at ON wallet_challenges(expires_at);
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS wallet_public_key TEXT,
+    ADD COLUMN IF NOT EXISTS wallet_verified_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,17 @@
+-- Adresses de wallet normalisées : forme canonique dans wallet_address, type dans wallet_type.
+-- NULL = adresse enregistrée avant la validation, conservée telle quelle.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS wallet_type TEXT;
+
+-- Comptes Hedera déjà sous forme canonique (sans somme de contrôle ni zéros superflus)
+UPDATE users
+SET wallet_type = 'hedera_account'
+WHERE wallet_type IS NULL
+  AND wallet_address ~ '^(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)$';
+
+-- Adresses long-zero (12 premiers octets nuls) : ramenées au compte qu'elles désignent
+UPDATE users
+SET wallet_type = 'evm_long_zero',
+    wallet_address = '0.0.' || ('x' || lpad(substr(lower(wallet_address), 27), 16, '0'))::bit(64)::bigint
+WHERE wallet_type IS NULL
+  AND lower(wallet_address) ~ '^0x0{24}[0-7][0-9a-f]{15}$';
@@ -0,0 +1,11 @@
+-- Co-signature de l'inventeur : signature de l'empreinte par la clé de son wallet,
+-- portée par la tâche d'ancrage puis par la preuve (NULL = preuve signée par le seul opérateur)
+ALTER TABLE proof_jobs
+    ADD COLUMN IF NOT EXISTS inventor_account TEXT,
+    ADD COLUMN IF NOT EXISTS inventor_public_key TEXT,
+    ADD COLUMN IF NOT EXISTS inventor_signature TEXT;
+
+ALTER TABLE proofs
+    ADD COLUMN IF NOT EXISTS inventor_account TEXT,
+    ADD COLUMN IF NOT EXISTS inventor_public_key TEXT,
+    ADD COLUMN IF NOT EXISTS inventor_signature TEXT;
@@ -0,0 +1,9 @@
+-- Clés publiques de signature des certificats (JWKS) : une clé retirée reste publiée
+-- pour que les certificats signés avant une rotation restent vérifiables
+CREATE TABLE IF NOT EXISTS certificate_signing_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm TEXT NOT NULL,
+    public_key TEXT NOT NULL, -- base64url (paramètre `x` du JWK)
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    retired_at TIMESTAMP WITH TIME ZONE
+);
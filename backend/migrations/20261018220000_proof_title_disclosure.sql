@@ -0,0 +1,3 @@
+-- Page publique de vérification : le titre du résumé n'y apparaît que si l'inventeur l'autorise
+ALTER TABLE proofs
+    ADD COLUMN IF NOT EXISTS title_disclosed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,13 @@
+-- Jeu de revendications d'un résumé : revendications indépendantes et dépendantes, numérotées à
+-- partir de 1. Une revendication ne peut dépendre que d'une revendication antérieure du même
+-- résumé, ce qui exclut les renvois en avant et les cycles. `text` est chiffré comme les résumés.
+-- Les résumés antérieurs n'ont pas de lignes ici : leur unique revendication reste `summaries.claim`.
+CREATE TABLE IF NOT EXISTS claims (
+    summary_id UUID NOT NULL REFERENCES summaries(id) ON DELETE CASCADE,
+    number INTEGER NOT NULL CHECK (number >= 1),
+    depends_on INTEGER,
+    text TEXT NOT NULL,
+    PRIMARY KEY (summary_id, number),
+    FOREIGN KEY (summary_id, depends_on) REFERENCES claims (summary_id, number),
+    CHECK (depends_on IS NULL OR depends_on < number)
+);
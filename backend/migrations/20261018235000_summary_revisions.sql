@@ -0,0 +1,22 @@
+-- Révisions de résumé : une modification crée une nouvelle ligne de `summaries`, les révisions
+-- précédentes ne sont plus modifiées. `original_id` regroupe les révisions d'un même résumé
+-- (révision 1 = le résumé généré par l'IA) ; `author_id` est l'utilisateur qui a créé la révision.
+ALTER TABLE summaries
+    ADD COLUMN IF NOT EXISTS original_id UUID REFERENCES summaries(id),
+    ADD COLUMN IF NOT EXISTS revision INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS author_id UUID REFERENCES users(id);
+
+UPDATE summaries SET original_id = id WHERE original_id IS NULL;
+UPDATE summaries s SET author_id = i.user_id FROM ideas i WHERE i.id = s.idea_id AND s.author_id IS NULL;
+
+ALTER TABLE summaries
+    ALTER COLUMN original_id SET NOT NULL,
+    ALTER COLUMN author_id SET NOT NULL;
+
+-- Deux modifications concurrentes de la même révision ne peuvent pas créer deux révisions suivantes
+CREATE UNIQUE INDEX IF NOT EXISTS uq_summaries_revision ON summaries(original_id, revision);
+
+-- Chaîne de conservation : empreinte de la dernière révision antérieure ancrée, incluse dans le
+-- document haché (schéma jcs-v3)
+ALTER TABLE proof_jobs ADD COLUMN IF NOT EXISTS previous_hash TEXT;
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS previous_hash TEXT;
@@ -0,0 +1,14 @@
+-- Langue des messages d'un compte (code de langue livrée : fr, en…). NULL : langue du navigateur.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS preferred_language TEXT;
+
+-- Notifications d'un compte. Seuls le type et les paramètres sont stockés :
+-- le texte est rendu à la lecture, dans la langue du moment.
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    kind TEXT NOT NULL,
+    params JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_created_idx ON notifications (user_id, created_at DESC);
@@ -0,0 +1,23 @@
+-- Génération des résumés en tâche de fond : la requête ne fait qu'écrire une tâche, un pool de workers
+-- appelle le service IA (queued -> running -> succeeded | failed).
+-- Une tâche `running` dont le bail a expiré (serveur arrêté) est reprise au démarrage suivant.
+-- En cas d'échec, `error_code` est le code d'erreur de l'API et `error_message` le message
+-- (clé du catalogue et paramètres), traduit à la lecture.
+CREATE TABLE IF NOT EXISTS summary_jobs (
+    id UUID PRIMARY KEY,
+    idea_id UUID NOT NULL REFERENCES ideas(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    summary_id UUID REFERENCES summaries(id),
+    error_code TEXT,
+    error_message JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Une seule génération en cours par idée : une nouvelle demande renvoie la tâche existante
+CREATE UNIQUE INDEX IF NOT EXISTS uq_summary_jobs_active ON summary_jobs(idea_id) WHERE status IN ('queued', 'running');
+CREATE INDEX IF NOT EXISTS idx_summary_jobs_idea ON summary_jobs(idea_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_summary_jobs_pending ON summary_jobs(created_at) WHERE status IN ('queued', 'running');
@@ -0,0 +1,35 @@
+-- Formules d'abonnement : le seuil de nouveauté à partir duquel une idée est retenue dépend de la formule.
+CREATE TABLE IF NOT EXISTS plans (
+    code TEXT PRIMARY KEY,
+    novelty_threshold SMALLINT NOT NULL CHECK (novelty_threshold BETWEEN 0 AND 100)
+);
+
+INSERT INTO plans (code, novelty_threshold) VALUES ('free', 50) ON CONFLICT (code) DO NOTHING;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS plan TEXT NOT NULL DEFAULT 'free' REFERENCES plans(code);
+
+-- Chaque réponse du service IA, retenue ou non. `response` (réponse complète) est chiffrée avec la clé
+-- de l'auteur de l'idée, comme le résumé. Un refus garde son motif (`rejection_reason`) et le message
+-- présenté à l'inventeur (clé du catalogue et paramètres, traduit à la lecture) ; l'inventeur peut
+-- demander une revue humaine (`review_status` : requested -> approved | dismissed).
+CREATE TABLE IF NOT EXISTS ai_evaluations (
+    id UUID PRIMARY KEY,
+    idea_id UUID NOT NULL REFERENCES ideas(id),
+    job_id UUID REFERENCES summary_jobs(id),
+    summary_id UUID REFERENCES summaries(id),
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    novelty_score SMALLINT NOT NULL,
+    novelty_threshold SMALLINT NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    accepted BOOLEAN NOT NULL,
+    rejection_reason TEXT,
+    rejection_message JSONB,
+    response TEXT NOT NULL,
+    review_status TEXT CHECK (review_status IN ('requested', 'approved', 'dismissed')),
+    review_requested_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_evaluations_idea ON ai_evaluations(idea_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_ai_evaluations_review ON ai_evaluations(review_requested_at) WHERE review_status = 'requested';
@@ -0,0 +1,5 @@
+-- Un wallet n'appartient qu'à un seul compte : la connexion par wallet et la co-signature retrouvent
+-- le compte par son adresse. Les adresses antérieures à la validation gardent leur casse d'origine,
+-- l'unicité porte donc sur la forme en minuscules. Doublons à résoudre avant d'appliquer :
+--   SELECT lower(wallet_address), array_agg(email) FROM users GROUP BY 1 HAVING count(*) > 1;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_wallet_address ON users (lower(wallet_address));
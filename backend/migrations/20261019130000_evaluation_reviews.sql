@@ -0,0 +1,3 @@
+-- Issue de la revue humaine d'un refus (`cargo run --bin review_evaluation`). Une revue approuvée remet
+-- l'idée en génération sans seuil de nouveauté ; le résumé produit est relié à la nouvelle évaluation.
+ALTER TABLE ai_evaluations ADD COLUMN IF NOT EXISTS reviewed_at TIMESTAMP WITH TIME ZONE;
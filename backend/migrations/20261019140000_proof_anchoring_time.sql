@@ -0,0 +1,3 @@
+-- `proofs.timestamp` est la date d'ancrage : les preuves existantes portaient la date de création du résumé.
+-- La date d'insertion de la preuve, écrite dès réception du reçu d'ancrage, la remplace.
+UPDATE proofs SET timestamp = created_at WHERE timestamp <> created_at;
@@ -0,0 +1,7 @@
+-- Lot Merkle d'une tâche d'ancrage, enregistré avant la soumission du lot : `transaction_id` est celui du lot,
+-- `batch_message` le message soumis. Une reprise retrouve le lot ou le resoumet sous le même identifiant.
+ALTER TABLE proof_jobs
+    ADD COLUMN IF NOT EXISTS batch_message TEXT,
+    ADD COLUMN IF NOT EXISTS merkle_root TEXT,
+    ADD COLUMN IF NOT EXISTS merkle_leaf_index INTEGER,
+    ADD COLUMN IF NOT EXISTS merkle_path JSONB;
@@ -1,14 +1,19 @@
 use hedera::{
-    Client, Hbar, PrivateKey, AccountId, TopicCreateTransaction,
+    Hbar, PrivateKey, AccountId, TopicCreateTransaction,
     TopicId, TransactionResponse, TransactionReceipt
 };
 
 use std::env;
 
+#[allow(dead_code)]
+#[path = "../network.rs"]
+mod network;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Configuration du client
-    let client = Client::for_testnet();
+    // Configuration du client (réseau choisi par HEDERA_NETWORK)
+    let config = network::NetworkConfig::from_env()?;
+    let client = config.client().map_err(|e| e as Box<dyn std::error::Error>)?;
 
     
     let account_id: AccountId = env::var("HEDERA_ACCOUNT_ID")?.parse()?;
@@ -33,6 +38,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     println!("✅ Topic créé avec succès !");
     println!("📋 Topic ID: {}", topic_id);
+    println!("🌐 Réseau: {}", config.network.as_str());
+    if let Some(url) = network::explorer_url(config.network.as_str(), &transaction.transaction_id.to_string()) {
+        println!("🔗 {}", url);
+    }
     
 
     Ok(())
@@ -1,8 +1,12 @@
 
 // Hedera SDK v0.33.0
-use hedera::{AccountId, Client, PrivateKey, Hbar, TransferTransaction};
+use hedera::{AccountId, PrivateKey, Hbar, TransferTransaction};
 use std::{env};
 
+#[allow(dead_code)]
+#[path = "../network.rs"]
+mod network;
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
 
@@ -10,8 +14,9 @@ async fn main() -> anyhow::Result<()> {
     let my_account_id: AccountId = env::var("HEDERA_ACCOUNT_ID")?.parse()?;
     let my_private_key: PrivateKey = env::var("HEDERA_PRIVATE_KEY")?.parse()?;
 
-    //Pre-configured client for test network (testnet)
-    let client = Client::for_testnet();
+    //Client for the network selected by HEDERA_NETWORK (testnet by default)
+    let config = network::NetworkConfig::from_env().map_err(anyhow::Error::msg)?;
+    let client = config.client().map_err(|e| anyhow::anyhow!(e))?;
 
     //Set the operator with the account ID and private key
     client.set_operator(my_account_id, my_private_key.clone());
@@ -38,7 +43,9 @@ async fn main() -> anyhow::Result<()> {
     println!("-------------------------------- Transfer HBAR ------------------------------ ");
     println!("Receipt status       : {:?}", status_transfer_tx);
     println!("Transaction ID       : {:?}", tx_id_transfer);
-    println!("Hashscan URL         : https://hashscan.io/testnet/tx/{tx_id_transfer}");
+    if let Some(url) = network::explorer_url(config.network.as_str(), &tx_id_transfer) {
+        println!("Hashscan URL         : {url}");
+    }
 
     //Start your code here
 
@@ -0,0 +1,102 @@
+use chrono::Utc;
+use dotenvy::dotenv;
+use serde_json::json;
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+// Revue humaine des idées refusées par le service IA, demandée par l'inventeur
+// (POST /api/v1/evaluations/{id}/review).
+//   cargo run --bin review_evaluation                          demandes en attente
+//   cargo run --bin review_evaluation -- approve <evaluation>  idée retenue : nouvelle génération sans seuil de nouveauté
+//   cargo run --bin review_evaluation -- dismiss <evaluation>  refus confirmé
+// L'inventeur est notifié de la décision ; le résumé d'une idée retenue est produit par les workers de génération.
+
+// Types de notification, rendus par notifications.rs
+const REVIEW_APPROVED: &str = "review_approved";
+const REVIEW_DISMISSED: &str = "review_dismissed";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+    let pool = PgPool::connect(&env::var("DATABASE_URL")?).await?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (approve, evaluation_id) = match args.as_slice() {
+        [] => return list_requested(&pool).await,
+        [decision, id] if decision == "approve" || decision == "dismiss" => (decision == "approve", Uuid::parse_str(id)?),
+        _ => return Err("usage : review_evaluation [approve|dismiss <evaluation>]".into()),
+    };
+
+    // La condition sur `review_status` protège d'une double décision
+    let mut tx = pool.begin().await?;
+    let Some(reviewed) = sqlx::query!(
+        "UPDATE ai_evaluations e SET review_status = $2, reviewed_at = $3
+         FROM ideas i
+         WHERE e.id = $1 AND e.review_status = 'requested' AND i.id = e.idea_id
+         RETURNING e.idea_id, i.user_id",
+        evaluation_id,
+        if approve { "approved" } else { "dismissed" },
+        Utc::now()
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Err(format!("aucune demande de revue en attente pour l'évaluation {}", evaluation_id).into());
+    };
+
+    if approve {
+        // Relance prise en charge par les workers ; une génération déjà en file ou en cours suffit
+        sqlx::query!(
+            "INSERT INTO summary_jobs (id, idea_id, user_id) VALUES ($1, $2, $3)
+             ON CONFLICT (idea_id) WHERE status IN ('queued', 'running') DO NOTHING",
+            Uuid::new_v4(),
+            reviewed.idea_id,
+            reviewed.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query!(
+        "INSERT INTO notifications (id, user_id, kind, params) VALUES ($1, $2, $3, $4)",
+        Uuid::new_v4(),
+        reviewed.user_id,
+        if approve { REVIEW_APPROVED } else { REVIEW_DISMISSED },
+        json!({ "idea_id": reviewed.idea_id })
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    if approve {
+        println!("✅ Évaluation {} approuvée, génération du résumé de l'idée {} relancée", evaluation_id, reviewed.idea_id);
+    } else {
+        println!("✅ Refus de l'idée {} confirmé (évaluation {})", reviewed.idea_id, evaluation_id);
+    }
+    Ok(())
+}
+
+// Demandes en attente, de la plus ancienne à la plus récente
+async fn list_requested(pool: &PgPool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let requests = sqlx::query!(
+        r#"SELECT id, idea_id, novelty_score, novelty_threshold, rejection_reason, review_requested_at AS "requested_at!"
+           FROM ai_evaluations WHERE review_status = 'requested' ORDER BY review_requested_at"#
+    )
+    .fetch_all(pool)
+    .await?;
+    if requests.is_empty() {
+        println!("Aucune demande de revue en attente");
+    }
+    for request in requests {
+        println!(
+            "{}  idée {}  score {}/{}  {}  demandée le {}",
+            request.id,
+            request.idea_id,
+            request.novelty_score,
+            request.novelty_threshold,
+            request.rejection_reason.as_deref().unwrap_or("-"),
+            request.requested_at.to_rfc3339()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use dotenvy::dotenv;
+use sqlx::PgPool;
+use std::env;
+
+// Formule d'abonnement d'un compte ; elle fixe le seuil de nouveauté à partir duquel ses idées sont retenues.
+//   cargo run --bin set_plan -- <email> <formule>
+// Les formules sont créées ou modifiées en SQL, le nouveau seuil s'applique aux générations suivantes :
+//   INSERT INTO plans (code, novelty_threshold) VALUES ('pro', 30)
+//   ON CONFLICT (code) DO UPDATE SET novelty_threshold = EXCLUDED.novelty_threshold;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [email, plan] = args.as_slice() else {
+        return Err("usage : set_plan <email> <formule>".into());
+    };
+    let pool = PgPool::connect(&env::var("DATABASE_URL")?).await?;
+
+    let Some(threshold) = sqlx::query_scalar!("SELECT novelty_threshold FROM plans WHERE code = $1", plan)
+        .fetch_optional(&pool)
+        .await?
+    else {
+        let plans = sqlx::query_scalar!("SELECT code FROM plans ORDER BY code").fetch_all(&pool).await?;
+        return Err(format!("formule {} inconnue (formules : {})", plan, plans.join(", ")).into());
+    };
+    let updated = sqlx::query!("UPDATE users SET plan = $2 WHERE email = $1", email, plan)
+        .execute(&pool)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(format!("aucun compte pour {}", email).into());
+    }
+    println!("✅ {} passe à la formule {} (seuil de nouveauté {})", email, plan, threshold);
+    Ok(())
+}
@@ -1,37 +1,415 @@
-use reqwest;
-use serde::{Serialize};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::sync::Arc;
 use std::time::Duration;
-use crate::models::AiResponse;
+use crate::models::{AiClaim, AiResponse};
+
+// Service IA qui structure une idée (titre, problème, solution, revendications, CPC, score de nouveauté).
+// Service Python `ia/` en HTTP en production, implémentation déterministe en local et pour les tests.
+
+#[derive(Debug, thiserror::Error)]
+pub enum AiError {
+    #[error("configuration du service IA invalide: {0}")]
+    Config(String),
+    #[error("service IA injoignable après {attempts} tentatives")]
+    Unreachable { attempts: u32 },
+    #[error("requête refusée par le service IA: {0}")]
+    Rejected(reqwest::StatusCode),
+    #[error("réponse du service IA illisible: {0}")]
+    InvalidResponse(reqwest::Error),
+}
+
+/// Fragment de texte produit pendant la génération (`title`, `problem` ou `solution`).
+#[derive(Debug, Clone)]
+pub struct AiPartial {
+    pub field: &'static str,
+    pub delta: String,
+}
+
+#[async_trait]
+pub trait AiProvider: Send + Sync {
+    /// Nom du fournisseur, pour les journaux.
+    fn name(&self) -> &'static str;
+
+    /// Structure une idée brute.
+    async fn structure(&self, raw_idea: &str) -> Result<AiResponse, AiError>;
+
+    /// Comme `structure`, en signalant le texte au fur et à mesure de sa production.
+    /// Un fournisseur sans streaming n'appelle jamais `on_partial`.
+    async fn structure_streaming(
+        &self,
+        raw_idea: &str,
+        _on_partial: &(dyn Fn(AiPartial) + Send + Sync),
+    ) -> Result<AiResponse, AiError> {
+        self.structure(raw_idea).await
+    }
+}
+
+/// Fournisseur choisi par `AI_PROVIDER` : `http` (défaut) ou `mock`.
+pub fn from_env() -> Result<Arc<dyn AiProvider>, AiError> {
+    let provider: Arc<dyn AiProvider> = match env::var("AI_PROVIDER").as_deref() {
+        Ok("mock") => Arc::new(MockAiProvider::from_env()?),
+        Ok("http") | Err(_) => Arc::new(HttpAiProvider::from_env()?),
+        Ok(other) => return Err(AiError::Config(format!("AI_PROVIDER inconnu: {}", other))),
+    };
+    println!("🤖 Service IA: {}", provider.name());
+    Ok(provider)
+}
+
+fn env_number<T: std::str::FromStr>(name: &str, default: T) -> Result<T, AiError> {
+    match env::var(name) {
+        Ok(value) => value.trim().parse().map_err(|_| AiError::Config(format!("{} invalide: {}", name, value))),
+        Err(_) => Ok(default),
+    }
+}
 
 #[derive(Serialize)]
-struct AiRequest {
-    raw_idea: String,
-}
-
-pub async fn call_ai_service(raw_idea: String) -> Result<AiResponse, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let mut attempts = 0;
-    loop {
-        attempts += 1;
-        match client
-            .post("http://localhost:8000/ai/structure")
-            .json(&AiRequest { raw_idea: raw_idea.clone() })
-            .timeout(Duration::from_secs(30))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status().is_success() {
-                    return Ok(response.json::<AiResponse>().await?);
-                } else {
-                    eprintln!("AI response error: {}", response.status());
+struct AiRequest<'a> {
+    raw_idea: &'a str,
+}
+
+/// Service IA distant. Un seul client HTTP, donc un seul pool de connexions, pour tout le serveur.
+pub struct HttpAiProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl HttpAiProvider {
+    /// `AI_SERVICE_URL` (défaut http://localhost:8000), `AI_TIMEOUT_SECS` (30), `AI_MAX_ATTEMPTS` (3).
+    /// `AI_AUTH_TOKEN`, s'il est défini, est envoyé tel quel dans l'en-tête `AI_AUTH_HEADER` (défaut `Authorization`).
+    pub fn from_env() -> Result<Self, AiError> {
+        let base_url = env::var("AI_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+        let timeout = Duration::from_secs(env_number("AI_TIMEOUT_SECS", 30)?);
+        let max_attempts = env_number("AI_MAX_ATTEMPTS", 3u32)?.max(1);
+        let auth = env::var("AI_AUTH_TOKEN").ok().map(|token| {
+            (env::var("AI_AUTH_HEADER").unwrap_or_else(|_| "Authorization".to_string()), token)
+        });
+        Self::new(&base_url, timeout, max_attempts, auth)
+    }
+
+    /// `auth` : nom de l'en-tête et jeton, envoyés avec chaque requête.
+    pub fn new(
+        base_url: &str,
+        timeout: Duration,
+        max_attempts: u32,
+        auth: Option<(String, String)>,
+    ) -> Result<Self, AiError> {
+        let mut headers = HeaderMap::new();
+        if let Some((name, token)) = auth {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|_| AiError::Config(format!("AI_AUTH_HEADER invalide: {}", name)))?;
+            let mut value = HeaderValue::try_from(token.trim())
+                .map_err(|_| AiError::Config("AI_AUTH_TOKEN invalide".to_string()))?;
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .default_headers(headers)
+            .build()
+            .map_err(|e| AiError::Config(e.to_string()))?;
+
+        Ok(HttpAiProvider {
+            client,
+            endpoint: format!("{}/ai/structure", base_url.trim_end_matches('/')),
+            max_attempts: max_attempts.max(1),
+            retry_delay: Duration::from_secs(1),
+        })
+    }
+}
+
+#[async_trait]
+impl AiProvider for HttpAiProvider {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn structure(&self, raw_idea: &str) -> Result<AiResponse, AiError> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.client.post(&self.endpoint).json(&AiRequest { raw_idea }).send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if status.is_success() {
+                        return response.json::<AiResponse>().await.map_err(AiError::InvalidResponse);
+                    }
+                    eprintln!("AI response error: {}", status);
+                    // Seules les erreurs réseau et les erreurs du service (5xx) sont retentées :
+                    // une requête refusée (4xx) le serait de nouveau
+                    if !status.is_server_error() {
+                        return Err(AiError::Rejected(status));
+                    }
+                }
+                Err(e) => eprintln!("AI request failed (attempt {}): {}", attempts, e),
+            }
+            if attempts >= self.max_attempts {
+                return Err(AiError::Unreachable { attempts });
+            }
+            tokio::time::sleep(self.retry_delay).await;
+        }
+    }
+}
+
+// Mots-clés du mock -> code CPC, premier trouvé
+const MOCK_CPC: [(&str, &str); 8] = [
+    ("solaire", "H02J"),
+    ("énergie", "H02J"),
+    ("médical", "A61B"),
+    ("santé", "A61B"),
+    ("véhicule", "B60L"),
+    ("capteur", "G01D"),
+    ("réseau", "H04L"),
+    ("logiciel", "G06F"),
+];
+const MOCK_DEFAULT_CPC: &str = "G06F";
+const MOCK_MAX_CLAIMS: usize = 5;
+const MOCK_MODEL: &str = "mock-v1";
+
+/// Service IA en mémoire, sans réseau : même idée, même réponse.
+/// Titre et revendications sont tirés des phrases de l'idée ; le score vaut `AI_MOCK_NOVELTY_SCORE`
+/// (défaut 75) pour pouvoir tester le refus d'une idée. En streaming, le texte est livré mot par mot,
+/// un mot toutes les `AI_MOCK_TOKEN_DELAY_MS` (défaut 0).
+pub struct MockAiProvider {
+    novelty_score: u8,
+    token_delay: Duration,
+}
+
+impl MockAiProvider {
+    pub fn new(novelty_score: u8) -> Self {
+        MockAiProvider { novelty_score: novelty_score.min(100), token_delay: Duration::ZERO }
+    }
+
+    pub fn from_env() -> Result<Self, AiError> {
+        Ok(MockAiProvider {
+            token_delay: Duration::from_millis(env_number("AI_MOCK_TOKEN_DELAY_MS", 0)?),
+            ..Self::new(env_number("AI_MOCK_NOVELTY_SCORE", 75)?)
+        })
+    }
+
+    fn respond(&self, raw_idea: &str) -> AiResponse {
+        let sentences: Vec<&str> = raw_idea
+            .split(['.', '!', '?', '\n'])
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+            .collect();
+        let first = sentences.first().copied().unwrap_or("Invention sans description");
+
+        let title: Vec<&str> = first.split_whitespace().take(12).collect();
+        let mut title = title.join(" ");
+        if let Some(initial) = title.chars().next() {
+            title.replace_range(..initial.len_utf8(), &initial.to_uppercase().to_string());
+        }
+
+        let lowercase = raw_idea.to_lowercase();
+        let cpc_code = MOCK_CPC
+            .iter()
+            .find(|(keyword, _)| lowercase.contains(keyword))
+            .map_or(MOCK_DEFAULT_CPC, |(_, code)| code);
+
+        // Revendication 1 indépendante, les suivantes en dépendent
+        let claims: Vec<AiClaim> = sentences
+            .iter()
+            .take(MOCK_MAX_CLAIMS)
+            .enumerate()
+            .map(|(index, sentence)| match index {
+                0 => AiClaim { text: format!("Dispositif caractérisé en ce que : {}.", sentence), depends_on: None },
+                _ => AiClaim {
+                    text: format!("Dispositif selon la revendication 1, dans lequel : {}.", sentence),
+                    depends_on: Some(1),
+                },
+            })
+            .collect();
+
+        // Référence stable de la réponse, pour reconnaître un résultat du mock dans les journaux
+        let digest = hex::encode(&Sha256::digest(raw_idea.as_bytes())[..4]);
+
+        AiResponse {
+            problem: format!("Les solutions actuelles ne permettent pas de : {} (mock {}).", first.to_lowercase(), digest),
+            solution: raw_idea.trim().to_string(),
+            claim: claims.first().map(|claim| claim.text.clone()).unwrap_or_default(),
+            claims,
+            cpc_code: cpc_code.to_string(),
+            novelty_score: self.novelty_score,
+            model: Some(MOCK_MODEL.to_string()),
+            title,
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for MockAiProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn structure(&self, raw_idea: &str) -> Result<AiResponse, AiError> {
+        Ok(self.respond(raw_idea))
+    }
+
+    async fn structure_streaming(
+        &self,
+        raw_idea: &str,
+        on_partial: &(dyn Fn(AiPartial) + Send + Sync),
+    ) -> Result<AiResponse, AiError> {
+        let response = self.respond(raw_idea);
+        for (field, text) in [("title", &response.title), ("problem", &response.problem), ("solution", &response.solution)] {
+            for word in text.split_inclusive(' ') {
+                if !self.token_delay.is_zero() {
+                    tokio::time::sleep(self.token_delay).await;
                 }
+                on_partial(AiPartial { field, delta: word.to_string() });
             }
-            Err(e) => eprintln!("AI request failed (attempt {}): {}", attempts, e),
         }
-        if attempts >= 3 {
-            return Err("AI service unreachable after 3 attempts".into());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claims;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Service IA factice qui répond `status` et `body` à chaque requête ; renvoie son URL et les requêtes reçues
+    async fn fake_service(status: u16, body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                // Requête lue en entier (en-têtes puis corps) avant de répondre
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                while let Ok(read) = socket.read(&mut buffer).await {
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    let complete = text.split_once("\r\n\r\n").is_some_and(|(headers, body)| {
+                        let length = headers
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        body.len() >= length
+                    });
+                    if read == 0 || complete {
+                        break;
+                    }
+                }
+                received.lock().unwrap().push(String::from_utf8_lossy(&request).into_owned());
+                let response = format!(
+                    "HTTP/1.1 {} Réponse\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (base_url, requests)
+    }
+
+    fn provider(base_url: &str, auth: Option<(&str, &str)>) -> HttpAiProvider {
+        let auth = auth.map(|(name, token)| (name.to_string(), token.to_string()));
+        HttpAiProvider { retry_delay: Duration::ZERO, ..HttpAiProvider::new(base_url, Duration::from_secs(5), 3, auth).unwrap() }
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_only() {
+        let (base_url, requests) = fake_service(503, "").await;
+        assert!(matches!(provider(&base_url, None).structure("idée").await, Err(AiError::Unreachable { attempts: 3 })));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        for status in [400, 401, 422] {
+            let (base_url, requests) = fake_service(status, "").await;
+            let result = provider(&base_url, None).structure("idée").await;
+            assert!(matches!(result, Err(AiError::Rejected(s)) if s.as_u16() == status));
+            assert_eq!(requests.lock().unwrap().len(), 1);
         }
-        tokio::time::sleep(Duration::from_secs(1)).await;
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn parses_a_successful_response_and_sends_the_auth_header() {
+        let body = r#"{"title":"Gourde filtrante","problem":"L'eau des sources n'est pas toujours potable.","solution":"Une gourde à filtre intégré.","claims":[{"text":"Gourde comprenant un filtre."},{"text":"Gourde selon la revendication 1, à filtre amovible.","depends_on":1}],"cpc_code":"C02F","novelty_score":82,"model":"structure-v2"}"#;
+        let (base_url, requests) = fake_service(200, body).await;
+        let provider = provider(&base_url, Some(("X-Api-Key", "secret-de-test")));
+
+        let response = provider.structure("une gourde qui filtre l'eau").await.unwrap();
+        assert_eq!(response.title, "Gourde filtrante");
+        assert_eq!(response.cpc_code, "C02F");
+        assert_eq!(response.novelty_score, 82);
+        assert_eq!(response.model.as_deref(), Some("structure-v2"));
+        assert_eq!(response.claim, "");
+        assert_eq!(response.claims.len(), 2);
+        assert_eq!(response.claims[1].depends_on, Some(1));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (head, sent) = requests[0].split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("POST /ai/structure "));
+        assert!(head.lines().any(|line| line.eq_ignore_ascii_case("x-api-key: secret-de-test")));
+        let sent: serde_json::Value = serde_json::from_str(sent).unwrap();
+        assert_eq!(sent, serde_json::json!({ "raw_idea": "une gourde qui filtre l'eau" }));
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_success_is_reported() {
+        let (base_url, requests) = fake_service(200, r#"{"title":"Sans le reste"}"#).await;
+        assert!(matches!(provider(&base_url, None).structure("idée").await, Err(AiError::InvalidResponse(_))));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_an_invalid_auth_header() {
+        let auth = Some(("en tête".to_string(), "jeton".to_string()));
+        assert!(matches!(HttpAiProvider::new("http://localhost", Duration::from_secs(1), 1, auth), Err(AiError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn mock_is_deterministic_and_valid() {
+        let provider = MockAiProvider::new(80);
+        let idea = "un panneau solaire orientable. Il suit le soleil grâce à un capteur. Il se replie la nuit";
+        let first = provider.structure(idea).await.unwrap();
+        let second = provider.structure(idea).await.unwrap();
+        assert_eq!(serde_json::to_value(&first).unwrap(), serde_json::to_value(&second).unwrap());
+
+        assert_eq!(first.title, "Un panneau solaire orientable");
+        assert_eq!(first.cpc_code, "H02J");
+        assert_eq!(first.novelty_score, 80);
+        let claims = claims::from_ai(first.claims, &first.claim).unwrap();
+        assert_eq!(claims.len(), 3);
+        assert_eq!(claims[2].depends_on, Some(1));
+    }
+
+    #[tokio::test]
+    async fn mock_streams_the_final_text() {
+        let provider = MockAiProvider::new(75);
+        let idea = "une gourde filtrante. Elle indique la qualité de l'eau";
+        let parts = std::sync::Mutex::new(Vec::new());
+        let response = provider
+            .structure_streaming(idea, &|partial| parts.lock().unwrap().push(partial))
+            .await
+            .unwrap();
+        let parts = parts.into_inner().unwrap();
+        let text = |field| parts.iter().filter(|p| p.field == field).map(|p| p.delta.as_str()).collect::<String>();
+        assert_eq!(text("title"), response.title);
+        assert_eq!(text("problem"), response.problem);
+        assert_eq!(text("solution"), response.solution);
+    }
+
+    #[tokio::test]
+    async fn mock_handles_empty_idea() {
+        let response = MockAiProvider::new(75).structure("   ").await.unwrap();
+        assert!(response.claims.is_empty());
+        assert!(claims::from_ai(response.claims, &response.claim).is_err());
+    }
+}
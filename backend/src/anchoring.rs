@@ -0,0 +1,308 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+use crate::inventor_signature::InventorSignature;
+use crate::ledger::{LedgerAnchor, LedgerError, LedgerResult, ReceiptStatus};
+use crate::merkle::{self, MerkleStep, MerkleTree};
+use crate::models::{MerkleProof, Proof, ProofJob};
+
+// Ancrage des empreintes sur le registre configuré :
+// - direct : une transaction par preuve
+// - batch  : les empreintes sont accumulées pendant une fenêtre puis seule la racine de Merkle est soumise
+// Une preuve co-signée par l'inventeur est toujours ancrée directement, pour que sa signature figure sur le topic.
+// Comme l'identifiant d'une soumission directe, le lot est enregistré sur ses tâches avant d'être soumis.
+
+pub struct MerkleInclusion {
+    pub root: String,
+    pub leaf_index: usize,
+    pub path: Vec<MerkleStep>,
+}
+
+impl MerkleInclusion {
+    /// Inclusion dans le lot enregistré sur une tâche avant sa soumission.
+    pub fn of_job(job: &ProofJob) -> Option<Self> {
+        Some(MerkleInclusion {
+            root: job.merkle_root.clone()?,
+            leaf_index: usize::try_from(job.merkle_leaf_index?).ok()?,
+            path: serde_json::from_value(job.merkle_path.clone()?).ok()?,
+        })
+    }
+}
+
+pub struct AnchorReceipt {
+    pub transaction_id: String,
+    pub topic_sequence_number: u64,
+    pub merkle: Option<MerkleInclusion>,
+}
+
+pub struct Anchoring {
+    ledger: Arc<dyn LedgerAnchor>,
+    batcher: Option<ProofBatcher>,
+}
+
+impl Anchoring {
+    /// Mode choisi par `ANCHOR_MODE` (`direct` par défaut, ou `batch`).
+    pub fn from_env(ledger: Arc<dyn LedgerAnchor>, pool: PgPool) -> Self {
+        match env::var("ANCHOR_MODE").as_deref() {
+            Ok("batch") => {
+                let window = env::var("ANCHOR_BATCH_WINDOW_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10);
+                let max_leaves = env::var("ANCHOR_BATCH_MAX_LEAVES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(256);
+                println!("⛓️  Ancrage par lots Merkle (fenêtre {}s, {} feuilles max)", window, max_leaves);
+                Self::batched(ledger, pool, Duration::from_secs(window), max_leaves)
+            }
+            _ => Self::direct(ledger),
+        }
+    }
+
+    pub fn direct(ledger: Arc<dyn LedgerAnchor>) -> Self {
+        Anchoring { ledger, batcher: None }
+    }
+
+    pub fn batched(ledger: Arc<dyn LedgerAnchor>, pool: PgPool, window: Duration, max_leaves: usize) -> Self {
+        let batcher = ProofBatcher::start(ledger.clone(), pool, window, max_leaves);
+        Anchoring { ledger, batcher: Some(batcher) }
+    }
+
+    pub fn ledger(&self) -> &Arc<dyn LedgerAnchor> {
+        &self.ledger
+    }
+
+    /// Identifiant à enregistrer avant la soumission directe.
+    /// En mode batch, l'identifiant appartient au lot et n'est connu qu'à sa fermeture.
+    pub fn prepare_transaction_id(&self, co_signed: bool) -> LedgerResult<Option<String>> {
+        match self.batcher {
+            Some(_) if !co_signed => Ok(None),
+            _ => self.ledger.new_transaction_id().map(Some),
+        }
+    }
+
+    /// Reçu d'une soumission déjà tentée, si elle a atteint le consensus avec succès.
+    /// `merkle` est l'inclusion de la preuve quand la transaction est celle de son lot.
+    pub async fn recover(&self, transaction_id: &str, merkle: Option<MerkleInclusion>) -> LedgerResult<Option<AnchorReceipt>> {
+        let receipt = self.ledger.fetch_receipt(transaction_id).await?;
+        Ok(match receipt.status {
+            ReceiptStatus::Success => Some(AnchorReceipt {
+                transaction_id: receipt.transaction_id,
+                topic_sequence_number: receipt.topic_sequence_number,
+                merkle,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Resoumet sous le même identifiant un lot enregistré dont la soumission a été interrompue.
+    /// Le registre ne l'ancre qu'une fois, quelle que soit la tâche du lot qui le resoumet.
+    pub async fn resubmit_batch(
+        &self,
+        transaction_id: &str,
+        message: &str,
+        merkle: MerkleInclusion,
+    ) -> LedgerResult<AnchorReceipt> {
+        let submission = self.ledger.submit_message(transaction_id, message.as_bytes().to_vec()).await?;
+        Ok(AnchorReceipt {
+            transaction_id: submission.transaction_id,
+            topic_sequence_number: submission.topic_sequence_number,
+            merkle: Some(merkle),
+        })
+    }
+
+    pub async fn anchor(
+        &self,
+        job: &ProofJob,
+        cpc_code: String,
+        created_at: DateTime<Utc>,
+        inventor: Option<&InventorSignature>,
+        transaction_id: Option<String>,
+    ) -> LedgerResult<AnchorReceipt> {
+        if let Some(batcher) = &self.batcher
+            && inventor.is_none()
+        {
+            return batcher.anchor(job).await;
+        }
+        let transaction_id = match transaction_id {
+            Some(id) => id,
+            None => self.ledger.new_transaction_id()?,
+        };
+
+        let mut message = json!({
+            "hash": job.hash,
+            "cpc_code": cpc_code,
+            "created_at": created_at.to_rfc3339(),
+        });
+        if let Some(inventor) = inventor {
+            message["inventor"] = inventor.payload();
+        }
+        let message = message.to_string();
+
+        let submission = self.ledger.submit_message(&transaction_id, message.into_bytes()).await?;
+        Ok(AnchorReceipt {
+            transaction_id: submission.transaction_id,
+            topic_sequence_number: submission.topic_sequence_number,
+            merkle: None,
+        })
+    }
+}
+
+struct PendingLeaf {
+    summary_id: Uuid,
+    attempts: i32,
+    hash: String,
+    respond: oneshot::Sender<Result<AnchorReceipt, String>>,
+}
+
+pub struct ProofBatcher {
+    sender: mpsc::Sender<PendingLeaf>,
+}
+
+impl ProofBatcher {
+    pub fn start(ledger: Arc<dyn LedgerAnchor>, pool: PgPool, window: Duration, max_leaves: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(max_leaves.max(1) * 4);
+        tokio::spawn(run_batches(ledger, pool, receiver, window, max_leaves.max(1)));
+        ProofBatcher { sender }
+    }
+
+    /// Attend la fermeture du lot contenant l'empreinte de `job` et renvoie son chemin d'inclusion.
+    pub async fn anchor(&self, job: &ProofJob) -> LedgerResult<AnchorReceipt> {
+        let (respond, response) = oneshot::channel();
+        let leaf = PendingLeaf { summary_id: job.summary_id, attempts: job.attempts, hash: job.hash.clone(), respond };
+        self.sender
+            .send(leaf)
+            .await
+            .map_err(|_| LedgerError::Batch("batcher d'ancrage arrêté".to_string()))?;
+        let receipt = response
+            .await
+            .map_err(|_| LedgerError::Batch("lot d'ancrage abandonné".to_string()))?
+            .map_err(LedgerError::Batch)?;
+        Ok(receipt)
+    }
+}
+
+async fn run_batches(
+    ledger: Arc<dyn LedgerAnchor>,
+    pool: PgPool,
+    mut receiver: mpsc::Receiver<PendingLeaf>,
+    window: Duration,
+    max_leaves: usize,
+) {
+    // Le premier élément ouvre la fenêtre, les suivants s'y ajoutent jusqu'à expiration ou saturation
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + window;
+        while batch.len() < max_leaves {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(leaf)) => batch.push(leaf),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        submit_batch(ledger.as_ref(), &pool, batch).await;
+    }
+}
+
+async fn submit_batch(ledger: &dyn LedgerAnchor, pool: &PgPool, batch: Vec<PendingLeaf>) {
+    let leaves: Vec<String> = batch.iter().map(|leaf| leaf.hash.clone()).collect();
+
+    let tree = match MerkleTree::from_hex_leaves(&leaves) {
+        Ok(tree) => tree,
+        Err(e) => {
+            for leaf in batch {
+                let _ = leaf.respond.send(Err(format!("Empreinte invalide dans le lot: {}", e)));
+            }
+            return;
+        }
+    };
+    let root = tree.root_hex();
+
+    let message = json!({
+        "type": "merkle_batch",
+        "merkle_root": root,
+        "leaf_count": leaves.len(),
+        "created_at": Utc::now().to_rfc3339(),
+    }).to_string();
+
+    let submission = match ledger.new_transaction_id() {
+        Ok(transaction_id) => match record_batch(pool, &batch, &tree, &root, &transaction_id, &message).await {
+            Ok(()) => ledger
+                .submit_message(&transaction_id, message.into_bytes())
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Enregistrement du lot impossible: {}", e)),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    match submission {
+        Ok(submission) => {
+            println!("⛓️  Lot Merkle ancré: {} feuilles, racine {}", leaves.len(), root);
+            for (index, leaf) in batch.into_iter().enumerate() {
+                let _ = leaf.respond.send(Ok(AnchorReceipt {
+                    transaction_id: submission.transaction_id.clone(),
+                    topic_sequence_number: submission.topic_sequence_number,
+                    merkle: Some(MerkleInclusion {
+                        root: root.clone(),
+                        leaf_index: index,
+                        path: tree.inclusion_path(index),
+                    }),
+                }));
+            }
+        }
+        Err(e) => {
+            eprintln!("Échec ancrage du lot Merkle: {}", e);
+            for leaf in batch {
+                let _ = leaf.respond.send(Err(e.clone()));
+            }
+        }
+    }
+}
+
+// Identifiant, message et inclusion de chaque feuille écrits sur les tâches du lot avant la soumission
+async fn record_batch(
+    pool: &PgPool,
+    batch: &[PendingLeaf],
+    tree: &MerkleTree,
+    root: &str,
+    transaction_id: &str,
+    message: &str,
+) -> Result<(), sqlx::Error> {
+    let summary_ids: Vec<Uuid> = batch.iter().map(|leaf| leaf.summary_id).collect();
+    let attempts: Vec<i32> = batch.iter().map(|leaf| leaf.attempts).collect();
+    let leaf_indexes: Vec<i32> = (0..batch.len() as i32).collect();
+    let paths: Vec<serde_json::Value> = (0..batch.len()).map(|index| json!(tree.inclusion_path(index))).collect();
+    // Une tâche reprise par un autre worker garde son propre état ; sa feuille reste dans le lot sans lui être attribuée
+    sqlx::query!(
+        "UPDATE proof_jobs j
+         SET transaction_id = $1, batch_message = $2, merkle_root = $3,
+             merkle_leaf_index = leaf.leaf_index, merkle_path = leaf.path, updated_at = $8
+         FROM UNNEST($4::uuid[], $5::int4[], $6::int4[], $7::jsonb[]) AS leaf(summary_id, attempts, leaf_index, path)
+         WHERE j.summary_id = leaf.summary_id AND j.status = 'pending' AND j.attempts = leaf.attempts",
+        transaction_id,
+        message,
+        root,
+        &summary_ids,
+        &attempts,
+        &leaf_indexes,
+        &paths,
+        Utc::now()
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Chemin d'inclusion stocké sur une preuve ancrée par lot, revérifié contre la racine.
+pub fn merkle_proof(proof: &Proof) -> Option<MerkleProof> {
+    let root = proof.merkle_root.clone()?;
+    let leaf_index = proof.merkle_leaf_index?;
+    let path: Vec<MerkleStep> = serde_json::from_value(proof.merkle_path.clone()?).ok()?;
+    let verified = merkle::verify_inclusion(&proof.hash, &path, &root);
+    Some(MerkleProof { root, leaf_index, path, verified })
+}
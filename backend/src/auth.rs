@@ -0,0 +1,156 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, ResponseError, web};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::env;
+use std::future::{Ready, ready};
+use uuid::Uuid;
+use crate::error::AppError;
+
+// Sessions signées : `v1.<charge utile base64url>.<HMAC-SHA256 base64url>`.
+// Sans état côté serveur ; la clé vient de `SESSION_SECRET` et la durée de `SESSION_TTL_SECS` (24 h).
+
+const TOKEN_VERSION: &str = "v1";
+
+#[derive(Serialize, Deserialize)]
+struct SessionClaims {
+    sub: Uuid,
+    iat: i64,
+    exp: i64,
+}
+
+pub struct SessionKeys {
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl SessionKeys {
+    pub fn from_env() -> Self {
+        let secret = match env::var("SESSION_SECRET") {
+            Ok(secret) if secret.len() >= 32 => secret.into_bytes(),
+            Ok(_) => panic!("SESSION_SECRET doit faire au moins 32 caractères"),
+            Err(_) => {
+                // Développement : les sessions ne survivent pas au redémarrage
+                eprintln!("⚠️  SESSION_SECRET absent, clé de session éphémère");
+                let mut secret = vec![0u8; 32];
+                OsRng.fill_bytes(&mut secret);
+                secret
+            }
+        };
+        let ttl = env::var("SESSION_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86_400);
+        SessionKeys { secret, ttl: Duration::seconds(ttl) }
+    }
+
+    fn sign(&self, data: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepte toute taille de clé");
+        mac.update(data.as_bytes());
+        mac
+    }
+
+    /// Jeton de session pour `user_id` et son expiration.
+    pub fn issue(&self, user_id: Uuid) -> (String, DateTime<Utc>) {
+        let now = Utc::now();
+        let expires_at = now + self.ttl;
+        let claims = SessionClaims { sub: user_id, iat: now.timestamp(), exp: expires_at.timestamp() };
+        let payload = format!(
+            "{}.{}",
+            TOKEN_VERSION,
+            BASE64URL.encode(serde_json::to_vec(&claims).expect("sérialisation des claims"))
+        );
+        let signature = BASE64URL.encode(self.sign(&payload).finalize().into_bytes());
+        (format!("{}.{}", payload, signature), expires_at)
+    }
+
+    /// Utilisateur d'un jeton valide et non expiré.
+    pub fn verify(&self, token: &str) -> Option<Uuid> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let (version, claims) = payload.split_once('.')?;
+        if version != TOKEN_VERSION {
+            return None;
+        }
+        // Comparaison en temps constant
+        self.sign(payload).verify_slice(&BASE64URL.decode(signature).ok()?).ok()?;
+        let claims: SessionClaims = serde_json::from_slice(&BASE64URL.decode(claims).ok()?).ok()?;
+        (claims.exp > Utc::now().timestamp()).then_some(claims.sub)
+    }
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+// Hash d'un mot de passe aléatoire oublié, aux paramètres de `hash_password`
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$LL+zE19hXiq0yXpRLF1WlA$95gFeAo/fEjF/cECeOvTjvgA/dWEBrqHMn1LCN6SDGA";
+
+/// Sans hash (e-mail inconnu, compte sans mot de passe), le mot de passe est tout de même vérifié contre
+/// un hash factice : la durée de la réponse ne révèle pas si le compte existe.
+pub fn verify_password_or_dummy(password: &str, hash: Option<&str>) -> bool {
+    match hash {
+        Some(hash) => verify_password(password, hash),
+        None => {
+            verify_password(password, DUMMY_PASSWORD_HASH);
+            false
+        }
+    }
+}
+
+/// Utilisateur authentifié par `require_session`, à utiliser comme paramètre de handler.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser(pub Uuid);
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<AuthenticatedUser>()
+                .copied()
+                .ok_or_else(|| AppError::Unauthorized("error.session_required".into()).into()),
+        )
+    }
+}
+
+/// Middleware des routes protégées : exige `Authorization: Bearer <jeton>`.
+pub async fn require_session(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let user_id = req
+        .app_data::<web::Data<SessionKeys>>()
+        .zip(req.headers().get("Authorization"))
+        .and_then(|(keys, header)| {
+            let token = header.to_str().ok()?.strip_prefix("Bearer ")?;
+            keys.verify(token.trim())
+        });
+
+    match user_id {
+        Some(user_id) => {
+            req.extensions_mut().insert(AuthenticatedUser(user_id));
+            Ok(next.call(req).await?.map_into_left_body())
+        }
+        None => Ok(req
+            .into_response(AppError::Unauthorized("error.session_invalid".into()).error_response())
+            .map_into_right_body()),
+    }
+}
@@ -0,0 +1,514 @@
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rect, Rgb,
+};
+use qrcode::{EcLevel, QrCode};
+use crate::i18n::Locale;
+use crate::models::Claim;
+
+// Certificat PDF d'une preuve ancrée, généré en Rust avec les polices standard PDF
+// (Helvetica / Courier, encodage WinAnsi : les accents français et espagnols sont pris en charge).
+// Libellés dans la langue du destinataire (`locale`), clés `certificate.*` du catalogue.
+// Mise en page A4 : bandeau de marque, sections, pagination automatique et pied de page "n / total".
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const HEADER_HEIGHT: f32 = 28.0;
+const FOOTER_HEIGHT: f32 = 15.0;
+const QR_SIZE: f32 = 32.0;
+
+const PT_TO_MM: f32 = 0.3528;
+
+// Chasses Helvetica (millièmes de cadratin, métriques AFM standard) des caractères ASCII 32 à 126 ;
+// une lettre accentuée a la chasse de sa lettre de base. Courier est à chasse fixe (600).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // espace à /
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0 à ?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @ à O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P à _
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // ` à o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p à ~
+];
+const COURIER_WIDTH: u16 = 600;
+
+/// Contenu du certificat, construit à partir des lignes `Proof`, `Summary` et `User`.
+pub struct CertificateData {
+    pub inventor_name: String,
+    pub inventor_wallet: String,
+    pub title: String,
+    pub problem: String,
+    pub solution: String,
+    pub claims: Vec<Claim>,
+    pub cpc_code: String,
+    pub hash: String,
+    pub hash_scheme: String,
+    pub transaction_id: String,
+    pub consensus_time: Option<String>,
+    pub registered_at: String,
+    pub network: String,
+    pub topic_sequence_number: Option<i64>,
+    pub merkle_root: Option<String>,
+    pub inventor_signature: Option<String>,
+    pub revision: i32,
+    pub previous_hash: Option<String>,
+    pub explorer_url: Option<String>,
+    pub verification_url: String,
+    pub document_verified: bool,
+    pub locale: Locale,
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+}
+
+// Curseur d'écriture : ouvre une nouvelle page quand la suivante ne tient plus
+struct Writer {
+    doc: PdfDocumentReference,
+    fonts: Fonts,
+    layers: Vec<PdfLayerReference>,
+    y: f32,
+    locale: Locale,
+}
+
+impl Writer {
+    fn t(&self, key: &'static str) -> &'static str {
+        self.locale.text(key)
+    }
+
+    fn layer(&self) -> &PdfLayerReference {
+        self.layers.last().expect("au moins une page")
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Contenu");
+        self.layers.push(self.doc.get_page(page).get_layer(layer));
+        self.draw_header(false);
+    }
+
+    fn draw_header(&mut self, first_page: bool) {
+        let layer = self.layer().clone();
+        let band = if first_page { HEADER_HEIGHT } else { 12.0 };
+        layer.set_fill_color(brand_color());
+        layer.add_rect(Rect::new(Mm(0.0), Mm(PAGE_HEIGHT - band), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT)));
+        layer.set_fill_color(white());
+        if first_page {
+            layer.use_text("BrevetChain", 22.0, Mm(MARGIN), Mm(PAGE_HEIGHT - 15.0), &self.fonts.bold);
+            layer.use_text(
+                self.t("certificate.tagline"),
+                10.0,
+                Mm(MARGIN),
+                Mm(PAGE_HEIGHT - 22.0),
+                &self.fonts.regular,
+            );
+        } else {
+            layer.use_text(self.t("certificate.continued"), 9.0, Mm(MARGIN), Mm(PAGE_HEIGHT - 8.0), &self.fonts.regular);
+        }
+        layer.set_fill_color(text_color());
+        self.y = PAGE_HEIGHT - band - 12.0;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN + FOOTER_HEIGHT {
+            self.new_page();
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.ensure_space(16.0);
+        self.y -= 4.0;
+        let layer = self.layer().clone();
+        layer.set_fill_color(brand_color());
+        layer.use_text(text, 13.0, Mm(MARGIN), Mm(self.y), &self.fonts.bold);
+        layer.set_fill_color(accent_color());
+        layer.add_rect(Rect::new(Mm(MARGIN), Mm(self.y - 2.0), Mm(PAGE_WIDTH - MARGIN), Mm(self.y - 1.6)));
+        layer.set_fill_color(text_color());
+        self.y -= 8.0;
+    }
+
+    /// Libellé en gras puis valeur, repliée sur la largeur utile.
+    fn field(&mut self, label: &str, value: &str, monospace: bool) {
+        let (font, size) = if monospace { (self.fonts.mono.clone(), 9.0) } else { (self.fonts.regular.clone(), 10.5) };
+        let lines = wrap(value, (PAGE_WIDTH - 2.0 * MARGIN) / (size * PT_TO_MM), monospace);
+        let line_height = size * PT_TO_MM * 1.45;
+
+        self.ensure_space(5.0 + line_height);
+        self.layer().use_text(label, 9.0, Mm(MARGIN), Mm(self.y), &self.fonts.bold);
+        self.y -= 5.0;
+        for line in lines {
+            self.ensure_space(line_height);
+            self.layer().use_text(line, size, Mm(MARGIN), Mm(self.y), &font);
+            self.y -= line_height;
+        }
+        self.y -= 2.5;
+    }
+
+    /// QR code de `url` à gauche, légende repliée à droite.
+    fn qr_code(&mut self, url: &str, caption: &str) -> Result<(), qrcode::types::QrError> {
+        let code = QrCode::with_error_correction_level(url, EcLevel::M)?;
+        // Marge blanche de 4 modules autour du code, exigée par les lecteurs
+        let width = code.width();
+        let module = QR_SIZE / (width + 8) as f32;
+        let colors = code.to_colors();
+
+        self.ensure_space(QR_SIZE + 4.0);
+        let layer = self.layer().clone();
+        let top = self.y + 3.0;
+        let left = MARGIN + 4.0 * module;
+        layer.set_fill_color(brand_color());
+        // Une bande par suite de modules sombres d'une même ligne
+        for (row, modules) in colors.chunks(width).enumerate() {
+            let y = top - (row + 4) as f32 * module;
+            let mut column = 0;
+            while column < width {
+                if modules[column] == qrcode::Color::Dark {
+                    let start = column;
+                    while column < width && modules[column] == qrcode::Color::Dark {
+                        column += 1;
+                    }
+                    layer.add_rect(Rect::new(
+                        Mm(left + start as f32 * module),
+                        Mm(y - module),
+                        Mm(left + column as f32 * module),
+                        Mm(y),
+                    ));
+                } else {
+                    column += 1;
+                }
+            }
+        }
+        layer.set_fill_color(text_color());
+
+        let text_left = MARGIN + QR_SIZE + 6.0;
+        let mut y = top - 4.0 * module - 3.0;
+        for line in wrap(caption, (PAGE_WIDTH - MARGIN - text_left) / (10.0 * PT_TO_MM), false) {
+            layer.use_text(line, 10.0, Mm(text_left), Mm(y), &self.fonts.regular);
+            y -= 10.0 * PT_TO_MM * 1.45;
+        }
+        self.y = top - QR_SIZE - 3.0;
+        Ok(())
+    }
+
+    fn footers(&self, generated_at: &str) {
+        let total = self.layers.len();
+        for (index, layer) in self.layers.iter().enumerate() {
+            layer.set_fill_color(muted_color());
+            layer.use_text(
+                self.locale.format("certificate.footer", &[("date", generated_at)]),
+                7.5,
+                Mm(MARGIN),
+                Mm(MARGIN - 8.0),
+                &self.fonts.regular,
+            );
+            layer.use_text(
+                self.locale.format("certificate.page", &[("page", &(index + 1).to_string()), ("total", &total.to_string())]),
+                7.5,
+                Mm(PAGE_WIDTH - MARGIN - 16.0),
+                Mm(MARGIN - 8.0),
+                &self.fonts.regular,
+            );
+        }
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb(Rgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, None))
+}
+
+// Couleurs de l'interface (theme-color #0a0f2c, accent #10b981)
+fn brand_color() -> Color {
+    rgb(0x0a, 0x0f, 0x2c)
+}
+
+fn accent_color() -> Color {
+    rgb(0x10, 0xb9, 0x81)
+}
+
+fn text_color() -> Color {
+    rgb(0x1f, 0x29, 0x37)
+}
+
+fn muted_color() -> Color {
+    rgb(0x6b, 0x72, 0x80)
+}
+
+fn white() -> Color {
+    rgb(0xff, 0xff, 0xff)
+}
+
+// Chasse d'un caractère en cadratins
+fn char_width(c: char, monospace: bool) -> f32 {
+    if monospace {
+        return COURIER_WIDTH as f32 / 1000.0;
+    }
+    let base = match c {
+        'à' | 'â' | 'ä' => 'a',
+        'À' | 'Â' | 'Ä' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'î' | 'ï' => 'i',
+        'Î' | 'Ï' => 'I',
+        'ô' | 'ö' => 'o',
+        'Ô' | 'Ö' => 'O',
+        'ù' | 'û' | 'ü' => 'u',
+        'Ù' | 'Û' | 'Ü' => 'U',
+        'á' => 'a',
+        'Á' => 'A',
+        'í' => 'i',
+        'Í' => 'I',
+        'ó' => 'o',
+        'Ó' => 'O',
+        'ú' => 'u',
+        'Ú' => 'U',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        'ç' => 'c',
+        'Ç' => 'C',
+        '¿' | '¡' => '?',
+        other => other,
+    };
+    let width = match base as u32 {
+        code @ 32..=126 => HELVETICA_WIDTHS[(code - 32) as usize],
+        _ => 1000, // caractère inconnu : majoration prudente
+    };
+    width as f32 / 1000.0
+}
+
+fn text_width(text: &str, monospace: bool) -> f32 {
+    text.chars().map(|c| char_width(c, monospace)).sum()
+}
+
+// Retour à la ligne par mots sur `max_width` cadratins ; un mot plus long que la ligne
+// (empreinte, signature, URL) est coupé, avec au moins un caractère par ligne
+fn wrap(text: &str, max_width: f32, monospace: bool) -> Vec<String> {
+    // Tolérance d'arrondi : une ligne exactement à la mesure tient sur la ligne
+    let max_width = max_width + 1e-3;
+    let space = char_width(' ', monospace);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0.0;
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            let mut width = text_width(&word, monospace);
+            while width > max_width && word.chars().nth(1).is_some() {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                let mut head_width = 0.0;
+                let split = word
+                    .char_indices()
+                    .find(|(_, c)| {
+                        head_width += char_width(*c, monospace);
+                        head_width > max_width
+                    })
+                    .map(|(index, _)| index)
+                    .filter(|&index| index > 0)
+                    .unwrap_or_else(|| word.chars().next().map_or(0, char::len_utf8));
+                let rest = word.split_off(split);
+                lines.push(word);
+                word = rest;
+                width = text_width(&word, monospace);
+            }
+            if !line.is_empty() && line_width + space + width > max_width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0.0;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+                line_width += space;
+            }
+            line.push_str(&word);
+            line_width += width;
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Rend le certificat et renvoie les octets du PDF.
+pub fn render(data: &CertificateData, generated_at: &str) -> Result<Vec<u8>, printpdf::Error> {
+    let locale = data.locale;
+    let t = |key| locale.text(key);
+    let (doc, page, layer) = PdfDocument::new(
+        locale.format("certificate.document_title", &[("title", &data.title)]),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Contenu",
+    );
+    let fonts = Fonts {
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        mono: doc.add_builtin_font(BuiltinFont::Courier)?,
+    };
+    let first_layer = doc.get_page(page).get_layer(layer);
+    let mut writer = Writer { doc, fonts, layers: vec![first_layer], y: 0.0, locale };
+    writer.draw_header(true);
+
+    writer.layer().use_text(t("certificate.heading"), 18.0, Mm(MARGIN), Mm(writer.y), &writer.fonts.bold);
+    writer.y -= 10.0;
+
+    writer.heading(t("certificate.section_inventor"));
+    writer.field(t("certificate.name"), &data.inventor_name, false);
+    writer.field(t("certificate.wallet"), &data.inventor_wallet, true);
+
+    writer.heading(t("certificate.section_invention"));
+    writer.field(t("certificate.title"), &data.title, false);
+    writer.field(t("certificate.revision"), &data.revision.to_string(), false);
+    writer.field(t("certificate.cpc"), &data.cpc_code, false);
+    writer.field(t("certificate.problem"), &data.problem, false);
+    writer.field(t("certificate.solution"), &data.solution, false);
+
+    writer.heading(t("certificate.section_claims"));
+    for claim in &data.claims {
+        let label = match claim.depends_on {
+            Some(parent) => locale.format(
+                "certificate.dependent_claim",
+                &[("number", &claim.number.to_string()), ("parent", &parent.to_string())],
+            ),
+            None => locale.format("certificate.independent_claim", &[("number", &claim.number.to_string())]),
+        };
+        writer.field(&label, &claim.text, false);
+    }
+
+    writer.heading(t("certificate.section_anchor"));
+    writer.field(&locale.format("certificate.hash", &[("scheme", &data.hash_scheme)]), &data.hash, true);
+    writer.field(
+        t("certificate.content_matches"),
+        t(if data.document_verified { "certificate.yes" } else { "certificate.changed_since_anchoring" }),
+        false,
+    );
+    if let Some(previous_hash) = &data.previous_hash {
+        writer.field(t("certificate.previous_hash"), previous_hash, true);
+    }
+    writer.field(t("certificate.network"), &data.network, false);
+    writer.field(t("certificate.transaction"), &data.transaction_id, true);
+    // Tant que le mirror node ne l'a pas confirmé, aucun horodatage n'est présenté comme celui du consensus
+    let consensus_time = match &data.consensus_time {
+        Some(consensus_time) => consensus_time.clone(),
+        None => locale.format("certificate.consensus_pending", &[("date", &data.registered_at)]),
+    };
+    writer.field(t("certificate.consensus_time"), &consensus_time, false);
+    if let Some(sequence) = data.topic_sequence_number {
+        writer.field(t("certificate.topic_sequence"), &sequence.to_string(), false);
+    }
+    if let Some(root) = &data.merkle_root {
+        writer.field(t("certificate.merkle_root"), root, true);
+    }
+    if let Some(signature) = &data.inventor_signature {
+        writer.field(t("certificate.inventor_signature"), signature, true);
+    }
+
+    writer.heading(t("certificate.section_verification"));
+    if let Err(e) = writer.qr_code(&data.verification_url, t("certificate.qr_caption")) {
+        eprintln!("QR code de vérification non généré: {}", e);
+    }
+    writer.field(t("certificate.verify_link"), &data.verification_url, true);
+    if let Some(explorer_url) = &data.explorer_url {
+        writer.field(t("certificate.explorer"), explorer_url, true);
+    }
+
+    writer.footers(generated_at);
+    writer.doc.save_to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use printpdf::lopdf;
+
+    fn widths(lines: &[String], monospace: bool) -> Vec<f32> {
+        lines.iter().map(|line| text_width(line, monospace)).collect()
+    }
+
+    #[test]
+    fn long_words_are_cut_to_the_line_width() {
+        let hash = "ab".repeat(32);
+        let lines = wrap(&hash, 10.0, true);
+        assert_eq!(lines.concat(), hash);
+        assert!(lines.len() > 1);
+        assert!(widths(&lines, true).iter().all(|w| *w <= 10.0));
+    }
+
+    #[test]
+    fn a_line_that_fits_exactly_is_not_wrapped() {
+        let text = "abc def";
+        let width = text_width(text, true);
+        assert_eq!(wrap(text, width, true), vec![text.to_string()]);
+        assert_eq!(wrap(text, width - 0.01, true), vec!["abc".to_string(), "def".to_string()]);
+        let text = "Brevet déposé";
+        assert_eq!(wrap(text, text_width(text, false), false), vec![text.to_string()]);
+        assert_eq!(wrap("", 10.0, false), vec![String::new()]);
+    }
+
+    #[test]
+    fn accented_text_wraps_on_words_without_splitting_characters() {
+        let text = "Élément révélé à l'équipe : ñandú, açaí et pingüino";
+        let lines = wrap(text, 8.0, false);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.join(" "), text);
+        assert!(widths(&lines, false).iter().all(|w| *w <= 8.0));
+        // Une URL accentuée coupée en plein mot garde des caractères entiers
+        let word = "é".repeat(40);
+        assert_eq!(wrap(&word, 3.0, false).concat(), word);
+    }
+
+    #[test]
+    fn a_line_narrower_than_one_character_still_progresses() {
+        assert_eq!(wrap("abc", 0.1, true), vec!["a", "b", "c"]);
+        assert_eq!(wrap("é", 0.0, false), vec!["é"]);
+    }
+
+    #[test]
+    fn renders_a_parseable_pdf_with_the_proof_hash() {
+        let hash = "3e0e1524fdb938de54be6cd47aad130dd44662aafb7d6402f45058619a2a5ac6".to_string();
+        let data = CertificateData {
+            inventor_name: "Inès Müller".to_string(),
+            inventor_wallet: "0.0.4242".to_string(),
+            title: "Capteur solaire pliable".to_string(),
+            problem: "Les panneaux fixes perdent du rendement.".to_string(),
+            solution: "Un capteur qui suit le soleil.".to_string(),
+            claims: vec![Claim { number: 1, depends_on: None, text: "Capteur pliable orientable.".to_string() }],
+            cpc_code: "H02S".to_string(),
+            hash: hash.clone(),
+            hash_scheme: "jcs-v3".to_string(),
+            transaction_id: "0.0.2@1700000000.000000001".to_string(),
+            consensus_time: None,
+            registered_at: "2026-10-18 10:00:00 UTC".to_string(),
+            network: "simulated".to_string(),
+            topic_sequence_number: Some(1),
+            merkle_root: None,
+            inventor_signature: None,
+            revision: 1,
+            previous_hash: None,
+            explorer_url: None,
+            verification_url: "http://127.0.0.1:8080/verify/00000000-0000-0000-0000-000000000000".to_string(),
+            document_verified: true,
+            locale: Locale::DEFAULT,
+        };
+        let pdf = render(&data, "2026-10-18 10:00 UTC").unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+
+        let document = lopdf::Document::load_mem(&pdf).unwrap();
+        let pages = document.get_pages();
+        assert!(!pages.is_empty());
+        let content: Vec<u8> = pages
+            .values()
+            .flat_map(|page| document.get_page_content(*page).unwrap())
+            .collect();
+        // Chaînes affichées (`<hex> Tj`) décodées en WinAnsi ; les lignes d'une empreinte coupée se suivent
+        let shown: String = String::from_utf8_lossy(&content)
+            .split('<')
+            .filter_map(|part| part.split_once("> Tj"))
+            .filter_map(|(text, _)| hex::decode(text).ok())
+            .flat_map(|bytes| bytes.into_iter().map(char::from))
+            .collect();
+        assert!(shown.contains(&hash));
+        assert!(shown.contains("Inès Müller"));
+    }
+}
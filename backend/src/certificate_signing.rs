@@ -0,0 +1,264 @@
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Acquire, PgPool, Postgres};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::encryption::{self, CryptoResult};
+
+// Signature des certificats par la plateforme : JWS Ed25519 (`alg` EdDSA), vérifiable hors ligne
+// avec le JWKS publié sur `/.well-known/jwks.json`. La clé privée (32 octets en hex) est lue depuis
+// `CERTIFICATE_SIGNING_KEY_FILE` (défaut `keys/certificate_signing.key`) ; les clés publiques sont
+// enregistrées en base et restent publiées après une rotation.
+
+pub const ALGORITHM: &str = "EdDSA";
+
+pub fn signing_key_path() -> PathBuf {
+    PathBuf::from(
+        env::var("CERTIFICATE_SIGNING_KEY_FILE").unwrap_or_else(|_| "keys/certificate_signing.key".to_string()),
+    )
+}
+
+pub struct CertificateSigner {
+    kid: String,
+    key: SigningKey,
+}
+
+impl CertificateSigner {
+    pub fn load(path: &Path) -> CryptoResult<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Clé de signature illisible ({}): {}", path.display(), e))?;
+        let seed: [u8; 32] = hex::decode(contents.trim())?
+            .try_into()
+            .map_err(|_| "La clé de signature doit faire 32 octets")?;
+        let key = SigningKey::from_bytes(&seed);
+        Ok(CertificateSigner { kid: thumbprint(&public_key_b64(&key)), key })
+    }
+
+    /// Génère une nouvelle clé de signature et l'écrit dans `path`.
+    pub fn generate(path: &Path) -> CryptoResult<Self> {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        encryption::write_private(path, &hex::encode(seed))?;
+        Self::load(path)
+    }
+
+    /// Clé du serveur : créée au premier démarrage, puis publiée dans le JWKS.
+    pub async fn from_env(pool: &PgPool) -> CryptoResult<Self> {
+        let path = signing_key_path();
+        let signer = if path.exists() {
+            Self::load(&path)?
+        } else {
+            let signer = Self::generate(&path)?;
+            println!("🖋️  Nouvelle clé de signature des certificats {} écrite dans {}", signer.kid(), path.display());
+            signer
+        };
+        signer.publish(pool).await?;
+        println!("🖋️  Certificats signés avec la clé {}", signer.kid());
+        Ok(signer)
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// Enregistre la clé publique ; les autres clés sont marquées retirées mais restent publiées.
+    pub async fn publish<'c>(&self, conn: impl Acquire<'c, Database = Postgres>) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        let mut tx = conn.begin().await?;
+        sqlx::query!(
+            "INSERT INTO certificate_signing_keys (kid, algorithm, public_key, created_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (kid) DO UPDATE SET retired_at = NULL",
+            self.kid,
+            ALGORITHM,
+            public_key_b64(&self.key),
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE certificate_signing_keys SET retired_at = $2 WHERE kid <> $1 AND retired_at IS NULL",
+            self.kid,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    fn sign_parts<T: Serialize>(&self, claims: &T) -> Result<(String, String, String), serde_json::Error> {
+        let header = json!({"alg": ALGORITHM, "kid": self.kid, "typ": "JOSE"});
+        let header = BASE64URL.encode(serde_jcs::to_vec(&header)?);
+        // Charge utile canonique (JCS) : le variant détaché se vérifie en recanonisant le JSON reçu
+        let payload = BASE64URL.encode(serde_jcs::to_vec(claims)?);
+        let signature = self.key.sign(format!("{}.{}", header, payload).as_bytes());
+        Ok((header, payload, BASE64URL.encode(signature.to_bytes())))
+    }
+
+    /// JWS compact `en-tête.charge.signature`.
+    pub fn sign_compact<T: Serialize>(&self, claims: &T) -> Result<String, serde_json::Error> {
+        let (header, payload, signature) = self.sign_parts(claims)?;
+        Ok(format!("{}.{}.{}", header, payload, signature))
+    }
+
+    /// JWS à charge détachée `en-tête..signature` (RFC 7515, annexe F) : la charge est le JSON
+    /// transmis à côté, sous sa forme canonique JCS.
+    pub fn sign_detached<T: Serialize>(&self, claims: &T) -> Result<String, serde_json::Error> {
+        let (header, _, signature) = self.sign_parts(claims)?;
+        Ok(format!("{}..{}", header, signature))
+    }
+}
+
+fn public_key_b64(key: &SigningKey) -> String {
+    BASE64URL.encode(key.verifying_key().as_bytes())
+}
+
+// Identifiant de clé : empreinte JWK (RFC 7638) de la clé publique
+fn thumbprint(x: &str) -> String {
+    let canonical = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#, x);
+    BASE64URL.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Jeu de clés publiques, de la plus récente à la plus ancienne.
+pub async fn jwks(conn: impl sqlx::PgExecutor<'_>) -> Result<serde_json::Value, sqlx::Error> {
+    let keys = sqlx::query!(
+        "SELECT kid, algorithm, public_key FROM certificate_signing_keys ORDER BY retired_at DESC NULLS FIRST, created_at DESC"
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(json!({
+        "keys": keys
+            .into_iter()
+            .map(|key| json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": key.public_key,
+                "kid": key.kid,
+                "alg": key.algorithm,
+                "use": "sig",
+            }))
+            .collect::<Vec<_>>()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, VerifyingKey};
+    use serde_json::Value;
+
+    async fn setup() -> PgPool {
+        dotenvy::dotenv().ok();
+        PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL")).await.unwrap()
+    }
+
+    fn new_signer() -> CertificateSigner {
+        let path = env::temp_dir().join(format!("certificate-signing-{}", uuid::Uuid::new_v4())).join("signing.key");
+        let signer = CertificateSigner::generate(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        signer
+    }
+
+    fn claims() -> Value {
+        json!({"summary_id": "00000000-0000-0000-0000-000000000001", "hash": "ab".repeat(32), "revision": 1})
+    }
+
+    // Vérification d'un tiers : clé choisie dans le JWKS par le `kid` de l'en-tête ; `detached` est
+    // la charge transmise à côté d'un JWS `en-tête..signature`
+    fn verify(jwks: &Value, jws: &str, detached: Option<&Value>) -> bool {
+        let [header, payload, signature] = jws.split('.').collect::<Vec<_>>()[..] else {
+            return false;
+        };
+        let header: Value = serde_json::from_slice(&BASE64URL.decode(header).unwrap()).unwrap();
+        assert_eq!(header["alg"], ALGORITHM);
+        let Some(jwk) = jwks["keys"].as_array().unwrap().iter().find(|key| key["kid"] == header["kid"]) else {
+            return false;
+        };
+        let x: [u8; 32] = BASE64URL.decode(jwk["x"].as_str().unwrap()).unwrap().try_into().unwrap();
+        let payload = match detached {
+            Some(claims) => BASE64URL.encode(serde_jcs::to_vec(claims).unwrap()),
+            None => payload.to_string(),
+        };
+        let signature = Signature::from_slice(&BASE64URL.decode(signature).unwrap()).unwrap();
+        let signing_input = format!("{}.{}", jws.split('.').next().unwrap(), payload);
+        VerifyingKey::from_bytes(&x).unwrap().verify_strict(signing_input.as_bytes(), &signature).is_ok()
+    }
+
+    #[tokio::test]
+    async fn compact_and_detached_signatures_verify_against_the_jwks() {
+        let pool = setup().await;
+        let mut tx = pool.begin().await.unwrap();
+        let signer = new_signer();
+        signer.publish(&mut *tx).await.unwrap();
+        let jwks = jwks(&mut *tx).await.unwrap();
+
+        let compact = signer.sign_compact(&claims()).unwrap();
+        assert!(verify(&jwks, &compact, None));
+        let payload = compact.split('.').nth(1).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&BASE64URL.decode(payload).unwrap()).unwrap(), claims());
+
+        let detached = signer.sign_detached(&claims()).unwrap();
+        assert!(detached.contains(".."));
+        // La charge est recanonisée : l'ordre et la mise en forme du JSON reçu sont indifférents
+        let received = format!(
+            r#"{{ "revision": 1, "hash": "{}", "summary_id": "00000000-0000-0000-0000-000000000001" }}"#,
+            "ab".repeat(32)
+        );
+        assert!(verify(&jwks, &detached, Some(&serde_json::from_str(&received).unwrap())));
+
+        // Une charge modifiée ou une autre clé ne vérifient pas
+        let mut tampered = claims();
+        tampered["revision"] = json!(2);
+        assert!(!verify(&jwks, &detached, Some(&tampered)));
+        assert!(!verify(&jwks, &new_signer().sign_compact(&claims()).unwrap(), None));
+    }
+
+    #[tokio::test]
+    async fn kid_is_the_rfc_7638_thumbprint() {
+        let pool = setup().await;
+        let mut tx = pool.begin().await.unwrap();
+        let signer = new_signer();
+        signer.publish(&mut *tx).await.unwrap();
+
+        let jwks = jwks(&mut *tx).await.unwrap();
+        let jwk = jwks["keys"].as_array().unwrap().iter().find(|key| key["kid"] == signer.kid()).unwrap();
+        // Membres requis d'une clé OKP, dans l'ordre lexicographique et sans espace
+        let required = json!({"crv": jwk["crv"], "kty": jwk["kty"], "x": jwk["x"]});
+        let expected = BASE64URL.encode(Sha256::digest(serde_jcs::to_vec(&required).unwrap()));
+        assert_eq!(signer.kid(), expected);
+    }
+
+    #[tokio::test]
+    async fn certificates_signed_with_a_retired_key_still_verify() {
+        let pool = setup().await;
+        let mut tx = pool.begin().await.unwrap();
+        let (retired, current) = (new_signer(), new_signer());
+        retired.publish(&mut *tx).await.unwrap();
+        let certificate = retired.sign_compact(&claims()).unwrap();
+
+        current.publish(&mut *tx).await.unwrap();
+        let retired_at = sqlx::query_scalar!("SELECT retired_at FROM certificate_signing_keys WHERE kid = $1", retired.kid())
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert!(retired_at.is_some());
+
+        let jwks = jwks(&mut *tx).await.unwrap();
+        assert_eq!(jwks["keys"][0]["kid"], current.kid());
+        assert!(verify(&jwks, &certificate, None));
+        assert!(verify(&jwks, &current.sign_compact(&claims()).unwrap(), None));
+    }
+}
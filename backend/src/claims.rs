@@ -0,0 +1,113 @@
+use crate::i18n::Message;
+use crate::models::{AiClaim, Claim};
+
+// Jeu de revendications : numérotation et règles de dépendance.
+// La revendication 1 est indépendante ; une revendication dépendante renvoie à une revendication
+// de numéro inférieur, ce qui rend impossibles les renvois en avant et les références circulaires.
+
+/// Numérote les revendications dans l'ordre reçu, à partir de 1.
+pub fn number(claims: Vec<AiClaim>) -> Vec<Claim> {
+    claims
+        .into_iter()
+        .zip(1..)
+        .map(|(claim, number)| Claim {
+            number,
+            depends_on: claim.depends_on,
+            text: claim.text.trim().to_string(),
+        })
+        .collect()
+}
+
+/// Revendications d'une réponse IA : jeu complet, ou revendication unique de l'ancien contrat.
+pub fn from_ai(claims: Vec<AiClaim>, single_claim: &str) -> Result<Vec<Claim>, Message> {
+    let claims = if claims.is_empty() && !single_claim.trim().is_empty() {
+        vec![Claim { number: 1, depends_on: None, text: single_claim.trim().to_string() }]
+    } else {
+        number(claims)
+    };
+    validate(&claims)?;
+    Ok(claims)
+}
+
+/// Vérifie la numérotation (1, 2, 3… sans trou) et les dépendances.
+pub fn validate(claims: &[Claim]) -> Result<(), Message> {
+    if claims.is_empty() {
+        return Err(Message::new("claims.empty"));
+    }
+    for (claim, expected) in claims.iter().zip(1..) {
+        if claim.number != expected {
+            return Err(Message::new("claims.unexpected_number").with("number", claim.number).with("expected", expected));
+        }
+        if claim.text.trim().is_empty() {
+            return Err(Message::new("claims.empty_text").with("number", claim.number));
+        }
+        match claim.depends_on {
+            None => {}
+            Some(parent) if parent == claim.number => {
+                return Err(Message::new("claims.self_dependency").with("number", claim.number));
+            }
+            Some(parent) if parent > claim.number => {
+                return Err(Message::new("claims.forward_dependency").with("number", claim.number).with("parent", parent));
+            }
+            Some(parent) if parent < 1 => {
+                return Err(Message::new("claims.unknown_parent").with("number", claim.number).with("parent", parent));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(number: i32, depends_on: Option<i32>) -> Claim {
+        Claim { number, depends_on, text: format!("Revendication {}", number) }
+    }
+
+    fn rejects_with(claims: &[Claim], reason: &str) {
+        let error = validate(claims).unwrap_err().to_string();
+        assert!(error.contains(reason), "{} : {}", reason, error);
+    }
+
+    #[test]
+    fn accepts_backward_dependencies() {
+        assert!(validate(&[claim(1, None), claim(2, Some(1)), claim(3, Some(2)), claim(4, None), claim(5, Some(1))]).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_dependencies() {
+        rejects_with(&[claim(1, None), claim(2, Some(3)), claim(3, None)], "renvoi en avant");
+        rejects_with(&[claim(1, Some(2)), claim(2, None)], "renvoi en avant");
+        rejects_with(&[claim(1, None), claim(2, Some(2))], "dépend d'elle-même");
+        rejects_with(&[claim(1, Some(1))], "dépend d'elle-même");
+        rejects_with(&[claim(1, None), claim(2, Some(0))], "inconnue");
+        rejects_with(&[claim(1, None), claim(2, Some(-1))], "inconnue");
+    }
+
+    #[test]
+    fn rejects_numbering_gaps() {
+        rejects_with(&[], "Au moins une");
+        rejects_with(&[claim(1, None), claim(3, Some(1))], "numéro attendu");
+        rejects_with(&[claim(2, None)], "numéro attendu");
+        rejects_with(&[claim(1, None), claim(1, None)], "numéro attendu");
+        rejects_with(&[claim(1, None), Claim { text: "  ".to_string(), ..claim(2, Some(1)) }], "texte vide");
+    }
+
+    #[test]
+    fn numbers_ai_claims_in_order() {
+        let claims = from_ai(
+            vec![
+                AiClaim { text: " Revendication principale ".to_string(), depends_on: None },
+                AiClaim { text: "Selon la revendication 1".to_string(), depends_on: Some(1) },
+            ],
+            "",
+        )
+        .unwrap();
+        assert_eq!(claims.iter().map(|c| c.number).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(claims[0].text, "Revendication principale");
+        // Ancien contrat : la revendication unique devient la revendication 1
+        assert_eq!(from_ai(Vec::new(), "Unique").unwrap()[0].text, "Unique");
+    }
+}
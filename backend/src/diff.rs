@@ -0,0 +1,273 @@
+use crate::models::{ClaimChange, ClaimDiff, DiffOp, FieldDiff, SummaryDocument, TextChange};
+
+// Différences entre deux révisions d'un résumé, mot par mot (plus longue sous-séquence commune).
+// Les préfixe et suffixe communs sont retirés d'abord : une correction ponctuelle reste bon marché
+// même sur un long texte.
+
+// Au-delà, la partie modifiée est rendue comme une suppression suivie d'un ajout
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+// Mots et blancs alternés, pour que le texte se reconstitue à l'identique
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (index, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|previous| previous != space) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+fn push(changes: &mut Vec<TextChange>, op: DiffOp, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match changes.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(text),
+        _ => changes.push(TextChange { op, text: text.to_string() }),
+    }
+}
+
+/// Diff mot par mot de `before` vers `after`.
+pub fn diff_text(before: &str, after: &str) -> Vec<TextChange> {
+    let a = tokens(before);
+    let b = tokens(after);
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (middle_a, middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut changes = Vec::new();
+    push(&mut changes, DiffOp::Equal, &a[..prefix].concat());
+
+    let (n, m) = (middle_a.len(), middle_b.len());
+    if n * m > MAX_TABLE_CELLS {
+        push(&mut changes, DiffOp::Delete, &middle_a.concat());
+        push(&mut changes, DiffOp::Insert, &middle_b.concat());
+    } else {
+        // lengths[i][j] : plus longue sous-séquence commune de middle_a[i..] et middle_b[j..]
+        let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i * (m + 1) + j] = if middle_a[i] == middle_b[j] {
+                    lengths[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && middle_a[i] == middle_b[j] {
+                push(&mut changes, DiffOp::Equal, middle_a[i]);
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1]) {
+                // À longueur égale, la suppression précède l'ajout
+                push(&mut changes, DiffOp::Delete, middle_a[i]);
+                i += 1;
+            } else {
+                push(&mut changes, DiffOp::Insert, middle_b[j]);
+                j += 1;
+            }
+        }
+    }
+
+    push(&mut changes, DiffOp::Equal, &a[a.len() - suffix..].concat());
+    changes
+}
+
+/// Champs modifiés entre deux révisions.
+pub fn diff_fields(before: &SummaryDocument, after: &SummaryDocument) -> Vec<FieldDiff> {
+    [
+        ("title", &before.title, &after.title),
+        ("problem", &before.problem, &after.problem),
+        ("solution", &before.solution, &after.solution),
+        ("cpc_code", &before.cpc_code, &after.cpc_code),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| FieldDiff { field: field.to_string(), changes: diff_text(before, after) })
+    .collect()
+}
+
+/// Revendications ajoutées, supprimées ou modifiées, comparées à numéro égal.
+pub fn diff_claims(before: &SummaryDocument, after: &SummaryDocument) -> Vec<ClaimDiff> {
+    let (before, after) = (before.claim_set(), after.claim_set());
+    let count = before.len().max(after.len());
+    (0..count)
+        .filter_map(|index| match (before.get(index), after.get(index)) {
+            (Some(old), Some(new)) if old == new => None,
+            (Some(old), Some(new)) => Some(ClaimDiff {
+                number: new.number,
+                change: ClaimChange::Modified,
+                depends_on_before: old.depends_on,
+                depends_on_after: new.depends_on,
+                changes: diff_text(&old.text, &new.text),
+            }),
+            (Some(old), None) => Some(ClaimDiff {
+                number: old.number,
+                change: ClaimChange::Removed,
+                depends_on_before: old.depends_on,
+                depends_on_after: None,
+                changes: diff_text(&old.text, ""),
+            }),
+            (None, Some(new)) => Some(ClaimDiff {
+                number: new.number,
+                change: ClaimChange::Added,
+                depends_on_before: None,
+                depends_on_after: new.depends_on,
+                changes: diff_text("", &new.text),
+            }),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Claim;
+
+    // Texte reconstitué à partir des segments retenus
+    fn rebuild(changes: &[TextChange], skipped: DiffOp) -> String {
+        changes.iter().filter(|change| change.op != skipped).map(|change| change.text.as_str()).collect()
+    }
+
+    fn document(claims: &[(i32, Option<i32>, &str)]) -> SummaryDocument {
+        SummaryDocument {
+            title: "Titre".to_string(),
+            problem: "Problème".to_string(),
+            solution: "Solution".to_string(),
+            claim: String::new(),
+            cpc_code: "A01B".to_string(),
+            claims: claims
+                .iter()
+                .map(|&(number, depends_on, text)| Claim { number, depends_on, text: text.to_string() })
+                .collect(),
+            previous_hash: None,
+        }
+    }
+
+    #[test]
+    fn segments_rebuild_both_texts() {
+        let before = "Un capteur  mesure la température\tdu sol et transmet la valeur.";
+        let after = "Un capteur optique mesure l'humidité du sol\net transmet chaque valeur.";
+        let changes = diff_text(before, after);
+        assert_eq!(rebuild(&changes, DiffOp::Delete), after);
+        assert_eq!(rebuild(&changes, DiffOp::Insert), before);
+        // Segments de même nature fusionnés
+        assert!(changes.windows(2).all(|pair| pair[0].op != pair[1].op));
+    }
+
+    #[test]
+    fn identical_and_empty_texts() {
+        let changes = diff_text("même texte", "même texte");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].op, DiffOp::Equal);
+        assert!(diff_text("", "").is_empty());
+
+        let added = diff_text("", "nouveau texte");
+        assert_eq!(added.len(), 1);
+        assert_eq!((added[0].op, added[0].text.as_str()), (DiffOp::Insert, "nouveau texte"));
+    }
+
+    #[test]
+    fn a_replaced_word_is_deleted_before_it_is_inserted() {
+        let changes = diff_text("le capteur rouge mesure", "le capteur bleu mesure");
+        let ops: Vec<(DiffOp, &str)> = changes.iter().map(|change| (change.op, change.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "le capteur "),
+                (DiffOp::Delete, "rouge"),
+                (DiffOp::Insert, "bleu"),
+                (DiffOp::Equal, " mesure"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_large_change_falls_back_to_delete_then_insert() {
+        // Plus de MAX_TABLE_CELLS cellules entre les parties modifiées
+        let words = |prefix: &str| (0..2_100).map(|i| format!("{}{}", prefix, i)).collect::<Vec<_>>().join(" ");
+        let before = format!("début {} fin", words("a"));
+        let after = format!("début {} fin", words("b"));
+        assert!(tokens(&words("a")).len() * tokens(&words("b")).len() > MAX_TABLE_CELLS);
+
+        let changes = diff_text(&before, &after);
+        let ops: Vec<DiffOp> = changes.iter().map(|change| change.op).collect();
+        assert_eq!(ops, vec![DiffOp::Equal, DiffOp::Delete, DiffOp::Insert, DiffOp::Equal]);
+        assert_eq!(changes[1].text, words("a"));
+        assert_eq!(changes[2].text, words("b"));
+        assert_eq!(rebuild(&changes, DiffOp::Delete), after);
+        assert_eq!(rebuild(&changes, DiffOp::Insert), before);
+    }
+
+    #[test]
+    fn only_changed_fields_are_reported() {
+        let before = document(&[]);
+        let mut after = before.clone();
+        after.solution = "Solution améliorée".to_string();
+        let fields = diff_fields(&before, &after);
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, "solution");
+        assert!(diff_fields(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn claims_are_reported_as_added_removed_or_modified() {
+        let before = document(&[
+            (1, None, "Dispositif comprenant un capteur."),
+            (2, Some(1), "Dispositif selon la revendication 1, étanche."),
+        ]);
+        let modified = document(&[
+            (1, None, "Dispositif comprenant un capteur."),
+            (2, Some(1), "Dispositif selon la revendication 1, étanche et solaire."),
+            (3, Some(2), "Procédé de mesure utilisant le dispositif."),
+        ]);
+
+        let diffs = diff_claims(&before, &modified);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!((diffs[0].number, diffs[0].change), (2, ClaimChange::Modified));
+        assert_eq!((diffs[0].depends_on_before, diffs[0].depends_on_after), (Some(1), Some(1)));
+        assert_eq!(rebuild(&diffs[0].changes, DiffOp::Delete), modified.claims[1].text);
+        assert_eq!((diffs[1].number, diffs[1].change), (3, ClaimChange::Added));
+        assert_eq!((diffs[1].depends_on_before, diffs[1].depends_on_after), (None, Some(2)));
+        assert_eq!(rebuild(&diffs[1].changes, DiffOp::Delete), modified.claims[2].text);
+
+        let removed = diff_claims(&modified, &before);
+        assert_eq!((removed[1].number, removed[1].change), (3, ClaimChange::Removed));
+        assert_eq!((removed[1].depends_on_before, removed[1].depends_on_after), (Some(2), None));
+        assert_eq!(rebuild(&removed[1].changes, DiffOp::Insert), modified.claims[2].text);
+
+        // Une dépendance modifiée suffit
+        let mut redirected = before.clone();
+        redirected.claims[1].depends_on = None;
+        let diffs = diff_claims(&before, &redirected);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].change, diffs[0].depends_on_after), (ClaimChange::Modified, None));
+    }
+
+    #[test]
+    fn a_legacy_single_claim_is_compared_as_claim_one() {
+        let mut before = document(&[]);
+        before.claim = "Dispositif comprenant un capteur.".to_string();
+        let after = document(&[(1, None, "Dispositif comprenant deux capteurs.")]);
+        let diffs = diff_claims(&before, &after);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].number, diffs[0].change), (1, ClaimChange::Modified));
+    }
+}
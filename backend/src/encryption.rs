@@ -0,0 +1,333 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+// Chiffrement au repos par enveloppe : chaque utilisateur a sa clé de données AES-256-GCM,
+// stockée dans `user_data_keys` enveloppée par la clé maître. La clé maître (32 octets en hex)
+// est lue depuis `MASTER_KEY_FILE` (défaut `keys/master.key`) et ne quitte jamais le serveur.
+
+pub type CryptoResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Préfixe des valeurs chiffrées ; une valeur sans préfixe est un texte clair antérieur au chiffrement
+const SEALED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+pub fn master_key_path() -> PathBuf {
+    PathBuf::from(env::var("MASTER_KEY_FILE").unwrap_or_else(|_| "keys/master.key".to_string()))
+}
+
+// Fichiers voisins utilisés pendant une rotation : `<fichier>.next` puis `<fichier>.previous`
+pub fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", suffix));
+    PathBuf::from(name)
+}
+
+pub struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl MasterKey {
+    fn from_hex(value: &str) -> CryptoResult<Self> {
+        let bytes = hex::decode(value.trim())?;
+        if bytes.len() != 32 {
+            return Err("La clé maître doit faire 32 octets".into());
+        }
+        // Identifiant public de la clé : empreinte SHA-256 tronquée
+        let id = hex::encode(&Sha256::digest(&bytes)[..8]);
+        Ok(MasterKey { id, cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)) })
+    }
+
+    pub fn load(path: &Path) -> CryptoResult<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Clé maître illisible ({}): {}", path.display(), e))?;
+        Self::from_hex(&contents)
+    }
+
+    /// Génère une nouvelle clé maître et l'écrit dans `path` (lisible par le seul propriétaire).
+    pub fn generate(path: &Path) -> CryptoResult<Self> {
+        let key = Aes256Gcm::generate_key(OsRng);
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        write_private(path, &hex::encode(key))?;
+        Self::load(path)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn wrap(&self, user_id: Uuid, data_key: &Key<Aes256Gcm>) -> CryptoResult<String> {
+        seal_bytes(&self.cipher, wrap_aad(user_id).as_bytes(), data_key)
+    }
+
+    pub fn unwrap(&self, user_id: Uuid, wrapped: &str) -> CryptoResult<Key<Aes256Gcm>> {
+        let bytes = open_bytes(&self.cipher, wrap_aad(user_id).as_bytes(), wrapped)?;
+        if bytes.len() != 32 {
+            return Err("Clé de données enveloppée invalide".into());
+        }
+        Ok(*Key::<Aes256Gcm>::from_slice(&bytes))
+    }
+}
+
+/// Écrit un nouveau fichier de clé lisible par le seul propriétaire (échoue s'il existe déjà).
+#[cfg(unix)]
+pub fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+pub fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    fs::write(path, contents)
+}
+
+// La clé de données est liée à son utilisateur : une clé enveloppée copiée sur un autre compte ne s'ouvre pas
+fn wrap_aad(user_id: Uuid) -> String {
+    format!("brevetchain-data-key:{}", user_id)
+}
+
+fn seal_bytes(cipher: &Aes256Gcm, aad: &[u8], plaintext: &[u8]) -> CryptoResult<String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| "Échec du chiffrement")?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(BASE64.encode(sealed))
+}
+
+fn open_bytes(cipher: &Aes256Gcm, aad: &[u8], sealed: &str) -> CryptoResult<Vec<u8>> {
+    let bytes = BASE64.decode(sealed)?;
+    if bytes.len() < NONCE_LEN {
+        return Err("Valeur chiffrée tronquée".into());
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| "Échec du déchiffrement (clé ou donnée invalide)".into())
+}
+
+/// Clés maîtres connues : la clé courante enveloppe les nouvelles clés de données,
+/// `.next` et `.previous` restent lisibles pour terminer ou suivre une rotation interrompue.
+pub struct MasterKeyring {
+    primary: MasterKey,
+    others: Vec<MasterKey>,
+}
+
+impl MasterKeyring {
+    pub fn load(path: &Path) -> CryptoResult<Self> {
+        let primary = MasterKey::load(path).map_err(|e| {
+            format!("{} — créez-la avec `cargo run --bin rotate_master_key -- --init`", e)
+        })?;
+        let mut others = Vec::new();
+        for suffix in ["next", "previous"] {
+            let sibling = sibling_path(path, suffix);
+            if sibling.exists() {
+                others.push(MasterKey::load(&sibling)?);
+            }
+        }
+        Ok(MasterKeyring { primary, others })
+    }
+
+    pub fn primary(&self) -> &MasterKey {
+        &self.primary
+    }
+
+    pub fn find(&self, id: &str) -> Option<&MasterKey> {
+        std::iter::once(&self.primary).chain(&self.others).find(|k| k.id == id)
+    }
+}
+
+/// Clé de données d'un utilisateur, déjà désenveloppée.
+#[derive(Clone)]
+pub struct DataKey {
+    cipher: Aes256Gcm,
+}
+
+impl DataKey {
+    /// Chiffre une valeur ; `aad` lie le texte chiffré à sa colonne et à sa ligne.
+    pub fn seal(&self, aad: &str, plaintext: &str) -> CryptoResult<String> {
+        Ok(format!("{}{}", SEALED_PREFIX, seal_bytes(&self.cipher, aad.as_bytes(), plaintext.as_bytes())?))
+    }
+
+    /// Déchiffre une valeur ; un texte clair hérité est renvoyé tel quel.
+    pub fn open(&self, aad: &str, stored: &str) -> CryptoResult<String> {
+        match stored.strip_prefix(SEALED_PREFIX) {
+            Some(sealed) => Ok(String::from_utf8(open_bytes(&self.cipher, aad.as_bytes(), sealed)?)?),
+            None => Ok(stored.to_string()),
+        }
+    }
+}
+
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(SEALED_PREFIX)
+}
+
+/// Accès aux clés de données : création à la demande, désenveloppement, cache en mémoire.
+pub struct Vault {
+    keyring: MasterKeyring,
+    cache: Mutex<HashMap<Uuid, DataKey>>,
+}
+
+impl Vault {
+    /// Charge la clé maître. Au premier démarrage (aucun fichier, aucune clé de données en base),
+    /// une clé est générée ; sinon un fichier manquant est une erreur, pour ne rien rendre illisible.
+    pub async fn from_env(pool: &PgPool) -> CryptoResult<Self> {
+        let path = master_key_path();
+        if !path.exists() {
+            let wrapped = sqlx::query_scalar!("SELECT COUNT(*) FROM user_data_keys")
+                .fetch_one(pool)
+                .await?
+                .unwrap_or(0);
+            if wrapped == 0 {
+                let key = MasterKey::generate(&path)?;
+                println!("🔐 Nouvelle clé maître {} écrite dans {}", key.id(), path.display());
+            }
+        }
+        let keyring = MasterKeyring::load(&path)?;
+        println!("🔐 Clé maître chargée ({})", keyring.primary().id());
+        Ok(Vault { keyring, cache: Mutex::new(HashMap::new()) })
+    }
+
+    pub async fn data_key(&self, pool: &PgPool, user_id: Uuid) -> CryptoResult<DataKey> {
+        if let Some(key) = self.cache.lock().map_err(|_| "Cache de clés corrompu")?.get(&user_id) {
+            return Ok(key.clone());
+        }
+
+        let stored = sqlx::query!(
+            "SELECT master_key_id, wrapped_key FROM user_data_keys WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let (master_key_id, wrapped_key) = match stored {
+            Some(row) => (row.master_key_id, row.wrapped_key),
+            None => {
+                // Première donnée de l'utilisateur : nouvelle clé, la première écrite l'emporte en cas de course
+                let master = self.keyring.primary();
+                let wrapped = master.wrap(user_id, &Aes256Gcm::generate_key(OsRng))?;
+                sqlx::query!(
+                    "INSERT INTO user_data_keys (user_id, master_key_id, wrapped_key, created_at)
+                     VALUES ($1, $2, $3, $4)
+                     ON CONFLICT (user_id) DO NOTHING",
+                    user_id,
+                    master.id(),
+                    wrapped,
+                    Utc::now()
+                )
+                .execute(pool)
+                .await?;
+                let row = sqlx::query!(
+                    "SELECT master_key_id, wrapped_key FROM user_data_keys WHERE user_id = $1",
+                    user_id
+                )
+                .fetch_one(pool)
+                .await?;
+                (row.master_key_id, row.wrapped_key)
+            }
+        };
+
+        let master = self
+            .keyring
+            .find(&master_key_id)
+            .ok_or(format!("Clé maître {} introuvable", master_key_id))?;
+        let key = DataKey { cipher: Aes256Gcm::new(&master.unwrap(user_id, &wrapped_key)?) };
+        self.cache.lock().map_err(|_| "Cache de clés corrompu")?.insert(user_id, key.clone());
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_key() -> DataKey {
+        DataKey { cipher: Aes256Gcm::new(&Aes256Gcm::generate_key(OsRng)) }
+    }
+
+    fn key_file() -> PathBuf {
+        env::temp_dir().join(format!("master-key-{}", Uuid::new_v4())).join("master.key")
+    }
+
+    #[test]
+    fn sealed_values_round_trip() {
+        let key = data_key();
+        let sealed = key.seal("summaries.title:1", "Capteur solaire pliable").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("Capteur"));
+        assert_eq!(key.open("summaries.title:1", &sealed).unwrap(), "Capteur solaire pliable");
+        // Nonce aléatoire : deux chiffrements du même texte diffèrent
+        assert_ne!(sealed, key.seal("summaries.title:1", "Capteur solaire pliable").unwrap());
+    }
+
+    #[test]
+    fn a_value_moved_to_another_column_or_row_does_not_open() {
+        let key = data_key();
+        let sealed = key.seal("summaries.title:1", "Titre").unwrap();
+        assert!(key.open("summaries.problem:1", &sealed).is_err());
+        assert!(key.open("summaries.title:2", &sealed).is_err());
+        // Ni avec la clé d'un autre utilisateur
+        assert!(data_key().open("summaries.title:1", &sealed).is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertexts_are_rejected() {
+        let key = data_key();
+        let sealed = key.seal("ideas.raw_idea:1", "Idée confidentielle").unwrap();
+        let bytes = BASE64.decode(sealed.strip_prefix(SEALED_PREFIX).unwrap()).unwrap();
+
+        // Un octet du texte chiffré, puis un octet de l'étiquette d'authentification
+        for index in [NONCE_LEN, bytes.len() - 1] {
+            let mut tampered = bytes.clone();
+            tampered[index] ^= 0x01;
+            let tampered = format!("{}{}", SEALED_PREFIX, BASE64.encode(tampered));
+            assert!(key.open("ideas.raw_idea:1", &tampered).is_err());
+        }
+        let truncated = format!("{}{}", SEALED_PREFIX, BASE64.encode(&bytes[..NONCE_LEN - 1]));
+        assert!(key.open("ideas.raw_idea:1", &truncated).is_err());
+    }
+
+    #[test]
+    fn legacy_plaintext_is_returned_as_is() {
+        let key = data_key();
+        assert!(!is_sealed("Texte antérieur au chiffrement"));
+        assert_eq!(key.open("ideas.raw_idea:1", "Texte antérieur au chiffrement").unwrap(), "Texte antérieur au chiffrement");
+    }
+
+    #[test]
+    fn data_keys_are_wrapped_for_their_user() {
+        let path = key_file();
+        let master = MasterKey::generate(&path).unwrap();
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let data_key = Aes256Gcm::generate_key(OsRng);
+
+        let wrapped = master.wrap(user, &data_key).unwrap();
+        assert_eq!(master.unwrap(user, &wrapped).unwrap(), data_key);
+        // Une clé enveloppée copiée sur un autre compte ne s'ouvre pas
+        assert!(master.unwrap(other, &wrapped).is_err());
+
+        // Même identifiant pour la même clé relue
+        assert_eq!(MasterKey::load(&path).unwrap().id(), master.id());
+        assert!(MasterKey::generate(&path).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse, ResponseError};
+use serde_json::json;
+use std::error::Error as StdError;
+use crate::ai_client::AiError;
+use crate::i18n::{self, Locale, Message};
+use crate::ledger::LedgerError;
+
+// Erreurs renvoyées par les handlers HTTP.
+// Chaque variante a un code stable, destiné aux clients, et un statut HTTP ; le corps est toujours
+// {"code": ..., "message": ...}, le message étant traduit par `localize` dans la langue de la requête.
+// Le détail d'une erreur interne est journalisé, jamais renvoyé.
+
+pub type AppResult<T> = Result<T, AppError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("{0}")]
+    InvalidRequest(Message),
+    #[error("{0}")]
+    Unauthorized(Message),
+    #[error("{0}")]
+    NotFound(Message),
+    #[error("Un compte existe déjà avec cet e-mail")]
+    DuplicateEmail,
+    #[error("{0}")]
+    Conflict(Message),
+    /// Modification d'une révision qui n'est plus la dernière
+    #[error("Seule la dernière révision ({latest_revision}) peut être modifiée")]
+    StaleRevision { latest_revision: i32 },
+    #[error("{0}")]
+    Unprocessable(Message),
+    #[error("Service IA indisponible")]
+    AiUnavailable(#[from] AiError),
+    /// Réponse du service IA reçue mais inutilisable
+    #[error("{0}")]
+    AiInvalidResponse(Message),
+    #[error("Registre indisponible")]
+    LedgerUnavailable(#[from] LedgerError),
+    #[error("Mirror node indisponible")]
+    MirrorUnavailable(#[source] LedgerError),
+    #[error("Erreur serveur")]
+    Database(#[from] sqlx::Error),
+    #[error("{message}")]
+    Internal {
+        message: Message,
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+}
+
+// Déchiffrement, signature, hachage : détail journalisé, message générique pour le client
+impl From<Box<dyn StdError + Send + Sync>> for AppError {
+    fn from(source: Box<dyn StdError + Send + Sync>) -> Self {
+        AppError::Internal { message: Message::new("error.server"), source }
+    }
+}
+
+impl AppError {
+    /// Erreur interne avec un message propre à l'opération échouée.
+    pub fn internal<E: Into<Box<dyn StdError + Send + Sync>>>(key: &'static str) -> impl FnOnce(E) -> AppError {
+        move |source| AppError::Internal { message: Message::new(key), source: source.into() }
+    }
+
+    /// Code stable, indépendant de la langue du message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::InvalidRequest(_) => "invalid_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::NotFound(_) => "not_found",
+            AppError::DuplicateEmail => "duplicate_email",
+            AppError::Conflict(_) => "conflict",
+            AppError::StaleRevision { .. } => "stale_revision",
+            AppError::Unprocessable(_) => "unprocessable",
+            AppError::AiUnavailable(_) => "ai_unavailable",
+            AppError::AiInvalidResponse(_) => "ai_invalid_response",
+            AppError::LedgerUnavailable(_) => "ledger_unavailable",
+            AppError::MirrorUnavailable(_) => "mirror_node_unavailable",
+            AppError::Database(_) | AppError::Internal { .. } => "internal_error",
+        }
+    }
+
+    /// Message destiné au client, à traduire dans sa langue.
+    pub fn message(&self) -> Message {
+        match self {
+            AppError::InvalidRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message)
+            | AppError::Unprocessable(message)
+            | AppError::AiInvalidResponse(message)
+            | AppError::Internal { message, .. } => message.clone(),
+            AppError::DuplicateEmail => Message::new("error.duplicate_email"),
+            AppError::StaleRevision { latest_revision } => {
+                Message::new("error.stale_revision").with("revision", latest_revision)
+            }
+            AppError::AiUnavailable(_) => Message::new("error.ai_unavailable"),
+            AppError::LedgerUnavailable(_) => Message::new("error.ledger_unavailable"),
+            AppError::MirrorUnavailable(_) => Message::new("error.mirror_unavailable"),
+            AppError::Database(_) => Message::new("error.server"),
+        }
+    }
+}
+
+// Corps d'une erreur, conservé sur la réponse pour être traduit par `localize`
+#[derive(Clone)]
+struct ErrorBody {
+    code: &'static str,
+    message: Message,
+    latest_revision: Option<i32>,
+}
+
+impl ErrorBody {
+    fn render(&self, locale: Locale) -> String {
+        let mut body = json!({"code": self.code, "message": self.message.render(locale)});
+        if let Some(latest_revision) = self.latest_revision {
+            body["latest_revision"] = json!(latest_revision);
+        }
+        body.to_string()
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::DuplicateEmail | AppError::Conflict(_) | AppError::StaleRevision { .. } => StatusCode::CONFLICT,
+            AppError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::AiUnavailable(_) | AppError::AiInvalidResponse(_) | AppError::LedgerUnavailable(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            AppError::MirrorUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Database(_) | AppError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            match self.source() {
+                Some(source) => eprintln!("Erreur {} ({}): {}: {}", status.as_u16(), self.code(), self, source),
+                None => eprintln!("Erreur {} ({}): {}", status.as_u16(), self.code(), self),
+            }
+        }
+        let body = ErrorBody {
+            code: self.code(),
+            message: self.message(),
+            latest_revision: match self {
+                AppError::StaleRevision { latest_revision } => Some(*latest_revision),
+                _ => None,
+            },
+        };
+        let mut response = HttpResponse::build(status)
+            .content_type("application/json")
+            .body(body.render(Locale::DEFAULT));
+        response.extensions_mut().insert(body);
+        response
+    }
+}
+
+/// Middleware : traduit le message des réponses d'erreur dans la langue de la requête.
+pub async fn localize<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B, String>>, Error> {
+    let response = next.call(req).await?;
+    let body = response.response().extensions().get::<ErrorBody>().cloned();
+    let Some(body) = body else {
+        return Ok(response.map_into_left_body());
+    };
+    let locale = i18n::resolve(response.request()).await;
+    Ok(response.map_body(|_, _| EitherBody::right(body.render(locale))))
+}
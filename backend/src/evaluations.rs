@@ -0,0 +1,59 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::encryption::{CryptoResult, Vault};
+use crate::i18n::{Locale, Message};
+use crate::models::AiEvaluationResponse;
+use crate::store;
+
+// Évaluations des idées par le service IA : chaque réponse est conservée, retenue ou non, pour que
+// l'inventeur sache pourquoi une idée a été refusée et puisse demander une revue humaine.
+
+/// Score de nouveauté inférieur au seuil de la formule.
+pub const NOVELTY_BELOW_THRESHOLD: &str = "novelty_below_threshold";
+/// Revendications proposées inutilisables.
+pub const INVALID_CLAIMS: &str = "invalid_claims";
+
+/// Seuil de nouveauté appliqué à une idée : celui de la formule de son auteur, levé une fois un refus
+/// approuvé en revue humaine.
+pub async fn novelty_threshold(pool: &PgPool, user_id: Uuid, idea_id: Uuid) -> Result<i16, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT CASE WHEN EXISTS (SELECT 1 FROM ai_evaluations WHERE idea_id = $2 AND review_status = 'approved')
+                       THEN 0::SMALLINT ELSE p.novelty_threshold END AS "threshold!"
+           FROM users u JOIN plans p ON p.code = u.plan WHERE u.id = $1"#,
+        user_id,
+        idea_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Évaluations d'une idée, de la plus récente à la plus ancienne, motifs de refus rendus dans `locale`.
+pub async fn list(pool: &PgPool, vault: &Vault, idea_id: Uuid, locale: Locale) -> CryptoResult<Vec<AiEvaluationResponse>> {
+    Ok(store::fetch_ai_evaluations(pool, vault, idea_id)
+        .await?
+        .into_iter()
+        .map(|(evaluation, response)| AiEvaluationResponse {
+            rejection_message: evaluation.rejection_message.as_ref().map(|message| {
+                Message::from_json(message)
+                    .unwrap_or_else(|| Message::new("error.server"))
+                    .render(locale)
+            }),
+            evaluation_id: evaluation.id,
+            idea_id: evaluation.idea_id,
+            job_id: evaluation.job_id,
+            summary_id: evaluation.summary_id,
+            provider: evaluation.provider,
+            model: evaluation.model,
+            novelty_score: evaluation.novelty_score,
+            novelty_threshold: evaluation.novelty_threshold,
+            latency_ms: evaluation.latency_ms,
+            accepted: evaluation.accepted,
+            rejection_reason: evaluation.rejection_reason,
+            response,
+            review_status: evaluation.review_status,
+            review_requested_at: evaluation.review_requested_at.map(|at| at.to_rfc3339()),
+            reviewed_at: evaluation.reviewed_at.map(|at| at.to_rfc3339()),
+            created_at: evaluation.created_at.to_rfc3339(),
+        })
+        .collect())
+}
@@ -1,53 +1,153 @@
+use async_trait::async_trait;
 use hedera::{
     Client, TopicMessageSubmitTransaction, PrivateKey, TopicId, Hbar, AccountId, Status,
+    TransactionId, TransactionReceiptQuery,
 };
+use crate::network::{HederaNetwork, NetworkConfig};
 use std::env;
-use chrono::Utc;
-use hex;
-use serde_json::json;
-
-pub async fn submit_to_hedera(
-    hash: String,
-    cpc_code: String,
-    _user_wallet: String,
-    created_at: chrono::DateTime<Utc>,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let client = Client::for_testnet();
-    let private_key_hex = env::var("HEDERA_PRIVATE_KEY")?;
-    let private_key_bytes = hex::decode(&private_key_hex)?;
-    let private_key = PrivateKey::from_bytes_der(&private_key_bytes)?;
-    let operator_account_id_str = env::var("HEDERA_ACCOUNT_ID")?;
-    let operator_account_id: AccountId = operator_account_id_str.parse()?;
-    client.set_operator(operator_account_id, private_key.clone());
-
-    let topic_id_str = env::var("HEDERA_TOPIC_ID")?;
-    let topic_id: TopicId = topic_id_str.parse()?;
-
-    let message = json!({
-        "hash": hash,
-        "cpc_code": cpc_code,
-        "created_at": created_at.to_rfc3339(),
-    }).to_string();
-
-    let mut attempts = 0;
-    loop {
-        attempts += 1;
-        let response = TopicMessageSubmitTransaction::new()
-            .topic_id(topic_id)
-            .message(message.as_bytes())
-            .max_transaction_fee(Hbar::from_tinybars(1_000_000))
-            .sign(private_key.clone())
-            .execute(&client)
-            .await?;
+use crate::ledger::{LedgerAnchor, LedgerError, LedgerMessage, LedgerReceipt, LedgerResult, LedgerSubmission, ReceiptStatus};
+use crate::mirror_node::MirrorNodeClient;
 
-        let receipt = response.get_receipt(&client).await?;
-        if receipt.status == Status::Success {
-            return Ok(receipt.transaction_id.map(|id| id.to_string()).unwrap_or_else(|| "no_tx_id".to_string()));
-        }
-        eprintln!("Hedera tx failed (attempt {}): {:?}", attempts, receipt.status);
-        if attempts >= 3 {
-            return Err(format!("Hedera submission failed after 3 attempts: {:?}", receipt.status).into());
+pub struct HederaLedger {
+    client: Client,
+    private_key: PrivateKey,
+    operator_account_id: AccountId,
+    topic_id: TopicId,
+    network: HederaNetwork,
+    mirror: MirrorNodeClient,
+}
+
+impl HederaLedger {
+    pub fn from_env() -> LedgerResult<Self> {
+        Self::configure().map_err(|e| LedgerError::Config(e.to_string()))
+    }
+
+    fn configure() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = NetworkConfig::from_env()?;
+        let client = config.client()?;
+        let private_key_hex = env::var("HEDERA_PRIVATE_KEY")?;
+        let private_key_bytes = hex::decode(&private_key_hex)?;
+        let private_key = PrivateKey::from_bytes_der(&private_key_bytes)?;
+        let operator_account_id_str = env::var("HEDERA_ACCOUNT_ID")?;
+        let operator_account_id: AccountId = operator_account_id_str.parse()?;
+        client.set_operator(operator_account_id, private_key.clone());
+
+        let topic_id_str = env::var("HEDERA_TOPIC_ID")?;
+        let topic_id: TopicId = topic_id_str.parse()?;
+
+        Ok(HederaLedger {
+            client,
+            private_key,
+            operator_account_id,
+            topic_id,
+            network: config.network,
+            mirror: MirrorNodeClient::new(&config.mirror_url()),
+        })
+    }
+}
+
+fn parse_transaction_id(transaction_id: &str) -> LedgerResult<TransactionId> {
+    transaction_id
+        .parse()
+        .map_err(|_| LedgerError::InvalidTransactionId(transaction_id.to_string()))
+}
+
+#[async_trait]
+impl LedgerAnchor for HederaLedger {
+    fn name(&self) -> &'static str {
+        "hedera"
+    }
+
+    fn network(&self) -> &str {
+        self.network.as_str()
+    }
+
+    fn new_transaction_id(&self) -> LedgerResult<String> {
+        Ok(TransactionId::generate(self.operator_account_id).to_string())
+    }
+
+    async fn submit_message(&self, transaction_id: &str, message: Vec<u8>) -> LedgerResult<LedgerSubmission> {
+        let id = parse_transaction_id(transaction_id)?;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+
+            // Une tentative précédente a pu atteindre le consensus malgré l'erreur : on consulte d'abord le reçu
+            if attempts > 1 {
+                match self.fetch_receipt(transaction_id).await {
+                    Ok(receipt) if receipt.status == ReceiptStatus::Success => {
+                        return Ok(LedgerSubmission {
+                            transaction_id: receipt.transaction_id,
+                            topic_sequence_number: receipt.topic_sequence_number,
+                        });
+                    }
+                    Ok(LedgerReceipt { status: ReceiptStatus::Failed(status), .. }) => {
+                        return Err(LedgerError::Rejected { transaction_id: transaction_id.to_string(), status });
+                    }
+                    _ => {}
+                }
+            }
+
+            let submitted = TopicMessageSubmitTransaction::new()
+                .transaction_id(id)
+                .topic_id(self.topic_id)
+                .message(message.clone())
+                .max_transaction_fee(Hbar::from_tinybars(1_000_000))
+                .sign(self.private_key.clone())
+                .execute(&self.client)
+                .await;
+
+            match submitted {
+                Ok(_) => {
+                    let receipt = self.fetch_receipt(transaction_id).await?;
+                    match receipt.status {
+                        ReceiptStatus::Success => {
+                            return Ok(LedgerSubmission {
+                                transaction_id: receipt.transaction_id,
+                                topic_sequence_number: receipt.topic_sequence_number,
+                            });
+                        }
+                        // Consensus atteint avec un échec : resoumettre le même identifiant serait rejeté
+                        ReceiptStatus::Failed(status) => {
+                            return Err(LedgerError::Rejected { transaction_id: transaction_id.to_string(), status });
+                        }
+                        ReceiptStatus::NotFound => eprintln!("Hedera receipt not found (attempt {})", attempts),
+                    }
+                }
+                // Déjà reçue par le réseau : le reçu sera consulté à la tentative suivante
+                Err(hedera::Error::TransactionPreCheckStatus { status: Status::DuplicateTransaction, .. }) => {}
+                Err(e) => eprintln!("Hedera tx failed (attempt {}): {}", attempts, e),
+            }
+
+            if attempts >= 3 {
+                return Err(LedgerError::Unavailable(format!(
+                    "Hedera submission of {} failed after 3 attempts",
+                    transaction_id
+                )));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
         }
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     }
-}
\ No newline at end of file
+
+    async fn fetch_receipt(&self, transaction_id: &str) -> LedgerResult<LedgerReceipt> {
+        let id = parse_transaction_id(transaction_id)?;
+        let receipt = TransactionReceiptQuery::new()
+            .transaction_id(id)
+            .execute(&self.client)
+            .await?;
+
+        Ok(LedgerReceipt {
+            transaction_id: receipt.transaction_id.map(|id| id.to_string()).unwrap_or_else(|| transaction_id.to_string()),
+            status: match receipt.status {
+                Status::Success => ReceiptStatus::Success,
+                Status::ReceiptNotFound => ReceiptStatus::NotFound,
+                other => ReceiptStatus::Failed(format!("{:?}", other)),
+            },
+            topic_sequence_number: receipt.topic_sequence_number,
+        })
+    }
+
+    async fn fetch_message(&self, transaction_id: &str) -> LedgerResult<Option<LedgerMessage>> {
+        self.mirror.topic_message_by_transaction(transaction_id).await
+    }
+}
@@ -0,0 +1,293 @@
+use actix_web::dev::Payload;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, web};
+use serde_json::{json, Map, Value};
+use sqlx::PgPool;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
+use crate::auth::AuthenticatedUser;
+
+// Catalogue des messages renvoyés aux utilisateurs (API, certificat, page publique, notifications).
+// Une langue = un fichier `locales/<code>.json` (clé -> texte, paramètres `{nom}`) déclaré dans `LOCALES`.
+// Langue d'une requête : préférence du compte, sinon `Accept-Language`, sinon le français.
+// Clé absente d'une langue : texte français, sinon la clé elle-même.
+
+const LOCALES: [(&str, &str); 3] = [
+    ("fr", include_str!("../locales/fr.json")),
+    ("en", include_str!("../locales/en.json")),
+    ("es", include_str!("../locales/es.json")),
+];
+
+static CATALOGUES: LazyLock<Vec<HashMap<String, String>>> = LazyLock::new(|| {
+    LOCALES
+        .iter()
+        .map(|(code, source)| {
+            serde_json::from_str(source).unwrap_or_else(|e| panic!("Catalogue {} invalide: {}", code, e))
+        })
+        .collect()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale(usize);
+
+impl Locale {
+    pub const DEFAULT: Locale = Locale(0);
+
+    /// Étiquette de langue (`fr`, `en-GB`, `es_ES`…) ramenée à une langue livrée.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        LOCALES.iter().position(|(code, _)| *code == primary).map(Locale)
+    }
+
+    pub fn code(self) -> &'static str {
+        LOCALES[self.0].0
+    }
+
+    /// Codes des langues livrées.
+    pub fn supported() -> impl Iterator<Item = &'static str> {
+        LOCALES.iter().map(|(code, _)| *code)
+    }
+
+    /// Texte brut d'une clé, avec repli sur la langue par défaut puis sur la clé.
+    pub fn text(self, key: &'static str) -> &'static str {
+        CATALOGUES[self.0]
+            .get(key)
+            .or_else(|| CATALOGUES[Self::DEFAULT.0].get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
+    }
+
+    /// Texte d'une clé avec ses paramètres `{nom}` remplacés.
+    pub fn format(self, key: &'static str, args: &[(&str, &str)]) -> String {
+        let mut text = self.text(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        text
+    }
+}
+
+/// Première langue livrée parmi celles d'un en-tête `Accept-Language`, par préférence décroissante.
+pub fn negotiate(accept_language: Option<&str>) -> Locale {
+    let Some(header) = accept_language else {
+        return Locale::DEFAULT;
+    };
+    let mut ranges: Vec<(f32, &str)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(1.0, |q| q.trim().parse().unwrap_or(0.0));
+            (!tag.is_empty() && quality > 0.0).then_some((quality, tag))
+        })
+        .collect();
+    // Tri stable : à qualité égale, l'ordre de l'en-tête est conservé
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges.into_iter().find_map(|(_, tag)| Locale::parse(tag)).unwrap_or(Locale::DEFAULT)
+}
+
+/// Langue préférée enregistrée sur un compte, `None` si elle n'est pas (ou plus) livrée.
+pub async fn preferred_locale(pool: &PgPool, user_id: uuid::Uuid) -> Result<Option<Locale>, sqlx::Error> {
+    let preferred = sqlx::query_scalar!("SELECT preferred_language FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    Ok(preferred.as_deref().and_then(Locale::parse))
+}
+
+/// Langue de la requête, calculée une fois puis mémorisée dans ses extensions.
+pub async fn resolve(req: &HttpRequest) -> Locale {
+    let cached = req.extensions().get::<Locale>().copied();
+    if let Some(locale) = cached {
+        return locale;
+    }
+
+    let user = req.extensions().get::<AuthenticatedUser>().copied();
+    let preferred = match (user, req.app_data::<web::Data<PgPool>>()) {
+        (Some(user), Some(pool)) => preferred_locale(pool.get_ref(), user.0).await.unwrap_or_else(|e| {
+            eprintln!("Erreur lecture langue préférée: {}", e);
+            None
+        }),
+        _ => None,
+    };
+    let locale = preferred.unwrap_or_else(|| {
+        negotiate(req.headers().get("Accept-Language").and_then(|value| value.to_str().ok()))
+    });
+    req.extensions_mut().insert(locale);
+    locale
+}
+
+/// Langue de la requête, à utiliser comme paramètre de handler.
+#[derive(Debug, Clone, Copy)]
+pub struct Lang(pub Locale);
+
+impl Lang {
+    pub fn t(self, key: &'static str) -> String {
+        self.0.text(key).to_string()
+    }
+}
+
+impl FromRequest for Lang {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { Ok(Lang(resolve(&req).await)) })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Arg {
+    Text(String),
+    Message(Message),
+}
+
+/// Message à traduire au dernier moment, dans la langue de son destinataire.
+#[derive(Debug, Clone)]
+pub struct Message {
+    key: &'static str,
+    args: Vec<(Cow<'static, str>, Arg)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Message { key, args: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name.into(), Arg::Text(value.to_string())));
+        self
+    }
+
+    /// Paramètre lui-même traduit (détail d'une erreur de validation, par exemple).
+    pub fn with_message(mut self, name: &'static str, message: Message) -> Self {
+        self.args.push((name.into(), Arg::Message(message)));
+        self
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        let values: Vec<(&str, String)> = self
+            .args
+            .iter()
+            .map(|(name, arg)| match arg {
+                Arg::Text(text) => (name.as_ref(), text.clone()),
+                Arg::Message(message) => (name.as_ref(), message.render(locale)),
+            })
+            .collect();
+        let args: Vec<(&str, &str)> = values.iter().map(|(name, value)| (*name, value.as_str())).collect();
+        locale.format(self.key, &args)
+    }
+
+    /// Forme enregistrable (clé et paramètres), pour un message rendu plus tard par `from_json`.
+    pub fn to_json(&self) -> Value {
+        let args: Map<String, Value> = self
+            .args
+            .iter()
+            .map(|(name, arg)| match arg {
+                Arg::Text(text) => (name.to_string(), json!(text)),
+                Arg::Message(message) => (name.to_string(), message.to_json()),
+            })
+            .collect();
+        json!({"key": self.key, "args": args})
+    }
+
+    /// Message enregistré par `to_json` ; `None` si sa clé n'est plus au catalogue.
+    pub fn from_json(value: &Value) -> Option<Message> {
+        let key = value.get("key")?.as_str()?;
+        let key = CATALOGUES[Locale::DEFAULT.0].get_key_value(key)?.0.as_str();
+        let args = match value.get("args") {
+            Some(Value::Object(args)) => args
+                .iter()
+                .map(|(name, value)| {
+                    let arg = match value {
+                        Value::String(text) => Arg::Text(text.clone()),
+                        other => Message::from_json(other).map_or_else(|| Arg::Text(other.to_string()), Arg::Message),
+                    };
+                    (Cow::Owned(name.clone()), arg)
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Some(Message { key, args })
+    }
+}
+
+impl From<&'static str> for Message {
+    fn from(key: &'static str) -> Self {
+        Message::new(key)
+    }
+}
+
+// Journaux et erreurs sans destinataire : langue par défaut
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(Locale::DEFAULT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{').skip(1).filter_map(|rest| rest.split_once('}').map(|(name, _)| name)).collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_locale() {
+        let reference = &CATALOGUES[Locale::DEFAULT.0];
+        for (index, (code, _)) in LOCALES.iter().enumerate() {
+            let catalogue = &CATALOGUES[index];
+            let missing: BTreeSet<_> = reference.keys().filter(|key| !catalogue.contains_key(*key)).collect();
+            let unknown: BTreeSet<_> = catalogue.keys().filter(|key| !reference.contains_key(*key)).collect();
+            assert!(missing.is_empty(), "clés absentes de {}: {:?}", code, missing);
+            assert!(unknown.is_empty(), "clés inconnues dans {}: {:?}", code, unknown);
+            for (key, text) in catalogue {
+                assert!(!text.trim().is_empty(), "{}: texte vide pour {}", code, key);
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(&reference[key]),
+                    "{}: paramètres différents pour {}",
+                    code,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negotiates_by_quality_then_region() {
+        assert_eq!(negotiate(Some("en-GB,en;q=0.9,fr;q=0.8")).code(), "en");
+        assert_eq!(negotiate(Some("de-DE, fr;q=0.5, en;q=0.7")).code(), "en");
+        assert_eq!(negotiate(Some("es_MX")).code(), "es");
+        assert_eq!(negotiate(Some("de, it;q=0.4")).code(), "fr");
+        assert_eq!(negotiate(Some("en;q=0, *")).code(), "fr");
+        assert_eq!(negotiate(None).code(), "fr");
+    }
+
+    #[test]
+    fn falls_back_to_default_then_key() {
+        let en = Locale::parse("en").unwrap();
+        assert_eq!(en.text("cle.inexistante"), "cle.inexistante");
+        let message = Message::new("error.stale_revision").with("revision", 3);
+        assert!(message.render(en).contains('3'));
+        assert_ne!(message.render(en), message.render(Locale::DEFAULT));
+    }
+
+    #[test]
+    fn stored_message_renders_like_the_original() {
+        let en = Locale::parse("en").unwrap();
+        let message = Message::new("error.invalid_ai_claims")
+            .with_message("detail", Message::new("claims.empty_text").with("number", 2));
+        let stored = Message::from_json(&message.to_json()).unwrap();
+        assert_eq!(stored.render(en), message.render(en));
+        assert!(Message::from_json(&json!({"key": "cle.inexistante"})).is_none());
+    }
+}
@@ -0,0 +1,38 @@
+use hedera::PublicKey;
+use serde_json::json;
+use std::str::FromStr;
+use crate::wallet_login;
+
+// Co-signature de l'inventeur : en plus de la transaction signée par l'opérateur de la plateforme,
+// l'inventeur signe l'empreinte du document avec la clé de son wallet (côté client).
+// La signature est vérifiée à l'enregistrement, publiée dans le message HCS et revérifiée sur le certificat.
+
+/// Texte à signer pour une empreinte ; distinct du défi de connexion pour qu'une signature ne serve qu'à un usage.
+pub fn signing_message(hash: &str) -> String {
+    format!("BrevetChain — preuve d'antériorité\nEmpreinte : {}", hash.to_ascii_lowercase())
+}
+
+#[derive(Debug, Clone)]
+pub struct InventorSignature {
+    pub account: String,    // adresse canonique du wallet
+    pub public_key: String, // clé publique DER en hex
+    pub signature: String,  // hex
+}
+
+impl InventorSignature {
+    /// Partie `inventor` du message ancré.
+    pub fn payload(&self) -> serde_json::Value {
+        json!({
+            "account": self.account,
+            "public_key": self.public_key,
+            "signature": self.signature,
+        })
+    }
+}
+
+/// La signature couvre-t-elle bien `hash` avec la clé enregistrée ?
+pub fn verify(hash: &str, public_key: &str, signature: &str) -> bool {
+    PublicKey::from_str(public_key)
+        .map(|key| wallet_login::verify_signature(&key, &signing_message(hash), signature))
+        .unwrap_or(false)
+}
@@ -0,0 +1,66 @@
+use dotenvy::dotenv;
+use sqlx::PgPool;
+use std::env;
+use std::fs;
+
+#[allow(dead_code)]
+#[path = "../encryption.rs"]
+mod encryption;
+
+#[allow(dead_code)]
+#[path = "../certificate_signing.rs"]
+mod certificate_signing;
+
+use certificate_signing::CertificateSigner;
+use encryption::sibling_path;
+
+// Rotation de la clé de signature des certificats. La nouvelle clé publique est publiée dans le JWKS
+// avant d'être utilisée ; l'ancienne y reste, les certificats déjà émis restent vérifiables.
+//   cargo run --bin rotate_certificate_key
+// Les serveurs signent avec la nouvelle clé à leur prochain démarrage.
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+    let path = certificate_signing::signing_key_path();
+    let next_path = sibling_path(&path, "next");
+    let previous_path = sibling_path(&path, "previous");
+    let pool = PgPool::connect(&env::var("DATABASE_URL")?).await?;
+
+    // Interruption entre les deux renommages : il ne reste qu'à installer la nouvelle clé
+    if !path.exists() && next_path.exists() {
+        fs::rename(&next_path, &path)?;
+        let signer = CertificateSigner::load(&path)?;
+        signer.publish(&pool).await?;
+        println!("✅ Rotation précédente terminée, clé {} installée", signer.kid());
+        return Ok(());
+    }
+
+    if !path.exists() {
+        let signer = CertificateSigner::generate(&path)?;
+        signer.publish(&pool).await?;
+        println!("✅ Première clé de signature {} créée dans {}", signer.kid(), path.display());
+        return Ok(());
+    }
+
+    let current = CertificateSigner::load(&path)?;
+    // Une rotation interrompue est reprise avec la même nouvelle clé
+    let next = if next_path.exists() {
+        CertificateSigner::load(&next_path)?
+    } else {
+        CertificateSigner::generate(&next_path)?
+    };
+    println!("🔑 Rotation {} -> {}", current.kid(), next.kid());
+
+    // L'ancienne clé est publiée (si elle ne l'était pas encore) puis retirée au profit de la nouvelle
+    current.publish(&pool).await?;
+    next.publish(&pool).await?;
+
+    fs::rename(&path, &previous_path)?;
+    fs::rename(&next_path, &path)?;
+    println!(
+        "✅ Clé {} installée ; l'ancienne clé reste publiée dans le JWKS. Redémarrez les serveurs.",
+        next.kid()
+    );
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use chrono::Utc;
+use dotenvy::dotenv;
+use sqlx::PgPool;
+use std::env;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+#[allow(dead_code)]
+#[path = "../encryption.rs"]
+mod encryption;
+
+use encryption::{MasterKey, MasterKeyring, sibling_path};
+
+// Rotation de la clé maître : les clés de données sont ré-enveloppées, les données chiffrées ne changent pas.
+//   cargo run --bin rotate_master_key             rotation
+//   cargo run --bin rotate_master_key -- --init   crée la première clé maître
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+    let path = encryption::master_key_path();
+
+    if env::args().any(|a| a == "--init") {
+        if path.exists() {
+            return Err(format!("{} existe déjà", path.display()).into());
+        }
+        let key = MasterKey::generate(&path)?;
+        println!("✅ Clé maître {} créée dans {}", key.id(), path.display());
+        return Ok(());
+    }
+
+    let next_path = sibling_path(&path, "next");
+    let previous_path = sibling_path(&path, "previous");
+
+    // Interruption entre les deux renommages : il ne reste qu'à installer la nouvelle clé
+    if !path.exists() && next_path.exists() {
+        fs::rename(&next_path, &path)?;
+        println!("✅ Rotation précédente terminée, clé maître {} installée", MasterKey::load(&path)?.id());
+        return Ok(());
+    }
+
+    let keyring = MasterKeyring::load(&path)?;
+    // Une rotation interrompue est reprise avec la même nouvelle clé
+    let next = if next_path.exists() {
+        MasterKey::load(&next_path)?
+    } else {
+        MasterKey::generate(&next_path)?
+    };
+    println!("🔑 Rotation {} -> {}", keyring.primary().id(), next.id());
+
+    let pool = PgPool::connect(&env::var("DATABASE_URL")?).await?;
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query!("SELECT user_id, master_key_id, wrapped_key FROM user_data_keys FOR UPDATE")
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let mut rewrapped = 0;
+    for row in &rows {
+        if row.master_key_id == next.id() {
+            continue;
+        }
+        sqlx::query!(
+            "UPDATE user_data_keys SET master_key_id = $2, wrapped_key = $3, rotated_at = $4 WHERE user_id = $1",
+            row.user_id,
+            next.id(),
+            rewrap(&keyring, &next, row.user_id, &row.master_key_id, &row.wrapped_key)?,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        rewrapped += 1;
+    }
+    tx.commit().await?;
+
+    promote_next(&path)?;
+
+    println!("✅ {} clé(s) de données ré-enveloppée(s) sur {}", rewrapped, rows.len());
+    println!("📋 Redémarrez le serveur, relancez la rotation s'il a créé des clés entre-temps,");
+    println!("   puis supprimez {} une fois la nouvelle clé sauvegardée.", previous_path.display());
+    Ok(())
+}
+
+// Clé de données d'un utilisateur, ré-enveloppée par la nouvelle clé maître
+fn rewrap(
+    keyring: &MasterKeyring,
+    next: &MasterKey,
+    user_id: Uuid,
+    master_key_id: &str,
+    wrapped_key: &str,
+) -> encryption::CryptoResult<String> {
+    let current = keyring
+        .find(master_key_id)
+        .ok_or(format!("Clé maître {} introuvable pour l'utilisateur {}", master_key_id, user_id))?;
+    next.wrap(user_id, &current.unwrap(user_id, wrapped_key)?)
+}
+
+// Bascule : courante -> .previous, .next -> courante
+fn promote_next(path: &Path) -> std::io::Result<()> {
+    fs::rename(path, sibling_path(path, "previous"))?;
+    fs::rename(sibling_path(path, "next"), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm::aead::{KeyInit, OsRng};
+    use aes_gcm::Aes256Gcm;
+
+    #[test]
+    fn rotated_keys_open_with_the_new_master_and_old_ones_with_previous() {
+        let dir = env::temp_dir().join(format!("rotation-{}", Uuid::new_v4()));
+        let path = dir.join("master.key");
+        let old = MasterKey::generate(&path).unwrap();
+        let (user, late_user) = (Uuid::new_v4(), Uuid::new_v4());
+        let (data_key, late_data_key) = (Aes256Gcm::generate_key(OsRng), Aes256Gcm::generate_key(OsRng));
+        let wrapped = old.wrap(user, &data_key).unwrap();
+
+        // Rotation : `.next` est lue avec la clé courante pendant le ré-enveloppement
+        let next = MasterKey::generate(&sibling_path(&path, "next")).unwrap();
+        let keyring = MasterKeyring::load(&path).unwrap();
+        assert!(keyring.find(next.id()).is_some());
+        let rewrapped = rewrap(&keyring, &next, user, old.id(), &wrapped).unwrap();
+        // Une clé créée par le serveur pendant la rotation reste enveloppée par l'ancienne clé
+        let late_wrapped = old.wrap(late_user, &late_data_key).unwrap();
+        promote_next(&path).unwrap();
+
+        let keyring = MasterKeyring::load(&path).unwrap();
+        assert_eq!(keyring.primary().id(), next.id());
+        assert!(!sibling_path(&path, "next").exists());
+        assert_eq!(keyring.find(next.id()).unwrap().unwrap(user, &rewrapped).unwrap(), data_key);
+        let previous = keyring.find(old.id()).expect("ancienne clé lue depuis .previous");
+        assert_eq!(previous.unwrap(late_user, &late_wrapped).unwrap(), late_data_key);
+
+        // Le ré-enveloppement reste lié à l'utilisateur
+        assert!(rewrap(&keyring, &next, late_user, next.id(), &rewrapped).is_err());
+        assert!(rewrap(&keyring, &next, user, "inconnue", &rewrapped).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::Arc;
+use crate::hedera_client::HederaLedger;
+use crate::simulated_ledger::SimulatedLedger;
+
+// Registre sur lequel les empreintes sont ancrées.
+// Hedera en production, registre simulé en local et pour les tests.
+
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerError {
+    /// Variables d'environnement ou clés du backend absentes ou invalides
+    #[error("configuration du registre invalide: {0}")]
+    Config(String),
+    /// Registre ou mirror node injoignable, ou en erreur
+    #[error("registre indisponible: {0}")]
+    Unavailable(String),
+    /// Consensus atteint, mais la transaction a échoué
+    #[error("transaction {transaction_id} refusée par le registre: {status}")]
+    Rejected { transaction_id: String, status: String },
+    /// Réponse ou enregistrement du registre illisible
+    #[error("réponse du registre invalide: {0}")]
+    InvalidResponse(String),
+    #[error("identifiant de transaction invalide: {0}")]
+    InvalidTransactionId(String),
+    /// Lot Merkle non ancré (batcher arrêté, empreinte invalide, soumission du lot échouée)
+    #[error("ancrage du lot échoué: {0}")]
+    Batch(String),
+    #[error("registre simulé: {0}")]
+    Storage(#[from] std::io::Error),
+}
+
+impl From<hedera::Error> for LedgerError {
+    fn from(e: hedera::Error) -> Self {
+        LedgerError::Unavailable(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for LedgerError {
+    fn from(e: reqwest::Error) -> Self {
+        LedgerError::Unavailable(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for LedgerError {
+    fn from(e: serde_json::Error) -> Self {
+        LedgerError::InvalidResponse(e.to_string())
+    }
+}
+
+impl From<hex::FromHexError> for LedgerError {
+    fn from(e: hex::FromHexError) -> Self {
+        LedgerError::InvalidResponse(e.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for LedgerError {
+    fn from(e: base64::DecodeError) -> Self {
+        LedgerError::InvalidResponse(e.to_string())
+    }
+}
+
+pub type LedgerResult<T> = Result<T, LedgerError>;
+
+// Résultat d'une soumission confirmée par le consensus
+#[derive(Debug, Clone)]
+pub struct LedgerSubmission {
+    pub transaction_id: String,
+    pub topic_sequence_number: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptStatus {
+    Success,
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct LedgerReceipt {
+    pub transaction_id: String,
+    pub status: ReceiptStatus,
+    pub topic_sequence_number: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerMessage {
+    pub transaction_id: String,
+    pub topic_id: String,
+    pub consensus_timestamp: DateTime<Utc>,
+    pub sequence_number: u64,
+    pub running_hash: String,
+    #[serde(with = "hex::serde")]
+    pub contents: Vec<u8>,
+}
+
+#[async_trait]
+pub trait LedgerAnchor: Send + Sync {
+    /// Nom du backend, pour les journaux.
+    fn name(&self) -> &'static str;
+
+    /// Réseau enregistré sur chaque preuve ancrée par ce backend.
+    fn network(&self) -> &str;
+
+    /// Identifiant de transaction généré avant la soumission, pour pouvoir interroger
+    /// son reçu après un échec au lieu de soumettre une seconde fois.
+    fn new_transaction_id(&self) -> LedgerResult<String>;
+
+    /// Soumet un message au topic sous `transaction_id` et attend le consensus.
+    /// Une transaction déjà passée au consensus n'est jamais resoumise.
+    async fn submit_message(&self, transaction_id: &str, message: Vec<u8>) -> LedgerResult<LedgerSubmission>;
+
+    /// Reçu d'une transaction déjà soumise.
+    async fn fetch_receipt(&self, transaction_id: &str) -> LedgerResult<LedgerReceipt>;
+
+    /// Message ancré par une transaction, s'il est connu du registre.
+    async fn fetch_message(&self, transaction_id: &str) -> LedgerResult<Option<LedgerMessage>>;
+}
+
+/// Backend choisi par `LEDGER_BACKEND` : `hedera` (défaut) ou `simulated`.
+pub fn from_env() -> LedgerResult<Arc<dyn LedgerAnchor>> {
+    let ledger: Arc<dyn LedgerAnchor> = match env::var("LEDGER_BACKEND").as_deref() {
+        Ok("simulated") => Arc::new(SimulatedLedger::from_env()?),
+        Ok("hedera") | Err(_) => Arc::new(HederaLedger::from_env()?),
+        Ok(other) => return Err(LedgerError::Config(format!("LEDGER_BACKEND inconnu: {}", other))),
+    };
+    println!("⛓️  Registre d'ancrage: {}", ledger.name());
+    Ok(ledger)
+}
+
+// Fenêtre de validité d'une transaction Hedera, avec une marge pour les horloges décalées
+const TRANSACTION_VALID_DURATION_SECS: i64 = 180;
+
+/// Vrai si la transaction `compte@secondes.nanos` ne peut plus être acceptée par le réseau.
+/// Un identifiant illisible est considéré comme expiré.
+pub fn transaction_expired(transaction_id: &str, now: DateTime<Utc>) -> bool {
+    let valid_start = transaction_id
+        .split_once('@')
+        .map(|(_, start)| start.split('?').next().unwrap_or(start))
+        .and_then(|start| {
+            let (secs, nanos) = start.split_once('.').unwrap_or((start, "0"));
+            DateTime::from_timestamp(secs.parse().ok()?, nanos.parse().ok()?)
+        });
+    match valid_start {
+        Some(start) => now > start + chrono::Duration::seconds(TRANSACTION_VALID_DURATION_SECS),
+        None => true,
+    }
+}
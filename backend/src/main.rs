@@ -4,11 +4,42 @@ use actix_files::Files;
 use sqlx::PgPool;
 use dotenvy::dotenv;
 use std::env;
+use std::sync::Arc;
 
 mod models;
+mod error;
+mod i18n;
+mod notifications;
 mod routes;
 mod ai_client;
+mod network;
+mod ledger;
 mod hedera_client;
+mod simulated_ledger;
+mod mirror_node;
+mod reconciler;
+mod proof_document;
+mod merkle;
+mod anchoring;
+mod proof_worker;
+mod evaluations;
+mod search;
+mod summary_worker;
+mod encryption;
+mod store;
+mod auth;
+mod wallet_login;
+mod wallet;
+mod inventor_signature;
+mod certificate_pdf;
+mod certificate_signing;
+mod claims;
+mod diff;
+mod verification_page;
+
+fn invalid_request(key: &'static str, detail: impl std::fmt::Display) -> actix_web::Error {
+    error::AppError::InvalidRequest(i18n::Message::new(key).with("detail", detail)).into()
+}
 
 async fn create_pool() -> PgPool {
     dotenv().ok();
@@ -16,9 +47,60 @@ async fn create_pool() -> PgPool {
     PgPool::connect(&database_url).await.expect("Failed to connect to DB")
 }
 
+// Chiffre en arrière-plan les idées et résumés enregistrés avant l'activation du chiffrement
+fn spawn_legacy_encryption(pool: PgPool, vault: Arc<encryption::Vault>) {
+    tokio::spawn(async move {
+        match store::encrypt_legacy_rows(&pool, &vault).await {
+            Ok((0, 0)) => {}
+            Ok((ideas, summaries)) => println!("🔐 {} idée(s) et {} résumé(s) chiffrés", ideas, summaries),
+            Err(e) => eprintln!("Erreur chiffrement des données existantes: {}", e),
+        }
+    });
+}
+
+// Indexe en arrière-plan les résumés déjà enregistrés pour la recherche d'antériorités, puis toutes les
+// `SEARCH_REFRESH_SECS` secondes (30 par défaut) ceux enregistrés par les autres instances
+fn spawn_search_index(pool: PgPool, vault: Arc<encryption::Vault>, index: Arc<search::SearchIndex>) {
+    let interval = env::var("SEARCH_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30)
+        .max(1);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            match index.refresh(&pool, &vault).await {
+                Ok(0) => {}
+                Ok(count) => println!("🔎 Index de recherche: {} résumé(s) indexé(s)", count),
+                Err(e) => eprintln!("Erreur mise à jour de l'index de recherche: {}", e),
+            }
+        }
+    });
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let pool = web::Data::new(create_pool().await);
+    let ledger = ledger::from_env().expect("Configuration du registre d'ancrage invalide");
+    let ai = ai_client::from_env().expect("Configuration du service IA invalide");
+    let vault = Arc::new(encryption::Vault::from_env(pool.get_ref()).await.expect("Clé maître de chiffrement indisponible"));
+    reconciler::spawn_from_env(pool.get_ref().clone(), ledger.clone());
+    let anchoring = Arc::new(anchoring::Anchoring::from_env(ledger, pool.get_ref().clone()));
+    proof_worker::spawn_from_env(pool.get_ref().clone(), anchoring.clone(), vault.clone());
+    let search = Arc::new(search::SearchIndex::new().expect("Index de recherche indisponible"));
+    let summaries = web::Data::from(summary_worker::spawn_from_env(pool.get_ref().clone(), vault.clone(), ai, search.clone()));
+    spawn_legacy_encryption(pool.get_ref().clone(), vault.clone());
+    spawn_search_index(pool.get_ref().clone(), vault.clone(), search.clone());
+    let search = web::Data::from(search);
+    let anchoring = web::Data::from(anchoring);
+    let vault = web::Data::from(vault);
+    let sessions = web::Data::new(auth::SessionKeys::from_env());
+    let signer = web::Data::new(
+        certificate_signing::CertificateSigner::from_env(pool.get_ref())
+            .await
+            .expect("Clé de signature des certificats indisponible"),
+    );
 
     println!("🚀 Backend MVP BrevetChain démarré sur http://127.0.0.1:8080");
 
@@ -26,7 +108,7 @@ async fn main() -> std::io::Result<()> {
         let cors = Cors::default()
             .allowed_origin("http://localhost:3000")
             .allowed_origin("http://127.0.0.1:3000")
-            .allowed_methods(vec!["GET", "POST"])
+            .allowed_methods(vec!["GET", "POST", "PUT"])
             .allowed_headers(vec![
                 actix_web::http::header::CONTENT_TYPE,
                 actix_web::http::header::AUTHORIZATION,
@@ -36,21 +118,60 @@ async fn main() -> std::io::Result<()> {
             .max_age(3600);
 
         App::new()
+            .wrap(middleware::from_fn(error::localize)) // ✅ Messages d'erreur dans la langue de la requête
             .wrap(cors)
             .wrap(middleware::Logger::default())
             .app_data(pool.clone())
+            .app_data(anchoring.clone())
+            .app_data(summaries.clone())
+            .app_data(search.clone())
+            .app_data(vault.clone())
+            .app_data(sessions.clone())
+            .app_data(signer.clone())
+            // Corps JSON, chemin ou paramètres illisibles : même format d'erreur que les handlers
+            .app_data(web::JsonConfig::default().error_handler(|e, _| invalid_request("error.invalid_body", e)))
+            .app_data(web::PathConfig::default().error_handler(|e, _| invalid_request("error.invalid_path", e)))
+            .app_data(web::QueryConfig::default().error_handler(|e, _| invalid_request("error.invalid_query", e)))
+            .route("/.well-known/jwks.json", web::get().to(routes::get_jwks)) // ✅ Vérification hors ligne des certificats
+            .route("/verify/{proof_id}", web::get().to(routes::get_verification_page)) // ✅ Page publique (QR code)
             .service(
                 web::scope("/api/v1")
                     .route("/register", web::post().to(routes::register_user)) // ✅ Création de compte OBLIGATOIRE
-                    .route("/submit-idea", web::post().to(routes::submit_idea)) // ✅ Fonction 1
-                    .route("/generate-summary/{idea_id}", web::post().to(routes::generate_summary)) // ✅ Fonction 2
-                    .route("/register-proof/{summary_id}", web::post().to(routes::register_proof)) // ✅ Fonction 3
-                    .route("/certificate/{summary_id}", web::get().to(routes::get_certificate)) // ✅ Fonction 4
-                    .route("/status/{idea_id}", web::get().to(routes::get_status)) // ✅ Fonction 5
+                    .route("/login", web::post().to(routes::login))
+                    .route("/wallet/challenge", web::post().to(routes::wallet_challenge)) // ✅ Connexion par wallet
+                    .route("/wallet/login", web::post().to(routes::wallet_login))
+                    .route("/verify", web::post().to(routes::verify_proof)) // ✅ Vérification publique
+                    .route("/verify/{proof_id}", web::get().to(routes::get_proof_verification))
+                    .route("/anchors/{transaction_id}", web::get().to(routes::get_anchor))
                     .route("/health", web::get().to(routes::health)) // ✅ Fonction 7
                     // Fonction 6 (CRUD agents/offices) est structurée mais désactivée → placeholder
                     .route("/agent/register", web::post().to(routes::agent_register_placeholder))
                     .route("/office/register", web::post().to(routes::office_register_placeholder))
+                    // ✅ Routes réservées à l'auteur de l'idée : session obligatoire
+                    .service(
+                        web::scope("")
+                            .wrap(middleware::from_fn(auth::require_session))
+                            .route("/submit-idea", web::post().to(routes::submit_idea)) // ✅ Fonction 1
+                            .route("/generate-summary/{idea_id}", web::post().to(routes::generate_summary)) // ✅ Fonction 2
+                            .route("/generate-summary/{idea_id}/stream", web::get().to(routes::stream_summary))
+                            .route("/jobs/{job_id}", web::get().to(routes::get_job))
+                            .route("/ideas/{idea_id}/evaluations", web::get().to(routes::list_evaluations))
+                            .route("/search", web::get().to(routes::search_summaries)) // ✅ Recherche d'antériorités
+                            .route("/evaluations/{evaluation_id}/review", web::post().to(routes::request_review)) // ✅ Revue humaine d'un refus
+                            .route("/summary/{summary_id}", web::get().to(routes::get_summary))
+                            .route("/summary/{summary_id}/revisions", web::get().to(routes::list_summary_revisions))
+                            .route("/summary/{summary_id}/revisions", web::post().to(routes::revise_summary)) // ✅ Modification = nouvelle révision
+                            .route("/summary/{summary_id}/diff/{other_id}", web::get().to(routes::diff_summary_revisions))
+                            .route("/signing-payload/{summary_id}", web::get().to(routes::get_signing_payload))
+                            .route("/register-proof/{summary_id}", web::post().to(routes::register_proof)) // ✅ Fonction 3
+                            .route("/certificate/{summary_id}.pdf", web::get().to(routes::get_certificate_pdf))
+                            .route("/certificate/{summary_id}.jws", web::get().to(routes::get_certificate_jws))
+                            .route("/certificate/{summary_id}", web::get().to(routes::get_certificate)) // ✅ Fonction 4
+                            .route("/disclosure/{summary_id}", web::put().to(routes::set_title_disclosure))
+                            .route("/status/{idea_id}", web::get().to(routes::get_status)) // ✅ Fonction 5
+                            .route("/account/language", web::put().to(routes::set_preferred_language))
+                            .route("/notifications", web::get().to(routes::list_notifications))
+                    )
             )
             .service(Files::new("/", "../frontend").index_file("index.html"))
     })
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Arbre de Merkle SHA-256 avec séparation de domaine (RFC 6962) :
+// feuille = H(0x00 || empreinte), nœud = H(0x01 || gauche || droite).
+// Un nœud sans frère remonte tel quel au niveau supérieur.
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SiblingPosition {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MerkleStep {
+    pub sibling: String,
+    pub position: SiblingPosition,
+}
+
+pub struct MerkleTree {
+    // levels[0] = feuilles, dernier niveau = racine
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+fn leaf_node(leaf: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(leaf);
+    hasher.finalize().into()
+}
+
+fn inner_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+impl MerkleTree {
+    /// Construit l'arbre à partir des empreintes hexadécimales des preuves.
+    pub fn from_hex_leaves(leaves: &[String]) -> Result<Self, hex::FromHexError> {
+        let mut level = Vec::with_capacity(leaves.len());
+        for leaf in leaves {
+            level.push(leaf_node(&hex::decode(leaf)?));
+        }
+
+        let mut levels = vec![level];
+        while levels.last().map(|l| l.len() > 1).unwrap_or(false) {
+            let current = levels.last().unwrap();
+            let next = current
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => inner_node(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Ok(MerkleTree { levels })
+    }
+
+    pub fn root_hex(&self) -> String {
+        self.levels
+            .last()
+            .and_then(|l| l.first())
+            .map(hex::encode)
+            .unwrap_or_default()
+    }
+
+    /// Chemin d'inclusion de la feuille `index`, de la feuille vers la racine.
+    pub fn inclusion_path(&self, index: usize) -> Vec<MerkleStep> {
+        let mut path = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len().saturating_sub(1)] {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                path.push(MerkleStep {
+                    sibling: hex::encode(level[sibling]),
+                    position: if sibling < position { SiblingPosition::Left } else { SiblingPosition::Right },
+                });
+            }
+            position /= 2;
+        }
+        path
+    }
+}
+
+/// Vérifie qu'une empreinte appartient à l'arbre de racine `root` via son chemin d'inclusion.
+pub fn verify_inclusion(leaf_hex: &str, path: &[MerkleStep], root_hex: &str) -> bool {
+    let Ok(leaf) = hex::decode(leaf_hex) else { return false };
+    let mut node = leaf_node(&leaf);
+    for step in path {
+        let Ok(sibling) = hex::decode(&step.sibling) else { return false };
+        let Ok(sibling) = <[u8; 32]>::try_from(sibling.as_slice()) else { return false };
+        node = match step.position {
+            SiblingPosition::Left => inner_node(&sibling, &node),
+            SiblingPosition::Right => inner_node(&node, &sibling),
+        };
+    }
+    hex::encode(node).eq_ignore_ascii_case(root_hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Empreintes de test : 32 octets identiques valant l'indice de la feuille
+    fn leaves(count: u8) -> Vec<String> {
+        (0..count).map(|i| hex::encode([i; 32])).collect()
+    }
+
+    #[test]
+    fn known_roots() {
+        let vectors = [
+            (1, "7f9c9e31ac8256ca2f258583df262dbc7d6f68f2a03043d5c99a4ae5a7396ce9"),
+            (2, "28fb81e496897e0ce886f08602392e9239b65c659041e5202163e58ad898f444"),
+            (3, "ba8d94b7fbcecae7b81c4c80574fe24734a6917bf9c1ecd66ff3e0c34ead4620"),
+            (5, "85e20cac1f02fda7bcdb2fc3f908568c57018c77815f1fa361acad13994f08bf"),
+        ];
+        for (count, root) in vectors {
+            assert_eq!(MerkleTree::from_hex_leaves(&leaves(count)).unwrap().root_hex(), root, "{} feuille(s)", count);
+        }
+    }
+
+    #[test]
+    fn odd_node_is_promoted_unchanged() {
+        let leaves = leaves(3);
+        let tree = MerkleTree::from_hex_leaves(&leaves).unwrap();
+        let [a, b, c] = [0, 1, 2].map(|i| leaf_node(&hex::decode(&leaves[i]).unwrap()));
+        assert_eq!(tree.root_hex(), hex::encode(inner_node(&inner_node(&a, &b), &c)));
+        // La feuille promue n'a qu'un frère, à gauche, au niveau supérieur
+        assert_eq!(
+            tree.inclusion_path(2),
+            vec![MerkleStep { sibling: hex::encode(inner_node(&a, &b)), position: SiblingPosition::Left }]
+        );
+    }
+
+    #[test]
+    fn every_leaf_verifies_against_the_root() {
+        for count in 1..=9 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::from_hex_leaves(&leaves).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                assert!(verify_inclusion(leaf, &tree.inclusion_path(index), &tree.root_hex()));
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_path_is_rejected() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::from_hex_leaves(&leaves).unwrap();
+        let root = tree.root_hex();
+        let path = tree.inclusion_path(1);
+        assert!(verify_inclusion(&leaves[1], &path, &root));
+
+        let mut sibling = path.clone();
+        sibling[0].sibling = hex::encode([0xff; 32]);
+        assert!(!verify_inclusion(&leaves[1], &sibling, &root));
+
+        let mut position = path.clone();
+        position[0].position = SiblingPosition::Right;
+        assert!(!verify_inclusion(&leaves[1], &position, &root));
+
+        assert!(!verify_inclusion(&leaves[1], &path[1..], &root));
+        assert!(!verify_inclusion(&leaves[2], &path, &root));
+    }
+}
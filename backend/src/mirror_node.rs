@@ -0,0 +1,160 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+use crate::ledger::{LedgerError, LedgerMessage, LedgerResult};
+use crate::network;
+
+// Client REST du mirror node Hedera.
+// L'URL de base est configurable pour pointer vers un nœud local ou un simulateur.
+
+#[derive(Clone)]
+pub struct MirrorNodeClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct MirrorTransactions {
+    transactions: Vec<MirrorTransaction>,
+}
+
+#[derive(Deserialize)]
+struct MirrorTransaction {
+    consensus_timestamp: String,
+}
+
+#[derive(Deserialize)]
+struct MirrorTopicMessage {
+    consensus_timestamp: String,
+    message: String,
+    running_hash: String,
+    sequence_number: u64,
+    topic_id: String,
+}
+
+#[derive(Deserialize)]
+struct MirrorAccount {
+    key: Option<MirrorKey>,
+}
+
+#[derive(Deserialize)]
+struct MirrorKey {
+    #[serde(rename = "_type")]
+    key_type: String,
+    key: String,
+}
+
+// "0.0.123@1700000000.000000001" (SDK) -> "0.0.123-1700000000-000000001" (mirror node)
+pub fn mirror_transaction_id(transaction_id: &str) -> String {
+    match transaction_id.split_once('@') {
+        Some((account, valid_start)) => format!("{}-{}", account, valid_start.replacen('.', "-", 1)),
+        None => transaction_id.to_string(),
+    }
+}
+
+// "1700000000.000000001" -> horodatage UTC
+pub fn parse_mirror_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let (secs, nanos) = value.split_once('.').unwrap_or((value, "0"));
+    DateTime::from_timestamp(secs.parse().ok()?, nanos.parse().ok()?)
+}
+
+impl MirrorNodeClient {
+    pub fn new(base_url: &str) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+        MirrorNodeClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    /// Mirror node du réseau d'une preuve, `None` pour un réseau sans mirror node (simulé).
+    pub fn for_network(network: &str) -> Option<Self> {
+        network::mirror_url_for(network).map(|url| Self::new(&url))
+    }
+
+    /// Message HCS publié par une transaction, `None` si le mirror node ne le connaît pas (encore).
+    pub async fn topic_message_by_transaction(&self, transaction_id: &str) -> LedgerResult<Option<LedgerMessage>> {
+        let url = format!("{}/api/v1/transactions/{}", self.base_url, mirror_transaction_id(transaction_id));
+        let response = self.http.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let transactions: MirrorTransactions = response.error_for_status()?.json().await?;
+        let Some(transaction) = transactions.transactions.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let url = format!("{}/api/v1/topics/messages/{}", self.base_url, transaction.consensus_timestamp);
+        let response = self.http.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let message: MirrorTopicMessage = response.error_for_status()?.json().await?;
+
+        Ok(Some(LedgerMessage {
+            transaction_id: transaction_id.to_string(),
+            topic_id: message.topic_id,
+            consensus_timestamp: parse_mirror_timestamp(&message.consensus_timestamp)
+                .ok_or_else(|| LedgerError::InvalidResponse("horodatage mirror node invalide".to_string()))?,
+            sequence_number: message.sequence_number,
+            running_hash: hex::encode(BASE64.decode(&message.running_hash)?),
+            contents: BASE64.decode(&message.message)?,
+        }))
+    }
+
+    /// Clé publique d'un compte, `None` si le compte est inconnu ou n'a pas de clé simple
+    /// (les clés à seuil ou listes de clés ne permettent pas la connexion par signature).
+    pub async fn account_public_key(&self, account: &str) -> LedgerResult<Option<hedera::PublicKey>> {
+        let url = format!("{}/api/v1/accounts/{}", self.base_url, account);
+        let response = self.http.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let account: MirrorAccount = response.error_for_status()?.json().await?;
+        let Some(key) = account.key else {
+            return Ok(None);
+        };
+        let bytes = hex::decode(&key.key)?;
+        Ok(match key.key_type.as_str() {
+            "ED25519" => Some(hedera::PublicKey::from_bytes_ed25519(&bytes).map_err(invalid_key)?),
+            "ECDSA_SECP256K1" => Some(hedera::PublicKey::from_bytes_ecdsa(&bytes).map_err(invalid_key)?),
+            _ => None,
+        })
+    }
+}
+
+fn invalid_key(e: hedera::Error) -> LedgerError {
+    LedgerError::InvalidResponse(format!("clé publique du compte: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_sdk_transaction_ids_to_the_mirror_format() {
+        assert_eq!(
+            mirror_transaction_id("0.0.123@1700000000.000000001"),
+            "0.0.123-1700000000-000000001"
+        );
+        // Un identifiant déjà au format du mirror node est laissé tel quel
+        assert_eq!(mirror_transaction_id("0.0.123-1700000000-000000001"), "0.0.123-1700000000-000000001");
+    }
+
+    #[test]
+    fn parses_mirror_timestamps_to_the_nanosecond() {
+        let timestamp = parse_mirror_timestamp("1700000000.000000001").unwrap();
+        assert_eq!(timestamp.timestamp(), 1_700_000_000);
+        assert_eq!(timestamp.timestamp_subsec_nanos(), 1);
+        assert_eq!(parse_mirror_timestamp("1700000000").unwrap().timestamp_subsec_nanos(), 0);
+
+        assert!(parse_mirror_timestamp("").is_none());
+        assert!(parse_mirror_timestamp("1700000000.abc").is_none());
+        assert!(parse_mirror_timestamp("1700000000.1000000000000").is_none());
+    }
+}
@@ -1,8 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::merkle::MerkleStep;
 
+#[allow(dead_code)] // Lu par les futurs écrans de profil
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -12,23 +14,69 @@ pub struct User {
     pub country: Option<String>,
     pub wallet_address: String,
     pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub password_hash: Option<String>,
+    pub wallet_public_key: Option<String>,
+    pub wallet_verified_at: Option<DateTime<Utc>>,
+    pub wallet_type: Option<String>,
+    pub preferred_language: Option<String>,
+    pub plan: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RegisterUserRequest {
     pub full_name: String,
     pub email: String,
+    pub password: Option<String>, // ✅ Facultatif : un compte sans mot de passe se connecte par wallet
     pub phone: Option<String>,
     pub country: Option<String>,
     pub wallet_address: String,
+    pub preferred_language: Option<String>, // ✅ Facultatif : sinon la langue du navigateur
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RegisterUserResponse {
     pub user_id: Uuid,
+    pub wallet_address: String,
+    pub wallet_type: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub preferred_language: Option<String>,
     pub message: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletChallengeRequest {
+    pub wallet_address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletChallengeResponse {
+    pub challenge_id: Uuid,
+    pub message: String, // Texte exact à signer avec le wallet
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletLoginRequest {
+    pub challenge_id: Uuid,
+    pub signature: String, // hex
+    pub public_key: Option<String>, // DER ou brute en hex ; sans mirror node, requise à la première connexion
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionResponse {
+    pub user_id: Uuid,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Idea {
     pub id: Uuid,
@@ -39,8 +87,7 @@ pub struct Idea {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubmitIdeaRequest {
-    pub user_id: Uuid, // ✅ Obligatoire — l'utilisateur doit être enregistré
-    pub raw_idea: String,
+    pub raw_idea: String, // ✅ L'auteur est l'utilisateur de la session
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,9 +103,123 @@ pub struct Summary {
     pub title: String,
     pub problem: String,
     pub solution: String,
-    pub claim: String, // ✅ MVP: une seule revendication principale
+    pub claim: String, // Revendication n° 1 ; le jeu complet est dans la table `claims`
     pub cpc_code: String,
     pub created_at: DateTime<Utc>,
+    pub original_id: Uuid, // Première révision du résumé
+    pub revision: i32,
+    pub author_id: Uuid,
+}
+
+// Revendication numérotée ; une revendication dépendante renvoie à une revendication antérieure
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Claim {
+    pub number: i32,
+    pub depends_on: Option<i32>,
+    pub text: String,
+}
+
+// Contenu d'un résumé qui entre dans le document de preuve haché
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryDocument {
+    pub title: String,
+    pub problem: String,
+    pub solution: String,
+    #[serde(default)]
+    pub claim: String, // Revendication unique des schémas `legacy` et `jcs-v1`
+    pub cpc_code: String,
+    #[serde(default)]
+    pub claims: Vec<Claim>, // Jeu de revendications haché à partir de `jcs-v2`
+    #[serde(default)]
+    pub previous_hash: Option<String>, // Empreinte de la révision antérieure ancrée (`jcs-v3`)
+}
+
+// Révision d'un résumé avec son jeu de revendications
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryResponse {
+    pub summary_id: Uuid,
+    pub idea_id: Uuid,
+    pub original_id: Uuid,
+    pub revision: i32,
+    pub latest_revision: i32,
+    pub author_id: Uuid,
+    pub created_at: String,
+    pub title: String,
+    pub problem: String,
+    pub solution: String,
+    pub claim: String,
+    pub claims: Vec<Claim>,
+    pub cpc_code: String,
+}
+
+// Modification d'un résumé : les champs absents reprennent la révision modifiée,
+// `claims` remplace le jeu complet
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReviseSummaryRequest {
+    pub title: Option<String>,
+    pub problem: Option<String>,
+    pub solution: Option<String>,
+    pub cpc_code: Option<String>,
+    pub claims: Option<Vec<Claim>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryRevision {
+    pub summary_id: Uuid,
+    pub revision: i32,
+    pub author_id: Uuid,
+    pub created_at: String,
+    pub proof_hash: Option<String>,
+    pub proof_status: Option<String>, // pending | anchored | failed
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+// Segment de texte d'un diff par mots ; les espaces font partie des segments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TextChange {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldDiff {
+    pub field: String,
+    pub changes: Vec<TextChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaimDiff {
+    pub number: i32,
+    pub change: ClaimChange,
+    pub depends_on_before: Option<i32>,
+    pub depends_on_after: Option<i32>,
+    pub changes: Vec<TextChange>,
+}
+
+// Différences entre deux révisions ; seuls les champs et revendications modifiés sont listés
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevisionDiffResponse {
+    pub from_summary_id: Uuid,
+    pub from_revision: i32,
+    pub to_summary_id: Uuid,
+    pub to_revision: i32,
+    pub fields: Vec<FieldDiff>,
+    pub claims: Vec<ClaimDiff>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -66,9 +227,21 @@ pub struct AiResponse {
     pub title: String,
     pub problem: String,
     pub solution: String,
-    pub claim: String, // ✅ Simplifié pour MVP
+    #[serde(default)]
+    pub claim: String, // Ancien contrat : une seule revendication
+    #[serde(default)]
+    pub claims: Vec<AiClaim>, // Revendications dans l'ordre, numérotées à partir de 1
     pub cpc_code: String,
     pub novelty_score: u8,
+    #[serde(default)]
+    pub model: Option<String>, // Modèle ayant produit la réponse, si le service l'indique
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AiClaim {
+    pub text: String,
+    #[serde(default)]
+    pub depends_on: Option<i32>, // Numéro de la revendication parente
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
@@ -79,14 +252,324 @@ pub struct Proof {
     pub hedera_tx_id: String,
     pub timestamp: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub hash_scheme: String,
+    pub topic_sequence_number: Option<i64>,
+    pub consensus_timestamp: Option<DateTime<Utc>>,
+    pub merkle_root: Option<String>,
+    pub merkle_leaf_index: Option<i32>,
+    pub merkle_path: Option<serde_json::Value>,
+    pub running_hash: Option<String>,
+    pub reconciliation_status: String,
+    pub reconciliation_detail: Option<String>,
+    pub reconciliation_attempts: i32,
+    pub reconciled_at: Option<DateTime<Utc>>,
+    pub network: String,
+    pub inventor_account: Option<String>,
+    pub inventor_public_key: Option<String>,
+    pub inventor_signature: Option<String>,
+    pub title_disclosed: bool,
+    pub previous_hash: Option<String>,
+}
+
+// Tâche d'ancrage de la boîte d'envoi `proof_jobs`, une par résumé
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct ProofJob {
+    pub summary_id: Uuid,
+    pub idempotency_key: Option<String>,
+    pub hash: String,
+    pub hash_scheme: String,
+    pub transaction_id: Option<String>,
+    pub locked_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub status: String, // pending | anchored | failed
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub inventor_account: Option<String>,
+    pub inventor_public_key: Option<String>,
+    pub inventor_signature: Option<String>,
+    pub previous_hash: Option<String>,
+    // Lot Merkle enregistré avant sa soumission (mode batch)
+    pub batch_message: Option<String>,
+    pub merkle_root: Option<String>,
+    pub merkle_leaf_index: Option<i32>,
+    pub merkle_path: Option<serde_json::Value>,
+}
+
+// Corps facultatif de l'enregistrement : co-signature de l'empreinte par l'inventeur
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RegisterProofRequest {
+    pub inventor_signature: Option<String>, // hex, sur le message de `/signing-payload`
+    pub public_key: Option<String>, // facultative, doit être la clé vérifiée du compte
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SigningPayloadResponse {
+    pub summary_id: Uuid,
+    pub hash: String,
+    pub hash_scheme: String,
+    pub wallet_address: String,
+    pub message: String, // Texte exact à signer avec le wallet
+}
+
+// Appartenance d'une preuve à la racine de Merkle ancrée (mode batch)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MerkleProof {
+    pub root: String,
+    pub leaf_index: i32,
+    pub path: Vec<MerkleStep>,
+    pub verified: bool,
 }
 
+// Co-signature de l'inventeur, revérifiée contre l'empreinte ancrée
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InventorCoSignature {
+    pub account: String,
+    pub public_key: String,
+    pub signature: String,
+    pub verified: bool,
+}
+
+// Certificat signé par la plateforme : `jws` est une JWS détachée sur ce même objet sans `jws` (JCS)
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CertificateResponse {
+    pub summary_id: Uuid,
+    pub proof_id: Uuid,
+    pub issuer: String,
+    pub issued_at: String,
+    pub verification_url: String, // Page publique `/verify/{proof_id}`, reprise dans le QR code du PDF
+    pub revision: i32,
+    pub hash: String,
+    pub hash_scheme: String,
+    pub previous_hash: Option<String>, // Révision antérieure ancrée (chaîne de conservation)
+    pub document_verified: bool,
+    pub timestamp: String, // Date d'ancrage
+    pub consensus_timestamp: Option<String>, // Nul tant que le mirror node n'a pas confirmé le consensus
+    pub topic_sequence_number: Option<i64>,
+    pub running_hash: Option<String>,
+    pub reconciliation_status: String,
+    pub network: String,
+    pub hedera_tx_id: String,
+    pub explorer_url: Option<String>,
+    pub merkle: Option<MerkleProof>,
+    pub inventor: Option<InventorCoSignature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jws: Option<String>,
+}
+
+// Vérification publique : le document d'origine, une empreinte brute, ou les deux
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerifyRequest {
+    pub document: Option<SummaryDocument>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyVerdict {
+    Match,
+    NoMatch,
+    HashMismatch,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnchorDetails {
+    pub proof_id: Uuid,
+    pub hedera_tx_id: String,
+    pub consensus_timestamp: Option<String>,
+    pub topic_sequence_number: Option<i64>,
+    pub reconciliation_status: String,
+    pub network: String,
+    pub explorer_url: Option<String>,
+    pub merkle: Option<MerkleProof>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerifyResponse {
+    pub verdict: VerifyVerdict,
+    pub hash: String,
+    pub hash_scheme: Option<String>,
+    pub anchor: Option<AnchorDetails>,
+    pub message: String,
+}
+
+// Fiche publique d'une preuve (`/verify/{proof_id}`) : rien sur l'inventeur, titre sur autorisation
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublicProofResponse {
+    pub proof_id: Uuid,
     pub hash: String,
-    pub timestamp: String,
+    pub hash_scheme: String,
+    pub title: Option<String>,
+    pub previous_hash: Option<String>,
+    pub previous_proof_id: Option<Uuid>,
+    pub registered_at: String,
+    pub consensus_timestamp: Option<String>,
+    pub topic_sequence_number: Option<i64>,
+    pub reconciliation_status: String,
+    pub network: String,
     pub hedera_tx_id: String,
-    pub explorer_url: String,
+    pub explorer_url: Option<String>,
+    pub merkle: Option<MerkleProof>,
+    pub inventor_signed: bool,
+    pub verification_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TitleDisclosureRequest {
+    pub title_disclosed: bool,
+}
+
+// Langue des messages du compte ; `null` revient à la langue du navigateur
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanguagePreferenceRequest {
+    pub preferred_language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanguagePreferenceResponse {
+    pub preferred_language: Option<String>,
+    pub supported_languages: Vec<String>,
+}
+
+// Notification rendue dans la langue du lecteur
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+// Message tel qu'ancré sur le registre, relu par identifiant de transaction
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnchoredMessageResponse {
+    pub ledger: String,
+    pub network: String,
+    pub transaction_id: String,
+    pub topic_id: String,
+    pub consensus_timestamp: String,
+    pub sequence_number: u64,
+    pub running_hash: String,
+    pub message: String,
+}
+
+// Tâche de génération d'un résumé par l'IA
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct SummaryJob {
+    pub id: Uuid,
+    pub idea_id: Uuid,
+    pub user_id: Uuid,
+    pub status: String, // queued | running | succeeded | failed
+    pub attempts: i32,
+    pub locked_until: DateTime<Utc>,
+    pub summary_id: Option<Uuid>,
+    pub error_code: Option<String>,
+    pub error_message: Option<serde_json::Value>, // Message du catalogue, traduit à la lecture
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummaryJobResponse {
+    pub job_id: Uuid,
+    pub idea_id: Uuid,
+    pub status: String,
+    pub summary_id: Option<Uuid>,
+    pub error: Option<JobError>,
+    pub similar: Vec<SearchHitResponse>, // Résumés existants les plus proches, une fois le résumé généré
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Réponse du service IA pour une idée, retenue ou non
+#[derive(Debug, Clone, FromRow)]
+pub struct AiEvaluation {
+    pub id: Uuid,
+    pub idea_id: Uuid,
+    pub job_id: Option<Uuid>,
+    pub summary_id: Option<Uuid>,
+    pub provider: String,
+    pub model: String,
+    pub novelty_score: i16,
+    pub novelty_threshold: i16, // Seuil de la formule de l'inventeur au moment de l'évaluation
+    pub latency_ms: i32,
+    pub accepted: bool,
+    pub rejection_reason: Option<String>, // novelty_below_threshold | invalid_claims
+    pub rejection_message: Option<serde_json::Value>, // Message du catalogue, traduit à la lecture
+    pub response: String, // AiResponse en JSON, chiffrée
+    pub review_status: Option<String>, // requested | approved | dismissed
+    pub review_requested_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>, // Revue approuvée ou rejetée
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AiEvaluationResponse {
+    pub evaluation_id: Uuid,
+    pub idea_id: Uuid,
+    pub job_id: Option<Uuid>,
+    pub summary_id: Option<Uuid>,
+    pub provider: String,
+    pub model: String,
+    pub novelty_score: i16,
+    pub novelty_threshold: i16,
+    pub latency_ms: i32,
+    pub accepted: bool,
+    pub rejection_reason: Option<String>,
+    pub rejection_message: Option<String>,
+    pub response: AiResponse,
+    pub review_status: Option<String>,
+    pub review_requested_at: Option<String>,
+    pub reviewed_at: Option<String>,
+    pub created_at: String,
+}
+
+// Recherche d'antériorités : `cpc` accepte plusieurs préfixes séparés par des virgules (H02J,G06F),
+// `from` et `to` sont des dates incluses (AAAA-MM-JJ)
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    #[serde(default)]
+    pub q: String,
+    pub cpc: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<usize>,
+}
+
+// Résumé trouvé. Le texte n'est visible que pour les résumés de l'appelant ; pour les autres, seul le
+// titre divulgué par leur inventeur apparaît, avec la preuve publique quand elle existe.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchHitResponse {
+    pub own: bool,
+    pub summary_id: Option<Uuid>,
+    pub proof_id: Option<Uuid>,
+    pub title: Option<String>,
+    pub snippet: Option<String>,
+    pub cpc_code: String,
+    pub created_at: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchResponse {
+    pub total: usize,
+    pub hits: Vec<SearchHitResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewRequestResponse {
+    pub evaluation_id: Uuid,
+    pub review_status: String,
+    pub review_requested_at: String,
+    pub message: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -94,6 +577,11 @@ pub struct StatusResponse {
     pub idea_received: bool,
     pub ia_summary_ready: bool,
     pub hedera_proof_registered: bool,
+    pub summary_job_id: Option<Uuid>,
+    pub summary_status: Option<String>, // queued | running | succeeded | failed
+    pub summary_error: Option<String>,
+    pub proof_status: Option<String>, // pending | anchored | failed
+    pub proof_error: Option<String>,
     pub agent_validated: bool, // ✅ Toujours false dans MVP — placeholder
     pub office_submitted: bool, // ✅ Toujours false dans MVP — placeholder
 }
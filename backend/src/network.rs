@@ -0,0 +1,123 @@
+use hedera::{AccountId, Client};
+use std::collections::HashMap;
+use std::env;
+
+// Réseau Hedera cible, partagé par le serveur et les binaires utilitaires.
+// `HEDERA_NETWORK` = local | previewnet | testnet (défaut) | mainnet
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HederaNetwork {
+    Local,
+    Previewnet,
+    Testnet,
+    Mainnet,
+}
+
+impl HederaNetwork {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HederaNetwork::Local => "local",
+            HederaNetwork::Previewnet => "previewnet",
+            HederaNetwork::Testnet => "testnet",
+            HederaNetwork::Mainnet => "mainnet",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "local" | "localhost" => Some(HederaNetwork::Local),
+            "previewnet" => Some(HederaNetwork::Previewnet),
+            "testnet" => Some(HederaNetwork::Testnet),
+            "mainnet" => Some(HederaNetwork::Mainnet),
+            _ => None,
+        }
+    }
+
+    fn default_mirror_url(&self) -> &'static str {
+        match self {
+            HederaNetwork::Local => "http://127.0.0.1:5551",
+            HederaNetwork::Previewnet => "https://previewnet.mirrornode.hedera.com",
+            HederaNetwork::Testnet => "https://testnet.mirrornode.hedera.com",
+            HederaNetwork::Mainnet => "https://mainnet-public.mirrornode.hedera.com",
+        }
+    }
+
+    fn default_explorer_template(&self) -> Option<&'static str> {
+        match self {
+            HederaNetwork::Local => None,
+            HederaNetwork::Previewnet => Some("https://hashscan.io/previewnet/transaction/{tx_id}"),
+            HederaNetwork::Testnet => Some("https://hashscan.io/testnet/transaction/{tx_id}"),
+            HederaNetwork::Mainnet => Some("https://hashscan.io/mainnet/transaction/{tx_id}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    pub network: HederaNetwork,
+    // Nœuds de consensus d'un réseau local : "127.0.0.1:50211=0.0.3,..."
+    local_nodes: String,
+    // Mirror node gRPC utilisé par le SDK pour un réseau local
+    local_mirror_grpc: String,
+}
+
+impl NetworkConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let network = match env::var("HEDERA_NETWORK") {
+            Ok(value) => HederaNetwork::parse(&value).ok_or(format!("HEDERA_NETWORK inconnu: {}", value))?,
+            Err(_) => HederaNetwork::Testnet,
+        };
+        Ok(NetworkConfig {
+            network,
+            local_nodes: env::var("HEDERA_LOCAL_NODES").unwrap_or_else(|_| "127.0.0.1:50211=0.0.3".to_string()),
+            local_mirror_grpc: env::var("HEDERA_LOCAL_MIRROR_GRPC").unwrap_or_else(|_| "127.0.0.1:5600".to_string()),
+        })
+    }
+
+    /// Client SDK pour le réseau configuré (sans opérateur).
+    pub fn client(&self) -> Result<Client, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(match self.network {
+            HederaNetwork::Mainnet => Client::for_mainnet(),
+            HederaNetwork::Testnet => Client::for_testnet(),
+            HederaNetwork::Previewnet => Client::for_previewnet(),
+            HederaNetwork::Local => {
+                let mut nodes = HashMap::new();
+                for entry in self.local_nodes.split(',').filter(|e| !e.trim().is_empty()) {
+                    let (address, account) = entry
+                        .split_once('=')
+                        .ok_or(format!("Entrée HEDERA_LOCAL_NODES invalide: {}", entry))?;
+                    nodes.insert(address.trim().to_string(), account.trim().parse::<AccountId>()?);
+                }
+                let client = Client::for_network(nodes)?;
+                client.set_mirror_network([self.local_mirror_grpc.clone()]);
+                client
+            }
+        })
+    }
+
+    /// API REST du mirror node du réseau configuré (`HEDERA_MIRROR_URL` prioritaire).
+    pub fn mirror_url(&self) -> String {
+        env::var("HEDERA_MIRROR_URL").unwrap_or_else(|_| self.network.default_mirror_url().to_string())
+    }
+}
+
+/// Mirror node REST d'un réseau enregistré sur une preuve. La surcharge `HEDERA_MIRROR_URL`
+/// ne s'applique qu'au réseau actuellement configuré.
+pub fn mirror_url_for(network: &str) -> Option<String> {
+    let network = HederaNetwork::parse(network)?;
+    match NetworkConfig::from_env() {
+        Ok(config) if config.network == network => Some(config.mirror_url()),
+        _ => Some(network.default_mirror_url().to_string()),
+    }
+}
+
+/// Lien explorateur pour une transaction ancrée sur `network`.
+/// `HEDERA_EXPLORER_URL_TEMPLATE_<RÉSEAU>` (ex. `..._LOCAL`) remplace le modèle HashScan par défaut.
+pub fn explorer_url(network: &str, tx_id: &str) -> Option<String> {
+    let override_key = format!("HEDERA_EXPLORER_URL_TEMPLATE_{}", network.to_ascii_uppercase());
+    let template = match env::var(override_key) {
+        Ok(template) => template,
+        Err(_) => HederaNetwork::parse(network)?.default_explorer_template()?.to_string(),
+    };
+    Some(template.replace("{network}", network).replace("{tx_id}", tx_id))
+}
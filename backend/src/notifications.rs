@@ -0,0 +1,81 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::i18n::Locale;
+use crate::models::NotificationResponse;
+use crate::store;
+
+// Notifications des comptes. Seuls le type et les paramètres sont enregistrés : sujet et corps sont
+// rendus à la lecture, dans la langue du lecteur (modèles `notification.<type>.subject` / `.body`).
+
+pub const PROOF_ANCHORED: &str = "proof_anchored";
+pub const PROOF_FAILED: &str = "proof_failed";
+// Enregistrées par `review_evaluation` (revue humaine d'un refus)
+pub const REVIEW_APPROVED: &str = "review_approved";
+pub const REVIEW_DISMISSED: &str = "review_dismissed";
+
+const LIST_LIMIT: i64 = 50;
+
+// Modèles (sujet, corps) d'un type de notification
+fn templates(kind: &str) -> (&'static str, &'static str) {
+    match kind {
+        PROOF_ANCHORED => ("notification.proof_anchored.subject", "notification.proof_anchored.body"),
+        PROOF_FAILED => ("notification.proof_failed.subject", "notification.proof_failed.body"),
+        REVIEW_APPROVED => ("notification.review_approved.subject", "notification.review_approved.body"),
+        REVIEW_DISMISSED => ("notification.review_dismissed.subject", "notification.review_dismissed.body"),
+        _ => ("notification.generic.subject", "notification.generic.body"),
+    }
+}
+
+/// Notifie le propriétaire d'un résumé ; `params` renseigne les `{nom}` des modèles.
+pub async fn notify_summary_owner(pool: &PgPool, summary_id: Uuid, kind: &str, params: Value) -> Result<(), sqlx::Error> {
+    let Some(user_id) = store::summary_owner(pool, summary_id).await? else {
+        return Ok(());
+    };
+    sqlx::query!(
+        "INSERT INTO notifications (id, user_id, kind, params) VALUES ($1, $2, $3, $4)",
+        Uuid::new_v4(),
+        user_id,
+        kind,
+        params
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Dernières notifications d'un compte, rendues dans `locale`.
+pub async fn list(pool: &PgPool, user_id: Uuid, locale: Locale) -> Result<Vec<NotificationResponse>, sqlx::Error> {
+    let rows = sqlx::query!(
+        "SELECT id, kind, params, created_at FROM notifications WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+        user_id,
+        LIST_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let values: Vec<(String, String)> = match &row.params {
+                Value::Object(params) => params
+                    .iter()
+                    .map(|(name, value)| match value {
+                        Value::String(text) => (name.clone(), text.clone()),
+                        other => (name.clone(), other.to_string()),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let args: Vec<(&str, &str)> = values.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+            let (subject, body) = templates(&row.kind);
+            NotificationResponse {
+                id: row.id,
+                subject: locale.format(subject, &args),
+                body: locale.format(body, &args),
+                kind: row.kind,
+                created_at: row.created_at.to_rfc3339(),
+            }
+        })
+        .collect())
+}
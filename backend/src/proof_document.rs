@@ -0,0 +1,224 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use crate::models::{Claim, Summary, SummaryDocument};
+
+// Document de preuve haché puis ancré sur Hedera.
+// Chaque schéma reste calculable pour que les anciennes preuves restent vérifiables.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashScheme {
+    /// Format historique (`format!` sans échappement) — conservé uniquement pour la vérification
+    Legacy,
+    /// JSON canonique RFC 8785 (JCS), revendication unique
+    JcsV1,
+    /// JSON canonique RFC 8785 (JCS), jeu de revendications numérotées avec leurs dépendances
+    JcsV2,
+    /// `jcs-v2` plus l'empreinte de la révision antérieure ancrée (`null` sans antécédent)
+    JcsV3,
+}
+
+pub const CURRENT_HASH_SCHEME: HashScheme = HashScheme::JcsV3;
+
+impl HashScheme {
+    pub const ALL: [HashScheme; 4] = [HashScheme::Legacy, HashScheme::JcsV1, HashScheme::JcsV2, HashScheme::JcsV3];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashScheme::Legacy => "legacy",
+            HashScheme::JcsV1 => "jcs-v1",
+            HashScheme::JcsV2 => "jcs-v2",
+            HashScheme::JcsV3 => "jcs-v3",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == value)
+    }
+}
+
+#[derive(Serialize)]
+struct ProofDocument<S: Serialize> {
+    scheme: &'static str,
+    summary: S,
+}
+
+// Document `jcs-v3` : la révision et le lien vers la preuve qui la précède
+#[derive(Serialize)]
+struct ChainedProofDocument<'a> {
+    scheme: &'static str,
+    summary: SummaryV2<'a>,
+    previous_hash: Option<&'a str>,
+}
+
+// Contenu haché par `jcs-v1`
+#[derive(Serialize)]
+struct SummaryV1<'a> {
+    title: &'a str,
+    problem: &'a str,
+    solution: &'a str,
+    claim: &'a str,
+    cpc_code: &'a str,
+}
+
+// Contenu haché par `jcs-v2` : `claim` est remplacé par le jeu complet
+#[derive(Serialize)]
+struct SummaryV2<'a> {
+    title: &'a str,
+    problem: &'a str,
+    solution: &'a str,
+    claims: &'a [Claim],
+    cpc_code: &'a str,
+}
+
+impl SummaryDocument {
+    /// `claims` est vide pour un résumé antérieur aux jeux de revendications.
+    pub fn from_summary(summary: &Summary, claims: Vec<Claim>) -> Self {
+        SummaryDocument {
+            title: summary.title.clone(),
+            problem: summary.problem.clone(),
+            solution: summary.solution.clone(),
+            claim: summary.claim.clone(),
+            cpc_code: summary.cpc_code.clone(),
+            claims,
+            previous_hash: None,
+        }
+    }
+
+    /// Jeu de revendications ; sans jeu, la revendication unique devient la revendication 1.
+    pub fn claim_set(&self) -> Cow<'_, [Claim]> {
+        if self.claims.is_empty() {
+            Cow::Owned(vec![Claim { number: 1, depends_on: None, text: self.claim.clone() }])
+        } else {
+            Cow::Borrowed(&self.claims)
+        }
+    }
+}
+
+/// Octets exacts qui sont hachés pour le schéma donné.
+pub fn document_bytes(scheme: HashScheme, document: &SummaryDocument) -> Vec<u8> {
+    match scheme {
+        HashScheme::Legacy => format!(
+            r#"{{"title":"{}","problem":"{}","solution":"{}","claim":"{}","cpc_code":"{}"}}"#,
+            document.title, document.problem, document.solution, document.claim, document.cpc_code
+        )
+        .into_bytes(),
+        // Un document ne contenant que des chaînes et des entiers est toujours sérialisable
+        HashScheme::JcsV1 => {
+            let summary = SummaryV1 {
+                title: &document.title,
+                problem: &document.problem,
+                solution: &document.solution,
+                claim: &document.claim,
+                cpc_code: &document.cpc_code,
+            };
+            let doc = ProofDocument { scheme: "brevetchain-proof/jcs-v1", summary };
+            serde_jcs::to_vec(&doc).expect("proof document serialization")
+        }
+        HashScheme::JcsV2 | HashScheme::JcsV3 => {
+            let claims = document.claim_set();
+            let summary = SummaryV2 {
+                title: &document.title,
+                problem: &document.problem,
+                solution: &document.solution,
+                claims: &claims,
+                cpc_code: &document.cpc_code,
+            };
+            if scheme == HashScheme::JcsV2 {
+                let doc = ProofDocument { scheme: "brevetchain-proof/jcs-v2", summary };
+                serde_jcs::to_vec(&doc).expect("proof document serialization")
+            } else {
+                let doc = ChainedProofDocument {
+                    scheme: "brevetchain-proof/jcs-v3",
+                    summary,
+                    previous_hash: document.previous_hash.as_deref(),
+                };
+                serde_jcs::to_vec(&doc).expect("proof document serialization")
+            }
+        }
+    }
+}
+
+/// Empreinte SHA-256 (hex minuscule) du document de preuve.
+pub fn hash_document(scheme: HashScheme, document: &SummaryDocument) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(document_bytes(scheme, document));
+    format!("{:x}", hasher.finalize())
+}
+
+/// Recalcule l'empreinte avec le schéma enregistré sur la preuve et la compare.
+/// Un schéma inconnu n'est jamais considéré comme vérifié.
+pub fn matches(scheme: &str, document: &SummaryDocument, expected_hash: &str) -> bool {
+    HashScheme::parse(scheme)
+        .map(|scheme| hash_document(scheme, document).eq_ignore_ascii_case(expected_hash))
+        .unwrap_or(false)
+}
+
+/// Empreintes du document sous chacun des schémas connus.
+pub fn candidate_hashes(document: &SummaryDocument) -> Vec<(HashScheme, String)> {
+    HashScheme::ALL
+        .into_iter()
+        .map(|scheme| (scheme, hash_document(scheme, document)))
+        .collect()
+}
+
+/// Une empreinte SHA-256 hexadécimale bien formée (64 caractères).
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn document(title: &str, problem: &str) -> SummaryDocument {
+        SummaryDocument {
+            title: title.to_string(),
+            problem: problem.to_string(),
+            solution: "Une solution".to_string(),
+            claim: "Revendication \"1\"\nsur deux lignes \\ fin".to_string(),
+            cpc_code: "H02J".to_string(),
+            claims: Vec::new(),
+            previous_hash: None,
+        }
+    }
+
+    #[test]
+    fn escapes_quotes_backslashes_and_newlines() {
+        let doc = document("Titre \"cité\" \\ chemin\nsuite", "Problème\t\u{1}");
+        for scheme in [HashScheme::JcsV1, HashScheme::JcsV2, HashScheme::JcsV3] {
+            let bytes = document_bytes(scheme, &doc);
+            let value: Value = serde_json::from_slice(&bytes).expect("JCS valide");
+            assert_eq!(value["summary"]["title"], doc.title.as_str());
+            assert_eq!(value["summary"]["problem"], doc.problem.as_str());
+            // Les octets hachés sont déjà sous forme canonique
+            assert_eq!(serde_jcs::to_vec(&value).unwrap(), bytes);
+        }
+        assert!(serde_json::from_slice::<Value>(&document_bytes(HashScheme::Legacy, &doc)).is_err());
+    }
+
+    #[test]
+    fn legacy_collisions_hash_differently() {
+        let first = document("x\",\"problem\":\"y", "z");
+        let second = document("x", "y\",\"problem\":\"z");
+        assert_eq!(document_bytes(HashScheme::Legacy, &first), document_bytes(HashScheme::Legacy, &second));
+        assert_eq!(hash_document(HashScheme::Legacy, &first), hash_document(HashScheme::Legacy, &second));
+        for scheme in [HashScheme::JcsV1, HashScheme::JcsV2, HashScheme::JcsV3] {
+            assert_ne!(hash_document(scheme, &first), hash_document(scheme, &second));
+        }
+    }
+
+    #[test]
+    fn legacy_hash_still_verifies() {
+        let doc = SummaryDocument { claim: "Une revendication".to_string(), ..document("Titre", "Problème") };
+        let legacy = r#"{"title":"Titre","problem":"Problème","solution":"Une solution","claim":"Une revendication","cpc_code":"H02J"}"#;
+        let expected = format!("{:x}", Sha256::digest(legacy.as_bytes()));
+
+        assert!(matches("legacy", &doc, &expected));
+        assert!(matches("legacy", &doc, &expected.to_ascii_uppercase()));
+        assert!(!matches("jcs-v3", &doc, &expected));
+        assert!(!matches("inconnu", &doc, &expected));
+        assert!(candidate_hashes(&doc).contains(&(HashScheme::Legacy, expected)));
+    }
+}
@@ -0,0 +1,490 @@
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use crate::anchoring::{AnchorReceipt, Anchoring, MerkleInclusion};
+use crate::encryption::Vault;
+use crate::inventor_signature::InventorSignature;
+use crate::ledger;
+use crate::models::{Proof, ProofJob};
+use crate::notifications;
+use crate::store;
+
+// Worker de la boîte d'envoi `proof_jobs` : les requêtes HTTP ne font qu'écrire une tâche,
+// l'ancrage sur le registre et l'insertion de la preuve se font ici, avec reprise après redémarrage.
+
+// Bail d'une tâche réclamée, supérieur à la fenêtre de validité d'une transaction Hedera (120 s).
+// Une tâche dont le bail expire (processus arrêté) est reprise au passage suivant.
+const LEASE_SECS: i64 = 180;
+// Une tentative bloquée (réseau injoignable) est abandonnée avant l'expiration de son bail
+const JOB_TIMEOUT: Duration = Duration::from_secs(150);
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+// Une tentative échoue sur le registre, la base ou le déchiffrement du résumé : l'erreur est seulement journalisée
+type JobResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Clone)]
+struct WorkerConfig {
+    poll_interval: Duration,
+    batch_size: i64,
+    max_attempts: i32,
+    backoff_base_secs: i64,
+}
+
+impl WorkerConfig {
+    // PROOF_WORKER_POLL_MS (1000), PROOF_WORKER_BATCH (20), PROOF_JOB_MAX_ATTEMPTS (8), PROOF_JOB_BACKOFF_SECS (5)
+    fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        WorkerConfig {
+            poll_interval: Duration::from_millis(var("PROOF_WORKER_POLL_MS", 1000u64).max(100)),
+            batch_size: var("PROOF_WORKER_BATCH", 20i64).max(1),
+            max_attempts: var("PROOF_JOB_MAX_ATTEMPTS", 8i32).max(1),
+            backoff_base_secs: var("PROOF_JOB_BACKOFF_SECS", 5i64).max(1),
+        }
+    }
+
+    // 5 s, 10 s, 20 s, ... plafonné à une heure
+    fn backoff(&self, attempts: i32) -> chrono::Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+        let secs = self.backoff_base_secs.saturating_mul(1i64 << exponent).min(MAX_BACKOFF_SECS);
+        chrono::Duration::seconds(secs)
+    }
+}
+
+/// Démarre le worker d'ancrage en tâche de fond.
+pub fn spawn_from_env(pool: PgPool, anchoring: Arc<Anchoring>, vault: Arc<Vault>) {
+    let config = WorkerConfig::from_env();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.poll_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = drain_due_jobs(&pool, &anchoring, &vault, &config).await {
+                eprintln!("Erreur worker d'ancrage: {}", e);
+            }
+        }
+    });
+}
+
+async fn drain_due_jobs(
+    pool: &PgPool,
+    anchoring: &Arc<Anchoring>,
+    vault: &Arc<Vault>,
+    config: &WorkerConfig,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    // Réclame les tâches échues ; SKIP LOCKED permet plusieurs instances du serveur
+    let jobs = sqlx::query_as!(
+        ProofJob,
+        "UPDATE proof_jobs
+         SET locked_until = $1, attempts = attempts + 1, updated_at = $2
+         WHERE summary_id IN (
+             SELECT summary_id FROM proof_jobs
+             WHERE status = 'pending' AND next_attempt_at <= $2 AND locked_until <= $2
+             ORDER BY next_attempt_at
+             LIMIT $3
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING *",
+        now + chrono::Duration::seconds(LEASE_SECS),
+        now,
+        config.batch_size
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Traitées en parallèle : en mode batch, elles rejoignent le même lot Merkle
+    let mut running = JoinSet::new();
+    for job in jobs {
+        let pool = pool.clone();
+        let anchoring = anchoring.clone();
+        let vault = vault.clone();
+        let config = config.clone();
+        running.spawn(async move {
+            let outcome = match tokio::time::timeout(JOB_TIMEOUT, run_job(&pool, &anchoring, &vault, &job)).await {
+                Ok(outcome) => outcome,
+                Err(_) => Err("Délai d'ancrage dépassé".into()),
+            };
+            match record_outcome(&pool, &config, &job, outcome).await {
+                Ok(true) => {}
+                Ok(false) => eprintln!("Tâche d'ancrage {} reprise par un autre worker, issue ignorée", job.summary_id),
+                Err(e) => eprintln!("Erreur mise à jour tâche d'ancrage {}: {}", job.summary_id, e),
+            }
+        });
+    }
+    while running.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+// Preuve ancrée, ou rien si la tentative a perdu son bail (tâche reprise par un autre worker après expiration)
+async fn run_job(pool: &PgPool, anchoring: &Anchoring, vault: &Vault, job: &ProofJob) -> JobResult<Option<Proof>> {
+    let summary = store::fetch_summary(pool, vault, job.summary_id)
+        .await?
+        .ok_or("Résumé introuvable")?;
+
+    let inventor = match (&job.inventor_account, &job.inventor_public_key, &job.inventor_signature) {
+        (Some(account), Some(public_key), Some(signature)) => Some(InventorSignature {
+            account: account.clone(),
+            public_key: public_key.clone(),
+            signature: signature.clone(),
+        }),
+        _ => None,
+    };
+
+    // Reprise : si la transaction d'une tentative précédente a atteint le consensus, on ne resoumet pas
+    let mut transaction_id = None;
+    if let Some(previous) = &job.transaction_id {
+        if let Ok(Some(receipt)) = anchoring.recover(previous, MerkleInclusion::of_job(job)).await {
+            return store_proof(pool, anchoring, job, receipt).await;
+        }
+        // Encore dans sa fenêtre de validité, la même transaction sera dédoublonnée par le réseau
+        if !ledger::transaction_expired(previous, Utc::now()) {
+            // Un lot enregistré est resoumis tel quel plutôt que d'ancrer l'empreinte dans un nouveau lot
+            if let (Some(message), Some(merkle)) = (&job.batch_message, MerkleInclusion::of_job(job)) {
+                let receipt = anchoring.resubmit_batch(previous, message, merkle).await?;
+                return store_proof(pool, anchoring, job, receipt).await;
+            }
+            transaction_id = Some(previous.clone());
+        }
+    }
+    if transaction_id.is_none() {
+        transaction_id = anchoring.prepare_transaction_id(inventor.is_some())?;
+        // L'identifiant est enregistré avant la soumission pour pouvoir retrouver la transaction
+        let updated = sqlx::query!(
+            "UPDATE proof_jobs
+             SET transaction_id = $2, batch_message = NULL, merkle_root = NULL, merkle_leaf_index = NULL,
+                 merkle_path = NULL, updated_at = $3
+             WHERE summary_id = $1 AND status = 'pending' AND attempts = $4",
+            job.summary_id,
+            transaction_id,
+            Utc::now(),
+            job.attempts
+        )
+        .execute(pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+    }
+
+    let receipt = anchoring
+        .anchor(
+            job,
+            summary.cpc_code.clone(),
+            summary.created_at,
+            inventor.as_ref(),
+            transaction_id,
+        )
+        .await?;
+    store_proof(pool, anchoring, job, receipt).await
+}
+
+// Preuve et état de la tâche sont écrits dans la même transaction, annulée si la tentative a perdu son bail
+async fn store_proof(
+    pool: &PgPool,
+    anchoring: &Anchoring,
+    job: &ProofJob,
+    receipt: AnchorReceipt,
+) -> JobResult<Option<Proof>> {
+    let merkle_root = receipt.merkle.as_ref().map(|m| m.root.clone());
+    let merkle_leaf_index = receipt.merkle.as_ref().map(|m| m.leaf_index as i32);
+    let merkle_path = receipt.merkle.as_ref().map(|m| json!(m.path));
+
+    // `timestamp` est la date d'ancrage ; l'horodatage de consensus est renseigné par la réconciliation
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+    let proof = sqlx::query_as!(
+        Proof,
+        "INSERT INTO proofs (id, summary_id, hash, hash_scheme, hedera_tx_id, topic_sequence_number,
+                             merkle_root, merkle_leaf_index, merkle_path, network, timestamp, created_at,
+                             inventor_account, inventor_public_key, inventor_signature, previous_hash)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+         ON CONFLICT (summary_id) DO UPDATE SET summary_id = EXCLUDED.summary_id
+         RETURNING *",
+        uuid::Uuid::new_v4(),
+        job.summary_id,
+        job.hash,
+        job.hash_scheme,
+        receipt.transaction_id,
+        receipt.topic_sequence_number as i64,
+        merkle_root,
+        merkle_leaf_index,
+        merkle_path,
+        anchoring.ledger().network(),
+        now,
+        now,
+        job.inventor_account,
+        job.inventor_public_key,
+        job.inventor_signature,
+        job.previous_hash
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let updated = sqlx::query!(
+        "UPDATE proof_jobs
+         SET status = 'anchored', transaction_id = $2, last_error = NULL, locked_until = $3, updated_at = $3
+         WHERE summary_id = $1 AND status = 'pending' AND attempts = $4",
+        job.summary_id,
+        proof.hedera_tx_id,
+        Utc::now(),
+        job.attempts
+    )
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+    tx.commit().await?;
+
+    Ok(Some(proof))
+}
+
+// Enregistre l'issue de la tâche et notifie l'inventeur ; faux si la tentative a perdu son bail
+async fn record_outcome(
+    pool: &PgPool,
+    config: &WorkerConfig,
+    job: &ProofJob,
+    outcome: JobResult<Option<Proof>>,
+) -> Result<bool, sqlx::Error> {
+    let error = match outcome {
+        Ok(Some(proof)) => {
+            println!("⛓️  Preuve ancrée pour le résumé {} ({})", job.summary_id, proof.hedera_tx_id);
+            let params = json!({"summary_id": job.summary_id, "transaction_id": proof.hedera_tx_id});
+            notifications::notify_summary_owner(pool, job.summary_id, notifications::PROOF_ANCHORED, params).await?;
+            return Ok(true);
+        }
+        Ok(None) => return Ok(false),
+        Err(e) => e.to_string(),
+    };
+
+    let now = Utc::now();
+    if job.attempts >= config.max_attempts {
+        // Lettre morte : la tâche n'est plus retentée jusqu'à une nouvelle demande d'enregistrement
+        eprintln!("❌ Ancrage abandonné pour le résumé {} après {} tentatives: {}", job.summary_id, job.attempts, error);
+        let updated = sqlx::query!(
+            "UPDATE proof_jobs SET status = 'failed', last_error = $2, locked_until = $3, updated_at = $3
+             WHERE summary_id = $1 AND status = 'pending' AND attempts = $4",
+            job.summary_id,
+            error,
+            now,
+            job.attempts
+        )
+        .execute(pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+        let params = json!({"summary_id": job.summary_id, "attempts": job.attempts});
+        notifications::notify_summary_owner(pool, job.summary_id, notifications::PROOF_FAILED, params).await?;
+    } else {
+        eprintln!("Échec ancrage du résumé {} (tentative {}): {}", job.summary_id, job.attempts, error);
+        let updated = sqlx::query!(
+            "UPDATE proof_jobs SET last_error = $2, next_attempt_at = $3, locked_until = $4, updated_at = $4
+             WHERE summary_id = $1 AND status = 'pending' AND attempts = $5",
+            job.summary_id,
+            error,
+            now + config.backoff(job.attempts),
+            now,
+            job.attempts
+        )
+        .execute(pool)
+        .await?;
+        return Ok(updated.rows_affected() > 0);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchoring::merkle_proof;
+    use crate::ledger::LedgerAnchor;
+    use crate::merkle::MerkleTree;
+    use crate::models::Summary;
+    use crate::simulated_ledger::SimulatedLedger;
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    async fn setup() -> (PgPool, Vault, Arc<dyn LedgerAnchor>) {
+        dotenvy::dotenv().ok();
+        let pool = PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL")).await.unwrap();
+        let vault = Vault::from_env(&pool).await.unwrap();
+        let ledger: Arc<dyn LedgerAnchor> = Arc::new(SimulatedLedger::open(Utc::now(), None).unwrap());
+        (pool, vault, ledger)
+    }
+
+    // Résumé d'un nouvel utilisateur et sa tâche d'ancrage, déjà réclamée : un serveur lancé sur la même base ne la prend pas
+    async fn claimed_job(pool: &PgPool, vault: &Vault) -> ProofJob {
+        let (user_id, idea_id, summary_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query!(
+            "INSERT INTO users (id, full_name, email, wallet_address) VALUES ($1, 'Test ancrage', $2, $3)",
+            user_id,
+            format!("anchoring-{}@test.invalid", user_id),
+            format!("0.0.{}", user_id.as_u128() % 1_000_000_000_000)
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        store::insert_idea(pool, vault, idea_id, user_id, "Idée à ancrer", Utc::now()).await.unwrap();
+        let summary = Summary {
+            id: summary_id,
+            idea_id,
+            title: "Titre".to_string(),
+            problem: "Problème".to_string(),
+            solution: "Solution".to_string(),
+            claim: "Revendication".to_string(),
+            cpc_code: "G06F".to_string(),
+            created_at: Utc::now(),
+            original_id: summary_id,
+            revision: 1,
+            author_id: user_id,
+        };
+        store::insert_summary(pool, vault, user_id, &summary, &[]).await.unwrap();
+        sqlx::query_as!(
+            ProofJob,
+            "INSERT INTO proof_jobs (summary_id, hash, hash_scheme, attempts, locked_until, created_at, updated_at)
+             VALUES ($1, $2, 'jcs-sha256-v2', 1, $3, $4, $4)
+             RETURNING *",
+            summary_id,
+            hex::encode(Sha256::digest(summary_id.as_bytes())),
+            Utc::now() + chrono::Duration::seconds(LEASE_SECS),
+            Utc::now()
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn reload(pool: &PgPool, job: &ProofJob) -> ProofJob {
+        sqlx::query_as!(ProofJob, "SELECT * FROM proof_jobs WHERE summary_id = $1", job.summary_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_batch_is_recorded_on_its_jobs_before_submission() {
+        let (pool, vault, ledger) = setup().await;
+        let anchoring = Anchoring::batched(ledger, pool.clone(), Duration::from_millis(200), 2);
+        let (first, second) = (claimed_job(&pool, &vault).await, claimed_job(&pool, &vault).await);
+
+        let (a, b) = tokio::join!(
+            run_job(&pool, &anchoring, &vault, &first),
+            run_job(&pool, &anchoring, &vault, &second)
+        );
+        let (a, b) = (a.unwrap().unwrap(), b.unwrap().unwrap());
+        assert_eq!(a.hedera_tx_id, b.hedera_tx_id);
+
+        for (job, proof) in [(&first, &a), (&second, &b)] {
+            let job = reload(&pool, job).await;
+            assert_eq!(job.transaction_id.as_deref(), Some(proof.hedera_tx_id.as_str()));
+            assert_eq!(job.merkle_root, proof.merkle_root);
+            assert_eq!(job.merkle_leaf_index, proof.merkle_leaf_index);
+            assert!(job.batch_message.unwrap().contains(proof.merkle_root.as_deref().unwrap()));
+            assert!(merkle_proof(proof).unwrap().verified);
+        }
+    }
+
+    #[tokio::test]
+    async fn an_interrupted_batch_is_resubmitted_under_its_recorded_transaction() {
+        let (pool, vault, ledger) = setup().await;
+        let anchoring = Anchoring::batched(ledger.clone(), pool.clone(), Duration::from_millis(200), 2);
+        let (first, second) = (claimed_job(&pool, &vault).await, claimed_job(&pool, &vault).await);
+
+        // Lot enregistré sur ses tâches, puis processus arrêté avant la soumission
+        let tree = MerkleTree::from_hex_leaves(&[first.hash.clone(), second.hash.clone()]).unwrap();
+        let transaction_id = ledger.new_transaction_id().unwrap();
+        let message = json!({"type": "merkle_batch", "merkle_root": tree.root_hex(), "leaf_count": 2}).to_string();
+        for (index, job) in [&first, &second].into_iter().enumerate() {
+            sqlx::query!(
+                "UPDATE proof_jobs
+                 SET transaction_id = $2, batch_message = $3, merkle_root = $4, merkle_leaf_index = $5, merkle_path = $6
+                 WHERE summary_id = $1",
+                job.summary_id,
+                transaction_id,
+                message,
+                tree.root_hex(),
+                index as i32,
+                json!(tree.inclusion_path(index))
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // La première reprise soumet le lot enregistré, la seconde le retrouve sur le registre
+        let a = run_job(&pool, &anchoring, &vault, &reload(&pool, &first).await).await.unwrap().unwrap();
+        let b = run_job(&pool, &anchoring, &vault, &reload(&pool, &second).await).await.unwrap().unwrap();
+        for proof in [&a, &b] {
+            assert_eq!(proof.hedera_tx_id, transaction_id);
+            assert_eq!(proof.topic_sequence_number, Some(1));
+            assert!(merkle_proof(proof).unwrap().verified);
+        }
+        let anchored = ledger.fetch_message(&transaction_id).await.unwrap().unwrap();
+        assert_eq!(anchored.contents, message.into_bytes());
+
+        // Aucun autre lot n'a été soumis
+        let next = ledger.new_transaction_id().unwrap();
+        assert_eq!(ledger.submit_message(&next, b"suivant".to_vec()).await.unwrap().topic_sequence_number, 2);
+    }
+
+    fn config() -> WorkerConfig {
+        WorkerConfig { poll_interval: Duration::from_secs(1), batch_size: 1, max_attempts: 1, backoff_base_secs: 5 }
+    }
+
+    async fn notification_kinds(pool: &PgPool, job: &ProofJob) -> Vec<String> {
+        sqlx::query_scalar!(
+            "SELECT n.kind FROM notifications n JOIN summaries s ON s.author_id = n.user_id WHERE s.id = $1",
+            job.summary_id
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_stale_attempt_records_nothing_after_its_lease_is_taken_over() {
+        let (pool, vault, ledger) = setup().await;
+        let anchoring = Anchoring::direct(ledger.clone());
+        let stale = claimed_job(&pool, &vault).await;
+
+        // Bail expiré : un autre worker a réclamé la tâche (tentative suivante)
+        sqlx::query!("UPDATE proof_jobs SET attempts = attempts + 1 WHERE summary_id = $1", stale.summary_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // L'ancienne tentative ne soumet rien, ne stocke aucune preuve et n'écrit aucune issue
+        assert!(run_job(&pool, &anchoring, &vault, &stale).await.unwrap().is_none());
+        let transaction_id = ledger.new_transaction_id().unwrap();
+        let submission = ledger.submit_message(&transaction_id, b"ancrage".to_vec()).await.unwrap();
+        let receipt = AnchorReceipt {
+            transaction_id: submission.transaction_id,
+            topic_sequence_number: submission.topic_sequence_number,
+            merkle: None,
+        };
+        assert!(store_proof(&pool, &anchoring, &stale, receipt).await.unwrap().is_none());
+        assert!(!record_outcome(&pool, &config(), &stale, Err("délai dépassé".into())).await.unwrap());
+
+        let job = reload(&pool, &stale).await;
+        assert_eq!((job.status.as_str(), job.transaction_id.as_deref(), job.last_error.as_deref()), ("pending", None, None));
+        let proofs = sqlx::query_scalar!("SELECT COUNT(*) FROM proofs WHERE summary_id = $1", stale.summary_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(proofs, Some(0));
+        assert!(notification_kinds(&pool, &stale).await.is_empty());
+
+        // La tentative en cours ancre et notifie une seule fois
+        let outcome = run_job(&pool, &anchoring, &vault, &job).await;
+        assert!(record_outcome(&pool, &config(), &job, outcome).await.unwrap());
+        assert_eq!(reload(&pool, &job).await.status, "anchored");
+        assert_eq!(notification_kinds(&pool, &job).await, vec![notifications::PROOF_ANCHORED.to_string()]);
+    }
+}
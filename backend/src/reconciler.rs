@@ -0,0 +1,303 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::ledger::{LedgerAnchor, LedgerMessage, LedgerResult};
+use crate::mirror_node::MirrorNodeClient;
+use crate::models::Proof;
+
+// Tâche de fond : relit chaque transaction ancrée sur le mirror node, enregistre
+// l'horodatage de consensus, le numéro de séquence et le running hash, et signale les écarts.
+// Un réseau sans mirror node (registre simulé) est relu sur le registre d'ancrage lui-même.
+
+const BATCH_SIZE: i64 = 50;
+const MAX_ATTEMPTS: i32 = 20;
+
+// Registre relu pour confirmer les preuves d'un réseau
+enum Source {
+    Mirror(MirrorNodeClient),
+    Ledger(Arc<dyn LedgerAnchor>),
+}
+
+impl Source {
+    fn for_network(network: &str, ledger: &Arc<dyn LedgerAnchor>) -> Option<Self> {
+        match MirrorNodeClient::for_network(network) {
+            Some(mirror) => Some(Source::Mirror(mirror)),
+            None => (ledger.network() == network).then(|| Source::Ledger(ledger.clone())),
+        }
+    }
+
+    async fn message(&self, transaction_id: &str) -> LedgerResult<Option<LedgerMessage>> {
+        match self {
+            Source::Mirror(mirror) => mirror.topic_message_by_transaction(transaction_id).await,
+            Source::Ledger(ledger) => ledger.fetch_message(transaction_id).await,
+        }
+    }
+}
+
+/// Démarre la réconciliation toutes les `RECONCILE_INTERVAL_SECS` secondes (60 par défaut, 0 = désactivée).
+pub fn spawn_from_env(pool: PgPool, ledger: Arc<dyn LedgerAnchor>) {
+    let interval = env::var("RECONCILE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    if interval == 0 {
+        println!("🔎 Réconciliation mirror node désactivée");
+        return;
+    }
+    tokio::spawn(async move {
+        let mut sources = HashMap::new();
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = reconcile_pending(&pool, &ledger, &mut sources).await {
+                eprintln!("Erreur réconciliation mirror node: {}", e);
+            }
+        }
+    });
+}
+
+async fn reconcile_pending(
+    pool: &PgPool,
+    ledger: &Arc<dyn LedgerAnchor>,
+    sources: &mut HashMap<String, Option<Source>>,
+) -> Result<(), sqlx::Error> {
+    // Le mirror node publie les transactions avec quelques secondes de retard
+    let proofs = sqlx::query_as!(
+        Proof,
+        "SELECT * FROM proofs
+         WHERE reconciliation_status IN ('pending', 'not_found')
+           AND reconciliation_attempts < $1
+           AND created_at < NOW() - INTERVAL '5 seconds'
+         ORDER BY created_at
+         LIMIT $2",
+        MAX_ATTEMPTS,
+        BATCH_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for proof in proofs {
+        // Chaque preuve est relue sur le mirror node du réseau où elle a été ancrée
+        let source = sources
+            .entry(proof.network.clone())
+            .or_insert_with(|| Source::for_network(&proof.network, ledger));
+        let Some(source) = source else { continue };
+
+        match source.message(&proof.hedera_tx_id).await {
+            Ok(Some(message)) => {
+                let discrepancies = compare(&proof, &message);
+                let (status, detail) = if discrepancies.is_empty() {
+                    ("confirmed", None)
+                } else {
+                    eprintln!("⚠️  Preuve {} en désaccord avec le mirror node: {}", proof.id, discrepancies.join("; "));
+                    ("mismatch", Some(discrepancies.join("; ")))
+                };
+                sqlx::query!(
+                    "UPDATE proofs
+                     SET consensus_timestamp = $2,
+                         topic_sequence_number = COALESCE(topic_sequence_number, $3),
+                         running_hash = $4,
+                         reconciliation_status = $5,
+                         reconciliation_detail = $6,
+                         reconciliation_attempts = reconciliation_attempts + 1,
+                         reconciled_at = $7
+                     WHERE id = $1",
+                    proof.id,
+                    message.consensus_timestamp,
+                    message.sequence_number as i64,
+                    message.running_hash,
+                    status,
+                    detail,
+                    Utc::now()
+                )
+                .execute(pool)
+                .await?;
+            }
+            Ok(None) => {
+                sqlx::query!(
+                    "UPDATE proofs
+                     SET reconciliation_status = 'not_found',
+                         reconciliation_detail = 'Transaction absente du mirror node',
+                         reconciliation_attempts = reconciliation_attempts + 1,
+                         reconciled_at = $2
+                     WHERE id = $1",
+                    proof.id,
+                    Utc::now()
+                )
+                .execute(pool)
+                .await?;
+            }
+            // Mirror node injoignable : on réessaiera au prochain passage sans consommer de tentative
+            Err(e) => {
+                eprintln!("Mirror node indisponible pour {}: {}", proof.hedera_tx_id, e);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Écarts entre ce que la base affirme et ce que le mirror node a enregistré
+fn compare(proof: &Proof, message: &LedgerMessage) -> Vec<String> {
+    let mut discrepancies = Vec::new();
+
+    if let Some(sequence) = proof.topic_sequence_number
+        && sequence != message.sequence_number as i64
+    {
+        discrepancies.push(format!(
+            "numéro de séquence {} en base, {} sur le mirror node",
+            sequence, message.sequence_number
+        ));
+    }
+
+    let payload: Option<serde_json::Value> = serde_json::from_slice(&message.contents).ok();
+    let (field, expected) = match &proof.merkle_root {
+        Some(root) => ("merkle_root", root.as_str()),
+        None => ("hash", proof.hash.as_str()),
+    };
+    let anchored = payload.as_ref().and_then(|p| p.get(field)).and_then(|v| v.as_str());
+    if anchored.map(|v| !v.eq_ignore_ascii_case(expected)).unwrap_or(true) {
+        discrepancies.push(format!(
+            "{} ancré {} au lieu de {}",
+            field,
+            anchored.unwrap_or("absent"),
+            expected
+        ));
+    }
+
+    if let Some(signature) = &proof.inventor_signature {
+        let anchored = payload
+            .as_ref()
+            .and_then(|p| p.pointer("/inventor/signature"))
+            .and_then(|v| v.as_str());
+        if anchored != Some(signature.as_str()) {
+            discrepancies.push(format!("co-signature de l'inventeur {} sur le topic", anchored.map_or("absente", |_| "différente")));
+        }
+    }
+
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated_ledger::SimulatedLedger;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn proof(sequence: i64) -> Proof {
+        let now = Utc::now();
+        Proof {
+            id: Uuid::new_v4(),
+            summary_id: Uuid::new_v4(),
+            hash: "ab".repeat(32),
+            hedera_tx_id: "0.0.1001@1700000000.000000001".to_string(),
+            timestamp: now,
+            created_at: now,
+            hash_scheme: "jcs-sha256-v2".to_string(),
+            topic_sequence_number: Some(sequence),
+            consensus_timestamp: None,
+            merkle_root: None,
+            merkle_leaf_index: None,
+            merkle_path: None,
+            running_hash: None,
+            reconciliation_status: "pending".to_string(),
+            reconciliation_detail: None,
+            reconciliation_attempts: 0,
+            reconciled_at: None,
+            network: "simulated".to_string(),
+            inventor_account: None,
+            inventor_public_key: None,
+            inventor_signature: None,
+            title_disclosed: false,
+            previous_hash: None,
+        }
+    }
+
+    fn message(sequence: u64, contents: serde_json::Value) -> LedgerMessage {
+        LedgerMessage {
+            transaction_id: "0.0.1001@1700000000.000000001".to_string(),
+            topic_id: "0.0.2002".to_string(),
+            consensus_timestamp: Utc::now(),
+            sequence_number: sequence,
+            running_hash: "00".repeat(48),
+            contents: contents.to_string().into_bytes(),
+        }
+    }
+
+    #[test]
+    fn a_matching_message_has_no_discrepancy() {
+        let proof = proof(7);
+        assert!(compare(&proof, &message(7, json!({"hash": proof.hash.to_uppercase()}))).is_empty());
+    }
+
+    #[test]
+    fn reports_a_sequence_mismatch() {
+        let proof = proof(7);
+        let discrepancies = compare(&proof, &message(8, json!({"hash": proof.hash})));
+        assert_eq!(discrepancies.len(), 1);
+        assert!(discrepancies[0].contains("7") && discrepancies[0].contains("8"));
+    }
+
+    #[test]
+    fn reports_a_different_or_missing_hash() {
+        let proof = proof(7);
+        let discrepancies = compare(&proof, &message(7, json!({"hash": "cd".repeat(32)})));
+        assert_eq!(discrepancies.len(), 1);
+        assert!(discrepancies[0].starts_with("hash"));
+
+        let discrepancies = compare(&proof, &message(7, json!({"cpc_code": "G06F"})));
+        assert!(discrepancies[0].contains("absent"));
+    }
+
+    #[test]
+    fn batched_proofs_are_checked_against_the_merkle_root() {
+        let mut proof = proof(7);
+        proof.merkle_root = Some("ef".repeat(32));
+        let batch = json!({"type": "merkle_batch", "merkle_root": "ef".repeat(32), "leaf_count": 2});
+        assert!(compare(&proof, &message(7, batch)).is_empty());
+
+        // L'empreinte de la feuille n'est pas celle ancrée par le lot
+        let discrepancies = compare(&proof, &message(7, json!({"hash": proof.hash})));
+        assert_eq!(discrepancies.len(), 1);
+        assert!(discrepancies[0].starts_with("merkle_root"));
+    }
+
+    #[test]
+    fn reports_a_missing_or_different_inventor_signature() {
+        let mut proof = proof(7);
+        proof.inventor_signature = Some("signature".to_string());
+        let signed = json!({"hash": proof.hash, "inventor": {"signature": "signature"}});
+        assert!(compare(&proof, &message(7, signed)).is_empty());
+
+        let discrepancies = compare(&proof, &message(7, json!({"hash": proof.hash, "inventor": {"signature": "autre"}})));
+        assert_eq!(discrepancies.len(), 1);
+        assert!(discrepancies[0].contains("différente"));
+
+        let discrepancies = compare(&proof, &message(7, json!({"hash": proof.hash})));
+        assert!(discrepancies[0].contains("absente"));
+    }
+
+    #[tokio::test]
+    async fn simulated_proofs_are_read_back_from_the_ledger() {
+        let ledger: Arc<dyn LedgerAnchor> = Arc::new(SimulatedLedger::open(Utc::now(), None).unwrap());
+        let transaction_id = ledger.new_transaction_id().unwrap();
+        let mut proof = proof(1);
+        proof.hedera_tx_id = transaction_id.clone();
+        let contents = json!({"hash": proof.hash}).to_string().into_bytes();
+        ledger.submit_message(&transaction_id, contents).await.unwrap();
+
+        let source = Source::for_network("simulated", &ledger).unwrap();
+        assert!(matches!(source, Source::Ledger(_)));
+        let message = source.message(&transaction_id).await.unwrap().unwrap();
+        assert!(compare(&proof, &message).is_empty());
+        assert!(source.message("0.0.1001@1.000000001").await.unwrap().is_none());
+
+        // Un réseau ni Hedera ni celui du registre configuré n'est pas réconcilié
+        assert!(Source::for_network("autre", &ledger).is_none());
+    }
+}
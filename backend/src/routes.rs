@@ -1,297 +1,1572 @@
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::web::Bytes;
 use serde_json::json;
 use crate::models::*;
 use sqlx::PgPool;
 use uuid::Uuid;
 use chrono::Utc;
-use crate::ai_client;
-use crate::hedera_client;
-use sha2::{Sha256, Digest};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use crate::anchoring::{self, Anchoring};
+use crate::auth::{self, AuthenticatedUser, SessionKeys};
+use crate::certificate_pdf::{self, CertificateData};
+use crate::certificate_signing::{self, CertificateSigner};
+use crate::claims;
+use crate::diff;
+use crate::encryption::{CryptoResult, Vault};
+use crate::evaluations;
+use crate::error::{AppError, AppResult};
+use crate::i18n::{self, Lang, Locale, Message};
+use crate::inventor_signature::{self, InventorSignature};
+use crate::network;
+use crate::notifications;
+use crate::proof_document::{self, CURRENT_HASH_SCHEME};
+use crate::search::{self, SearchIndex};
+use crate::store;
+use crate::summary_worker::{self, Progress, SummaryQueue};
+use crate::verification_page;
+use crate::wallet;
+use crate::wallet_login;
 
 // ✅ Fonction 6 (Partielle) — Placeholder pour agents/offices
-pub async fn agent_register_placeholder(_data: web::Json<serde_json::Value>) -> ActixResult<HttpResponse> {
+pub async fn agent_register_placeholder(_data: web::Json<serde_json::Value>, lang: Lang) -> AppResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(json!({
         "status": "coming_soon",
-        "message": "L'enregistrement des agents sera disponible dans la prochaine version."
+        "message": lang.t("message.agent_coming_soon")
     })))
 }
 
-pub async fn office_register_placeholder(_data: web::Json<serde_json::Value>) -> ActixResult<HttpResponse> {
+pub async fn office_register_placeholder(_data: web::Json<serde_json::Value>, lang: Lang) -> AppResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(json!({
         "status": "coming_soon",
-        "message": "L'intégration avec les offices sera disponible dans la prochaine version."
+        "message": lang.t("message.office_coming_soon")
     })))
 }
 
-// ✅ Création de compte OBLIGATOIRE — Première étape
+// ✅ Création de compte OBLIGATOIRE — Première étape (ouvre aussi une session)
 pub async fn register_user(
+    req: HttpRequest,
     data: web::Json<RegisterUserRequest>,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
+    anchoring: web::Data<Anchoring>,
+    sessions: web::Data<SessionKeys>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
     let user_id = Uuid::new_v4();
 
-    if let Err(e) = sqlx::query!(
-        "INSERT INTO users (id, full_name, email, phone, country, wallet_address, created_at) 
-         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    // Langue choisie, sinon celle du navigateur : elle sert aussi aux notifications, envoyées hors requête
+    let locale = match &data.preferred_language {
+        Some(tag) => Some(supported_locale(tag)?),
+        None => req.headers().contains_key("Accept-Language").then_some(lang.0),
+    };
+    let lang = locale.map_or(lang, Lang);
+    let preferred_language = locale.map(|locale| locale.code().to_string());
+
+    // Adresse enregistrée sous sa forme canonique, somme de contrôle vérifiée pour le réseau d'ancrage
+    let wallet = wallet::parse(&data.wallet_address, ledger_network(&anchoring)).map_err(AppError::InvalidRequest)?;
+
+    let password_hash = match &data.password {
+        Some(password) if password.chars().count() < 8 => {
+            return Err(AppError::InvalidRequest("error.password_too_short".into()));
+        }
+        Some(password) => {
+            let password = password.clone();
+            let hash = web::block(move || auth::hash_password(&password).map_err(|e| e.to_string()))
+                .await
+                .map_err(AppError::internal("error.server"))?
+                .map_err(AppError::internal("error.server"))?;
+            Some(hash)
+        }
+        // Sans mot de passe, la connexion se fait par signature du wallet
+        None => None,
+    };
+
+    sqlx::query!(
+        "INSERT INTO users (id, full_name, email, phone, country, wallet_address, wallet_type, password_hash, preferred_language, created_at) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
         user_id,
         data.full_name,
         data.email,
         data.phone,
         data.country,
-        data.wallet_address,
+        wallet.canonical,
+        wallet.kind.as_str(),
+        password_hash,
+        preferred_language,
         Utc::now()
     )
     .execute(pool.as_ref())
     .await
-    {
-        eprintln!("Erreur création utilisateur: {}", e);
-        return Ok(HttpResponse::InternalServerError().json(json!({"message": "Échec de création de l'utilisateur"})));
-    }
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.constraint() == Some("idx_users_wallet_address") => {
+            AppError::Conflict("error.duplicate_wallet".into())
+        }
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::DuplicateEmail,
+        _ => AppError::Database(e),
+    })?;
 
+    let (token, expires_at) = sessions.issue(user_id);
     Ok(HttpResponse::Ok().json(RegisterUserResponse {
         user_id,
-        message: "Utilisateur enregistré avec succès".to_string(),
+        wallet_address: wallet.canonical,
+        wallet_type: wallet.kind.as_str().to_string(),
+        token,
+        expires_at,
+        preferred_language,
+        message: lang.t("message.user_registered"),
     }))
 }
 
-// ✅ Fonction 1: Soumettre une idée (texte)
-pub async fn submit_idea(
-    data: web::Json<SubmitIdeaRequest>,
+// Langue demandée par le client, refusée si elle n'est pas livrée
+fn supported_locale(tag: &str) -> AppResult<Locale> {
+    Locale::parse(tag).ok_or_else(|| {
+        AppError::InvalidRequest(
+            Message::new("error.unsupported_language")
+                .with("language", tag.trim())
+                .with("supported", Locale::supported().collect::<Vec<_>>().join(", ")),
+        )
+    })
+}
+
+// ✅ Langue préférée du compte (`null` : langue du navigateur à chaque requête)
+pub async fn set_preferred_language(
+    caller: AuthenticatedUser,
+    data: web::Json<LanguagePreferenceRequest>,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
-    let idea_id = Uuid::new_v4();
+) -> AppResult<HttpResponse> {
+    let locale = data.preferred_language.as_deref().map(supported_locale).transpose()?;
+    let preferred_language = locale.map(|locale| locale.code().to_string());
 
-    if let Err(e) = sqlx::query!(
-        "INSERT INTO ideas (id, user_id, raw_idea, created_at) VALUES ($1, $2, $3, $4)",
-        idea_id,
-        data.user_id,
-        data.raw_idea,
+    sqlx::query!("UPDATE users SET preferred_language = $1 WHERE id = $2", preferred_language, caller.0)
+        .execute(pool.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(LanguagePreferenceResponse {
+        preferred_language,
+        supported_languages: Locale::supported().map(str::to_string).collect(),
+    }))
+}
+
+// ✅ Notifications du compte, rédigées dans sa langue
+pub async fn list_notifications(
+    caller: AuthenticatedUser,
+    pool: web::Data<PgPool>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(notifications::list(pool.as_ref(), caller.0, lang.0).await?))
+}
+
+// ✅ Connexion : e-mail et mot de passe contre un jeton de session
+pub async fn login(
+    data: web::Json<LoginRequest>,
+    pool: web::Data<PgPool>,
+    sessions: web::Data<SessionKeys>,
+) -> AppResult<HttpResponse> {
+    let account = sqlx::query!("SELECT id, password_hash FROM users WHERE email = $1", data.email)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+    // Même réponse pour un e-mail inconnu, un compte sans mot de passe ou un mot de passe faux
+    let (user_id, password_hash) = match account {
+        Some(account) => (Some(account.id), account.password_hash),
+        None => (None, None),
+    };
+    let password = data.password.clone();
+    let valid = web::block(move || auth::verify_password_or_dummy(&password, password_hash.as_deref()))
+        .await
+        .unwrap_or(false);
+
+    match user_id {
+        Some(user_id) if valid => {
+            let (token, expires_at) = sessions.issue(user_id);
+            Ok(HttpResponse::Ok().json(SessionResponse { user_id, token, expires_at }))
+        }
+        _ => Err(AppError::Unauthorized("error.invalid_credentials".into())),
+    }
+}
+
+// ✅ Connexion par wallet (1/2) : défi à signer
+pub async fn wallet_challenge(
+    data: web::Json<WalletChallengeRequest>,
+    pool: web::Data<PgPool>,
+    anchoring: web::Data<Anchoring>,
+) -> AppResult<HttpResponse> {
+    let wallet = wallet::parse(&data.wallet_address, ledger_network(&anchoring)).map_err(AppError::InvalidRequest)?;
+
+    // Le défi est émis même pour un wallet inconnu, pour ne pas révéler quels comptes existent
+    let challenge_id = Uuid::new_v4();
+    let now = Utc::now();
+    let expires_at = now + chrono::Duration::seconds(wallet_login::CHALLENGE_TTL_SECS);
+    let message = wallet_login::challenge_message(&wallet, &Uuid::new_v4().simple().to_string(), expires_at);
+
+    let _ = sqlx::query!("DELETE FROM wallet_challenges WHERE expires_at < $1", now)
+        .execute(pool.as_ref())
+        .await;
+
+    sqlx::query!(
+        "INSERT INTO wallet_challenges (id, wallet_address, message, expires_at, created_at) VALUES ($1, $2, $3, $4, $5)",
+        challenge_id,
+        wallet.canonical,
+        message,
+        expires_at,
+        now
+    )
+    .execute(pool.as_ref())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(WalletChallengeResponse { challenge_id, message, expires_at }))
+}
+
+// ✅ Connexion par wallet (2/2) : vérification de la signature et ouverture de session
+pub async fn wallet_login(
+    data: web::Json<WalletLoginRequest>,
+    pool: web::Data<PgPool>,
+    anchoring: web::Data<Anchoring>,
+    sessions: web::Data<SessionKeys>,
+) -> AppResult<HttpResponse> {
+    // Le défi est consommé avant toute vérification : une signature ne sert qu'une fois
+    let challenge = sqlx::query!(
+        "UPDATE wallet_challenges SET consumed_at = $2
+         WHERE id = $1 AND consumed_at IS NULL AND expires_at > $2
+         RETURNING wallet_address, message",
+        data.challenge_id,
         Utc::now()
     )
+    .fetch_optional(pool.as_ref())
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("error.invalid_challenge".into()))?;
+
+    // Adresse déjà validée à l'émission du défi
+    let wallet = wallet::parse(&challenge.wallet_address, None)
+        .map_err(|_| AppError::InvalidRequest("error.invalid_wallet".into()))?;
+
+    // Une adresse antérieure à la validation (wallet_type NULL) est comparée sans tenir compte de la casse
+    let account = sqlx::query!(
+        "SELECT id, wallet_public_key FROM users
+         WHERE wallet_address = $1 OR (wallet_type IS NULL AND lower(wallet_address) = lower($1))",
+        wallet.canonical
+    )
+    .fetch_optional(pool.as_ref())
+    .await?;
+    let stored = account.as_ref().and_then(|account| account.wallet_public_key.as_deref());
+
+    let key = wallet_login::resolve_public_key(anchoring.ledger().network(), &wallet, stored, data.public_key.as_deref())
+        .await
+        .map_err(AppError::MirrorUnavailable)?
+        .ok_or_else(|| AppError::Unauthorized("error.account_key_mismatch".into()))?;
+
+    if !wallet_login::verify_signature(key.key(), &challenge.message, &data.signature) {
+        return Err(AppError::Unauthorized("error.invalid_signature".into()));
+    }
+    let user_id = account.ok_or_else(|| AppError::Unauthorized("error.no_account_for_wallet".into()))?.id;
+
+    // La clé vérifiée est mémorisée sur le compte. Sans mirror node, une clé déjà mémorisée ne change plus :
+    // la condition écarte aussi une première connexion concurrente avec une autre clé.
+    let mirror = matches!(key, wallet_login::KeySource::Mirror(_));
+    let updated = sqlx::query!(
+        "UPDATE users SET wallet_public_key = $2, wallet_verified_at = $3
+         WHERE id = $1 AND ($4 OR wallet_public_key IS NULL OR wallet_public_key = $2)",
+        user_id,
+        key.key().to_string(),
+        Utc::now(),
+        mirror
+    )
     .execute(pool.as_ref())
-    .await
-    {
-        eprintln!("Erreur insertion idée: {}", e);
-        return Ok(HttpResponse::InternalServerError().json(json!({"message": "Échec d'enregistrement de l'idée"})));
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(AppError::Unauthorized("error.account_key_mismatch".into()));
+    }
+
+    if let wallet_login::KeySource::Supplied(_) = key {
+        println!("⚠️  Clé du wallet {} fournie par le client mémorisée à la première connexion (registre sans mirror node)", wallet.canonical);
     }
 
+    let (token, expires_at) = sessions.issue(user_id);
+    Ok(HttpResponse::Ok().json(SessionResponse { user_id, token, expires_at }))
+}
+
+// Réseau Hedera du registre d'ancrage, `None` pour le registre simulé (sommes de contrôle non vérifiées)
+fn ledger_network(anchoring: &Anchoring) -> Option<network::HederaNetwork> {
+    network::HederaNetwork::parse(anchoring.ledger().network())
+}
+
+// Ressource absente ou appartenant à un autre utilisateur : même réponse 404, pour ne pas révéler son existence
+fn owned_by(owner: Result<Option<Uuid>, sqlx::Error>, caller: &AuthenticatedUser, not_found: &'static str) -> AppResult<()> {
+    match owner? {
+        Some(owner) if owner == caller.0 => Ok(()),
+        _ => Err(AppError::NotFound(not_found.into())),
+    }
+}
+
+// ✅ Fonction 1: Soumettre une idée (texte)
+pub async fn submit_idea(
+    caller: AuthenticatedUser,
+    data: web::Json<SubmitIdeaRequest>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let idea_id = Uuid::new_v4();
+
+    // ✅ L'idée est chiffrée avec la clé de données de son auteur
+    store::insert_idea(pool.as_ref(), vault.as_ref(), idea_id, caller.0, &data.raw_idea, Utc::now())
+        .await
+        .map_err(AppError::internal("error.idea_store_failed"))?;
+
     Ok(HttpResponse::Ok().json(SubmitIdeaResponse {
         idea_id,
-        message: "Idée enregistrée avec succès".to_string(),
+        message: lang.t("message.idea_registered"),
     }))
 }
 
-// ✅ Fonction 2: Générer le résumé IA
+// ✅ Fonction 2: Générer le résumé IA (en tâche de fond : 202 puis suivi par GET /jobs/{id})
 pub async fn generate_summary(
+    caller: AuthenticatedUser,
     path: web::Path<Uuid>,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
+    queue: web::Data<SummaryQueue>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
     let idea_id = path.into_inner();
 
-    // ✅ Correction 1 : Utiliser match au lieu de ? après map_err
-    let idea_row = match sqlx::query_as!(Idea, "SELECT * FROM ideas WHERE id = $1", idea_id)
+    owned_by(store::idea_owner(pool.as_ref(), idea_id).await, &caller, "error.idea_not_found")?;
+
+    let job = queue.enqueue(pool.as_ref(), idea_id, caller.0).await?;
+
+    Ok(HttpResponse::Accepted()
+        .insert_header(("Location", format!("/api/v1/jobs/{}", job.id)))
+        .json(summary_job_response(job, lang.0, Vec::new())))
+}
+
+fn summary_job_response(job: SummaryJob, locale: Locale, similar: Vec<SearchHitResponse>) -> SummaryJobResponse {
+    SummaryJobResponse {
+        similar,
+        error: job.error_code.map(|code| JobError {
+            code,
+            message: job
+                .error_message
+                .as_ref()
+                .and_then(Message::from_json)
+                .unwrap_or_else(|| Message::new("error.server"))
+                .render(locale),
+        }),
+        job_id: job.id,
+        idea_id: job.idea_id,
+        status: job.status,
+        summary_id: job.summary_id,
+        created_at: job.created_at.to_rfc3339(),
+        updated_at: job.updated_at.to_rfc3339(),
+    }
+}
+
+// ✅ État d'une génération de résumé : queued | running | succeeded | failed,
+// avec les résumés existants les plus proches une fois le résumé généré
+pub async fn get_job(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    index: web::Data<SearchIndex>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let job = sqlx::query_as!(SummaryJob, "SELECT * FROM summary_jobs WHERE id = $1", path.into_inner())
         .fetch_optional(pool.as_ref())
-        .await
+        .await?;
+
+    owned_by(Ok(job.as_ref().map(|job| job.user_id)), &caller, "error.job_not_found")?;
+    let job = job.ok_or_else(|| AppError::NotFound("error.job_not_found".into()))?;
+
+    let similar = match job.summary_id {
+        Some(summary_id) => {
+            let visibility = search::Visibility::of(pool.as_ref(), caller.0).await?;
+            let hits = index
+                .similar(summary_id, &visibility, SIMILAR_LIMIT)
+                .map_err(AppError::internal("error.search_failed"))?;
+            visible_hits(pool.as_ref(), &caller, hits).await?
+        }
+        None => Vec::new(),
+    };
+
+    Ok(HttpResponse::Ok().json(summary_job_response(job, lang.0, similar)))
+}
+
+// Nombre de résumés proches joints au résultat d'une génération
+const SIMILAR_LIMIT: usize = 5;
+const SEARCH_DEFAULT_LIMIT: usize = 10;
+const SEARCH_MAX_LIMIT: usize = 50;
+
+// Résumés des autres inventeurs (ancrés, titre divulgué, cf. `search::Visibility`) : titre et preuve seulement
+async fn visible_hits(pool: &PgPool, caller: &AuthenticatedUser, hits: Vec<search::Hit>) -> Result<Vec<SearchHitResponse>, sqlx::Error> {
+    let ids: Vec<Uuid> = hits.iter().map(|hit| hit.summary_id).collect();
+    let proofs: std::collections::HashMap<Uuid, (Uuid, bool)> = sqlx::query!(
+        "SELECT summary_id, id, title_disclosed FROM proofs WHERE summary_id = ANY($1)",
+        &ids
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.summary_id, (row.id, row.title_disclosed)))
+    .collect();
+
+    Ok(hits
+        .into_iter()
+        .map(|hit| {
+            let proof = proofs.get(&hit.summary_id).copied();
+            let own = hit.user_id == caller.0;
+            SearchHitResponse {
+                own,
+                summary_id: own.then_some(hit.summary_id),
+                proof_id: proof.map(|(proof_id, _)| proof_id),
+                title: (own || proof.is_some_and(|(_, disclosed)| disclosed)).then_some(hit.title),
+                snippet: own.then_some(hit.snippet),
+                cpc_code: hit.cpc_code,
+                created_at: hit.created_at.to_rfc3339(),
+                score: hit.score,
+            }
+        })
+        .collect())
+}
+
+// ✅ Recherche d'antériorités parmi les résumés de la plateforme (texte, préfixes CPC, période)
+pub async fn search_summaries(
+    caller: AuthenticatedUser,
+    params: web::Query<SearchParams>,
+    pool: web::Data<PgPool>,
+    index: web::Data<SearchIndex>,
+) -> AppResult<HttpResponse> {
+    let params = params.into_inner();
+
+    if let (Some(from), Some(to)) = (params.from, params.to)
+        && from > to
     {
-        Ok(row) => row,
-        Err(e) => {
-            eprintln!("Erreur récupération idée: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(json!({"message": "Erreur serveur"})));
+        return Err(AppError::InvalidRequest("error.invalid_date_range".into()));
+    }
+    let cpc_prefixes: Vec<String> = params
+        .cpc
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|prefix| !prefix.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let query = search::SearchQuery {
+        text: &params.q,
+        cpc_prefixes: &cpc_prefixes,
+        from: params.from.and_then(|date| date.and_hms_opt(0, 0, 0)).map(|at| at.and_utc()),
+        to: params.to.and_then(|date| date.and_hms_micro_opt(23, 59, 59, 999_999)).map(|at| at.and_utc()),
+        limit: params.limit.unwrap_or(SEARCH_DEFAULT_LIMIT).clamp(1, SEARCH_MAX_LIMIT),
+    };
+    let visibility = search::Visibility::of(pool.as_ref(), caller.0).await?;
+    let (total, hits) = index.search(&query, &visibility).map_err(AppError::internal("error.search_failed"))?;
+
+    Ok(HttpResponse::Ok().json(SearchResponse {
+        total,
+        hits: visible_hits(pool.as_ref(), &caller, hits).await?,
+    }))
+}
+
+// ✅ Évaluations de l'idée par l'IA, y compris les refus (score, seuil de la formule, motif)
+pub async fn list_evaluations(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let idea_id = path.into_inner();
+
+    owned_by(store::idea_owner(pool.as_ref(), idea_id).await, &caller, "error.idea_not_found")?;
+
+    let evaluations = evaluations::list(pool.as_ref(), vault.as_ref(), idea_id, lang.0)
+        .await
+        .map_err(AppError::internal("error.server"))?;
+    Ok(HttpResponse::Ok().json(evaluations))
+}
+
+// ✅ Demande de revue humaine d'une idée refusée par l'IA
+pub async fn request_review(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let evaluation_id = path.into_inner();
+
+    let owner = store::evaluation_owner(pool.as_ref(), evaluation_id).await.map(|row| row.map(|(_, user_id)| user_id));
+    owned_by(owner, &caller, "error.evaluation_not_found")?;
+
+    // Une seule demande par évaluation refusée ; la condition protège des demandes simultanées
+    let now = Utc::now();
+    let requested = sqlx::query!(
+        "UPDATE ai_evaluations SET review_status = 'requested', review_requested_at = $2
+         WHERE id = $1 AND NOT accepted AND review_status IS NULL
+         RETURNING id",
+        evaluation_id,
+        now
+    )
+    .fetch_optional(pool.as_ref())
+    .await?;
+
+    if requested.is_none() {
+        let accepted = sqlx::query_scalar!("SELECT accepted FROM ai_evaluations WHERE id = $1", evaluation_id)
+            .fetch_one(pool.as_ref())
+            .await?;
+        return Err(AppError::Conflict(
+            if accepted { "error.review_not_needed" } else { "error.review_already_requested" }.into(),
+        ));
+    }
+
+    println!("🧑‍⚖️ Revue humaine demandée pour l'évaluation {}", evaluation_id);
+    Ok(HttpResponse::Ok().json(ReviewRequestResponse {
+        evaluation_id,
+        review_status: "requested".to_string(),
+        review_requested_at: now.to_rfc3339(),
+        message: lang.t("message.review_requested"),
+    }))
+}
+
+// Commentaire keep-alive du flux de génération, qui relit aussi la tâche en base
+const STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+fn sse_frame(progress: &Progress, job_id: Uuid, locale: Locale) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", progress.event(), progress.data(job_id, locale)))
+}
+
+// ✅ Suivi en direct de la dernière génération d'une idée (Server-Sent Events) :
+// queued, calling_ai, partial (texte en cours, si le service IA le produit), validating, puis persisted ou failed.
+// Le flux ne lance aucune génération : une reconnexion reprend simplement le suivi.
+pub async fn stream_summary(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    queue: web::Data<SummaryQueue>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let idea_id = path.into_inner();
+
+    owned_by(store::idea_owner(pool.as_ref(), idea_id).await, &caller, "error.idea_not_found")?;
+
+    // Abonnement avant la lecture de la tâche, pour ne perdre aucune étape entre les deux
+    let mut events = queue.subscribe();
+    let job = summary_worker::latest_job(pool.as_ref(), idea_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.job_not_found".into()))?;
+
+    let (tx, mut rx) = mpsc::channel::<Bytes>(64);
+    let locale = lang.0;
+    tokio::spawn(async move {
+        let job_id = job.id;
+        let current = Progress::of_job(&job);
+        if tx.send(sse_frame(&current, job_id, locale)).await.is_err() || current.is_terminal() {
+            return;
+        }
+
+        let mut keep_alive = tokio::time::interval(STREAM_KEEP_ALIVE);
+        keep_alive.tick().await;
+        loop {
+            // Relecture en base : seule une étape finale y est encore utile au client
+            let reload = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) if event.job_id == job_id => {
+                        if tx.send(sse_frame(&event.progress, job_id, locale)).await.is_err() || event.progress.is_terminal() {
+                            return;
+                        }
+                        false
+                    }
+                    Ok(_) => false,
+                    Err(broadcast::error::RecvError::Lagged(_)) => true,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                _ = keep_alive.tick() => {
+                    if tx.send(Bytes::from_static(b": keep-alive\n\n")).await.is_err() {
+                        return;
+                    }
+                    true
+                }
+                _ = tx.closed() => return,
+            };
+            if reload {
+                match sqlx::query_as!(SummaryJob, "SELECT * FROM summary_jobs WHERE id = $1", job_id)
+                    .fetch_optional(pool.as_ref())
+                    .await
+                {
+                    Ok(Some(job)) => {
+                        let progress = Progress::of_job(&job);
+                        if progress.is_terminal() {
+                            let _ = tx.send(sse_frame(&progress, job_id, locale)).await;
+                            return;
+                        }
+                    }
+                    Ok(None) => return,
+                    Err(e) => eprintln!("Erreur suivi de la génération {}: {}", job_id, e),
+                }
+            }
+        }
+    });
+
+    let body = futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx).map(|frame| frame.map(Ok::<_, std::convert::Infallible>)));
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(body))
+}
+
+// Empreinte ancrée de la dernière révision antérieure du même résumé : la preuve d'une révision renvoie
+// ainsi à celle qui la précède. Une révision encore en file n'est pas retenue, son ancrage peut échouer
+// et être relancé avec une autre empreinte.
+async fn previous_revision_hash(pool: &PgPool, summary_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT anchored.hash
+           FROM summaries current
+           JOIN summaries earlier ON earlier.original_id = current.original_id AND earlier.revision < current.revision
+           JOIN proofs anchored ON anchored.summary_id = earlier.id
+           WHERE current.id = $1
+           ORDER BY earlier.revision DESC
+           LIMIT 1"#,
+        summary_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn latest_revision(pool: &PgPool, original_id: Uuid) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT MAX(revision) AS "revision!" FROM summaries WHERE original_id = $1"#,
+        original_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+fn summary_response(summary: Summary, claims: Vec<Claim>, latest_revision: i32) -> SummaryResponse {
+    let claims = SummaryDocument::from_summary(&summary, claims).claim_set().into_owned();
+    SummaryResponse {
+        summary_id: summary.id,
+        idea_id: summary.idea_id,
+        original_id: summary.original_id,
+        revision: summary.revision,
+        latest_revision,
+        author_id: summary.author_id,
+        created_at: summary.created_at.to_rfc3339(),
+        title: summary.title,
+        problem: summary.problem,
+        solution: summary.solution,
+        claim: summary.claim,
+        claims,
+        cpc_code: summary.cpc_code,
+    }
+}
+
+// ✅ Révision d'un résumé et son jeu de revendications
+pub async fn get_summary(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+) -> AppResult<HttpResponse> {
+    let summary_id = path.into_inner();
+
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.summary_not_found")?;
+
+    let (summary, claims) = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+
+    let latest = latest_revision(pool.as_ref(), summary.original_id).await?;
+    Ok(HttpResponse::Ok().json(summary_response(summary, claims, latest)))
+}
+
+// Valeur modifiée d'un champ texte, ou celle de la révision précédente
+fn revised_field(field: &'static str, value: Option<String>, previous: &str) -> AppResult<String> {
+    match value {
+        None => Ok(previous.to_string()),
+        Some(value) if value.trim().is_empty() => {
+            Err(AppError::InvalidRequest(Message::new("error.empty_field").with("field", field)))
         }
+        Some(value) => Ok(value.trim().to_string()),
+    }
+}
+
+// ✅ Modifier un résumé : une nouvelle révision est créée, les précédentes restent inchangées
+pub async fn revise_summary(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    data: web::Json<ReviseSummaryRequest>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    index: web::Data<SearchIndex>,
+) -> AppResult<HttpResponse> {
+    let summary_id = path.into_inner();
+    let data = data.into_inner();
+
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.summary_not_found")?;
+
+    let (summary, claims) = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+
+    // Pas de branches dans l'historique : seule la dernière révision se modifie
+    let latest = latest_revision(pool.as_ref(), summary.original_id).await?;
+    if summary.revision != latest {
+        return Err(AppError::StaleRevision { latest_revision: latest });
+    }
+
+    let before = SummaryDocument::from_summary(&summary, claims);
+
+    let revised_claims = match data.claims {
+        Some(claims) => claims
+            .into_iter()
+            .map(|claim| Claim { text: claim.text.trim().to_string(), ..claim })
+            .collect(),
+        None => before.claim_set().into_owned(),
     };
+    claims::validate(&revised_claims).map_err(AppError::InvalidRequest)?;
 
-    let idea = match idea_row {
-        Some(i) => i,
-        None => return Ok(HttpResponse::NotFound().json(json!({"message": "Idée non trouvée"}))),
+    let id = Uuid::new_v4();
+    let revision = Summary {
+        id,
+        idea_id: summary.idea_id,
+        title: revised_field("title", data.title, &summary.title)?,
+        problem: revised_field("problem", data.problem, &summary.problem)?,
+        solution: revised_field("solution", data.solution, &summary.solution)?,
+        claim: revised_claims[0].text.clone(),
+        cpc_code: revised_field("cpc_code", data.cpc_code, &summary.cpc_code)?,
+        created_at: Utc::now(),
+        original_id: summary.original_id,
+        revision: summary.revision + 1,
+        author_id: caller.0,
     };
 
-    let ai_response = match ai_client::call_ai_service(idea.raw_idea.clone()).await {
-        Ok(resp) => resp,
-        Err(e) => {
-            eprintln!("Erreur IA: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(json!({"message": "Service IA indisponible"})));
+    let after = SummaryDocument::from_summary(&revision, revised_claims.clone());
+    if diff::diff_fields(&before, &after).is_empty() && diff::diff_claims(&before, &after).is_empty() {
+        return Err(AppError::InvalidRequest("error.no_change".into()));
+    }
+
+    if let Err(e) = store::insert_summary(pool.as_ref(), vault.as_ref(), caller.0, &revision, &revised_claims).await {
+        // Une autre modification de la même révision a été enregistrée entre-temps
+        if let Some(sqlx::Error::Database(db)) = e.downcast_ref::<sqlx::Error>()
+            && db.is_unique_violation()
+        {
+            return Err(AppError::Conflict("error.summary_modified".into()));
         }
+        return Err(AppError::internal("error.revision_store_failed")(e));
+    }
+    if let Err(e) = index.upsert(caller.0, &revision, &revised_claims) {
+        eprintln!("Erreur indexation de la révision {}: {}", revision.id, e);
+    }
+
+    let latest = revision.revision;
+    Ok(HttpResponse::Created().json(summary_response(revision, revised_claims, latest)))
+}
+
+// ✅ Historique des révisions, avec l'empreinte et l'état d'ancrage de chacune
+pub async fn list_summary_revisions(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+) -> AppResult<HttpResponse> {
+    let summary_id = path.into_inner();
+
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.summary_not_found")?;
+
+    let rows = sqlx::query!(
+        r#"SELECT s.id, s.revision, s.author_id, s.created_at,
+                  COALESCE(p.hash, j.hash) AS proof_hash,
+                  CASE WHEN p.id IS NOT NULL THEN 'anchored' ELSE j.status END AS proof_status
+           FROM summaries current
+           JOIN summaries s ON s.original_id = current.original_id
+           LEFT JOIN proofs p ON p.summary_id = s.id
+           LEFT JOIN proof_jobs j ON j.summary_id = s.id
+           WHERE current.id = $1
+           ORDER BY s.revision"#,
+        summary_id
+    )
+    .fetch_all(pool.as_ref())
+    .await?;
+
+    let revisions: Vec<SummaryRevision> = rows
+        .into_iter()
+        .map(|row| SummaryRevision {
+            summary_id: row.id,
+            revision: row.revision,
+            author_id: row.author_id,
+            created_at: row.created_at.to_rfc3339(),
+            proof_hash: row.proof_hash,
+            proof_status: row.proof_status,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(revisions))
+}
+
+// ✅ Différences entre deux révisions d'un même résumé
+pub async fn diff_summary_revisions(
+    caller: AuthenticatedUser,
+    path: web::Path<(Uuid, Uuid)>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+) -> AppResult<HttpResponse> {
+    let (from_id, to_id) = path.into_inner();
+
+    owned_by(store::summary_owner(pool.as_ref(), from_id).await, &caller, "error.summary_not_found")?;
+
+    let mut revisions = Vec::with_capacity(2);
+    for summary_id in [from_id, to_id] {
+        let row = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+        revisions.push(row);
+    }
+    let (to, to_claims) = revisions.pop().expect("deux révisions");
+    let (from, from_claims) = revisions.pop().expect("deux révisions");
+
+    // Même résumé d'origine, donc même propriétaire que la première révision
+    if from.original_id != to.original_id {
+        return Err(AppError::InvalidRequest("error.not_same_summary".into()));
+    }
+
+    let before = SummaryDocument::from_summary(&from, from_claims);
+    let after = SummaryDocument::from_summary(&to, to_claims);
+    Ok(HttpResponse::Ok().json(RevisionDiffResponse {
+        from_summary_id: from.id,
+        from_revision: from.revision,
+        to_summary_id: to.id,
+        to_revision: to.revision,
+        fields: diff::diff_fields(&before, &after),
+        claims: diff::diff_claims(&before, &after),
+    }))
+}
+
+// Réponse identique pour un premier enregistrement et pour ses répétitions
+fn proof_registered_body(proof: &Proof, lang: Lang) -> serde_json::Value {
+    json!({
+        "transaction_id": proof.hedera_tx_id,
+        "hash": proof.hash,
+        "hash_scheme": proof.hash_scheme,
+        "topic_sequence_number": proof.topic_sequence_number,
+        "merkle_root": proof.merkle_root,
+        "merkle_leaf_index": proof.merkle_leaf_index,
+        "network": proof.network,
+        "explorer_url": network::explorer_url(&proof.network, &proof.hedera_tx_id),
+        "inventor_signed": proof.inventor_signature.is_some(),
+        "timestamp": proof.timestamp.to_rfc3339(),
+        "status": "registered",
+        "message": lang.t("message.proof_registered")
+    })
+}
+
+// Tâche d'ancrage encore en file (pending) ou abandonnée (failed)
+fn proof_job_body(job: &ProofJob, lang: Lang) -> serde_json::Value {
+    let message = match job.status.as_str() {
+        "failed" => lang.t("message.proof_failed"),
+        _ => lang.t("message.proof_queued"),
     };
+    json!({
+        "summary_id": job.summary_id,
+        "hash": job.hash,
+        "hash_scheme": job.hash_scheme,
+        "status": job.status,
+        "attempts": job.attempts,
+        "inventor_signed": job.inventor_signature.is_some(),
+        "next_attempt_at": job.next_attempt_at.to_rfc3339(),
+        "last_error": job.last_error,
+        "message": message
+    })
+}
 
-    if ai_response.novelty_score < 50 {
-        return Ok(HttpResponse::BadRequest().json(json!({"message": "Idée probablement non brevetable"})));
+// En-tête `Idempotency-Key` facultatif : 1 à 255 caractères ASCII visibles
+fn idempotency_key(req: &HttpRequest) -> AppResult<Option<String>> {
+    let Some(value) = req.headers().get("Idempotency-Key") else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= 255 && key.chars().all(|c| c.is_ascii_graphic()) => {
+            Ok(Some(key.to_string()))
+        }
+        _ => Err(AppError::InvalidRequest("error.invalid_idempotency_key".into())),
     }
+}
+
+// ✅ Co-signature : empreinte à signer par l'inventeur avant l'enregistrement de la preuve
+pub async fn get_signing_payload(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+) -> AppResult<HttpResponse> {
+    let summary_id = path.into_inner();
 
-    let summary_id = Uuid::new_v4();
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.summary_not_found")?;
 
-    if let Err(e) = sqlx::query!(
-        r#"INSERT INTO summaries (id, idea_id, title, problem, solution, claim, cpc_code, created_at)
-          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+    let (summary, claims) = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+    let mut document = SummaryDocument::from_summary(&summary, claims);
+    document.previous_hash = previous_revision_hash(pool.as_ref(), summary_id).await?;
+
+    let wallet_address = sqlx::query_scalar!("SELECT wallet_address FROM users WHERE id = $1", caller.0)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+    let hash = proof_document::hash_document(CURRENT_HASH_SCHEME, &document);
+    Ok(HttpResponse::Ok().json(SigningPayloadResponse {
         summary_id,
-        idea_id,
-        ai_response.title,
-        ai_response.problem,
-        ai_response.solution,
-        ai_response.claim,
-        ai_response.cpc_code,
-        Utc::now()
+        message: inventor_signature::signing_message(&hash),
+        hash,
+        hash_scheme: CURRENT_HASH_SCHEME.as_str().to_string(),
+        wallet_address,
+    }))
+}
+
+// Vérifie la co-signature fournie avec la clé du wallet de l'inventeur (mirror node, sinon clé fournie ou déjà vérifiée)
+async fn inventor_co_signature(
+    pool: &PgPool,
+    anchoring: &Anchoring,
+    user_id: Uuid,
+    hash: &str,
+    request: &RegisterProofRequest,
+) -> AppResult<Option<InventorSignature>> {
+    let Some(signature) = &request.inventor_signature else {
+        return Ok(None);
+    };
+
+    let user = sqlx::query!("SELECT wallet_address, wallet_public_key FROM users WHERE id = $1", user_id)
+        .fetch_one(pool)
+        .await?;
+    let wallet = wallet::parse(&user.wallet_address, None)
+        .map_err(|_| AppError::Unprocessable("error.invalid_account_wallet".into()))?;
+
+    let key = wallet_login::resolve_public_key(
+        anchoring.ledger().network(),
+        &wallet,
+        user.wallet_public_key.as_deref(),
+        request.public_key.as_deref(),
     )
-    .execute(pool.as_ref())
-    .await
-    {
-        eprintln!("Erreur insertion résumé: {}", e);
-        return Ok(HttpResponse::InternalServerError().json(json!({"message": "Échec stockage résumé"})));
+        .await
+        .map_err(AppError::MirrorUnavailable)?
+        .ok_or_else(|| AppError::Unprocessable("error.wallet_key_mismatch".into()))?;
+    // Sans mirror node, seule la clé vérifiée lors d'une connexion par wallet fait foi
+    if let wallet_login::KeySource::Supplied(_) = key {
+        return Err(AppError::Unprocessable("error.wallet_key_unverified".into()));
     }
 
-    Ok(HttpResponse::Ok().json(json!({
-        "summary_id": summary_id,
-        "status": "completed",
-        "message": "Résumé IA généré avec succès"
-    })))
+    let signature = signature.trim().trim_start_matches("0x").to_ascii_lowercase();
+    if !wallet_login::verify_signature(key.key(), &inventor_signature::signing_message(hash), &signature) {
+        return Err(AppError::Unprocessable("error.invalid_co_signature".into()));
+    }
+
+    Ok(Some(InventorSignature { account: wallet.canonical, public_key: key.key().to_string(), signature }))
 }
 
-// ✅ Fonction 3: Enregistrer la preuve sur Hedera
+// ✅ Fonction 3: Enregistrer la preuve sur Hedera (idempotent par résumé)
+// La demande est écrite dans la boîte d'envoi `proof_jobs` ; le worker d'ancrage la traite en arrière-plan.
+// Corps facultatif : `RegisterProofRequest` avec la co-signature de l'inventeur.
 pub async fn register_proof(
+    caller: AuthenticatedUser,
+    req: HttpRequest,
     path: web::Path<Uuid>,
+    body: web::Bytes,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
+    anchoring: web::Data<Anchoring>,
+    vault: web::Data<Vault>,
+) -> AppResult<HttpResponse> {
     let summary_id = path.into_inner();
+    let lang = Lang(i18n::resolve(&req).await);
+
+    let request: RegisterProofRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        RegisterProofRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| AppError::InvalidRequest(Message::new("error.invalid_body").with("detail", e)))?
+    };
+
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.summary_not_found")?;
+
+    let idempotency_key = idempotency_key(&req)?;
+
+    // Une clé d'idempotence ne peut servir qu'à un seul résumé
+    if let Some(key) = &idempotency_key
+        && let Some(other) = sqlx::query_scalar!("SELECT summary_id FROM proof_jobs WHERE idempotency_key = $1", key)
+            .fetch_optional(pool.as_ref())
+            .await?
+        && other != summary_id
+    {
+        return Err(AppError::Unprocessable("error.idempotency_key_reused".into()));
+    }
 
-    // ✅ Correction 2 : Utiliser match au lieu de ? après map_err
-    let summary_row = match sqlx::query_as!(Summary, "SELECT * FROM summaries WHERE id = $1", summary_id)
+    // Preuve déjà ancrée : on renvoie la même réponse sans nouvelle tâche
+    if let Some(proof) = sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE summary_id = $1", summary_id)
         .fetch_optional(pool.as_ref())
-        .await
+        .await?
     {
-        Ok(row) => row,
-        Err(e) => {
-            eprintln!("Erreur récupération résumé: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(json!({"message": "Erreur serveur"})));
-        }
-    };
+        return Ok(HttpResponse::Ok().json(proof_registered_body(&proof, lang)));
+    }
 
-    let summary = match summary_row {
-        Some(s) => s,
-        None => return Ok(HttpResponse::NotFound().json(json!({"message": "Résumé non trouvé"}))),
-    };
+    let (summary, claims) = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
 
-    let structured_data = format!(
-        r#"{{"title":"{}","problem":"{}","solution":"{}","claim":"{}","cpc_code":"{}"}}"#,
-        summary.title, summary.problem, summary.solution, summary.claim, summary.cpc_code
-    );
-
-    let mut hasher = Sha256::new();
-    hasher.update(structured_data.as_bytes());
-    let patent_hash = format!("{:x}", hasher.finalize());
-
-    let hedera_tx_id = match hedera_client::submit_to_hedera(
-        patent_hash.clone(),
-        summary.cpc_code.clone(),
-        "PLACEHOLDER_WALLET".to_string(), // À remplacer par user_wallet dans v2
-        summary.created_at,
-    ).await {
-        Ok(id) => id,
-        Err(e) => {
-            eprintln!("Échec Hedera: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(json!({"message": "Échec enregistrement blockchain"})));
-        }
-    };
+    let mut document = SummaryDocument::from_summary(&summary, claims);
+    document.previous_hash = previous_revision_hash(pool.as_ref(), summary_id).await?;
+    let patent_hash = proof_document::hash_document(CURRENT_HASH_SCHEME, &document);
 
-    let proof_id = Uuid::new_v4();
+    let inventor = inventor_co_signature(pool.as_ref(), anchoring.as_ref(), caller.0, &patent_hash, &request).await?;
 
-    if let Err(e) = sqlx::query!(
-        "INSERT INTO proofs (id, summary_id, hash, hedera_tx_id, timestamp, created_at) 
-         VALUES ($1, $2, $3, $4, $5, $6)",
-        proof_id,
+    // La tâche est créée dans la même transaction que la vérification de la preuve existante.
+    // Une tâche en lettre morte (failed) est remise en file avec un compteur de tentatives à zéro.
+    match enqueue_proof_job(pool.as_ref(), summary_id, idempotency_key, patent_hash, document.previous_hash, inventor).await? {
+        Ok(job) => Ok(HttpResponse::Accepted().json(proof_job_body(&job, lang))),
+        Err(proof) => Ok(HttpResponse::Ok().json(proof_registered_body(&proof, lang))),
+    }
+}
+
+// Tâche d'ancrage du résumé, ou la preuve si elle a été ancrée entre-temps
+async fn enqueue_proof_job(
+    pool: &PgPool,
+    summary_id: Uuid,
+    idempotency_key: Option<String>,
+    patent_hash: String,
+    previous_hash: Option<String>,
+    inventor: Option<InventorSignature>,
+) -> Result<Result<ProofJob, Proof>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    if let Some(proof) = sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE summary_id = $1", summary_id)
+        .fetch_optional(&mut *tx)
+        .await?
+    {
+        return Ok(Err(proof));
+    }
+
+    let now = Utc::now();
+    let job = sqlx::query_as!(
+        ProofJob,
+        "INSERT INTO proof_jobs (summary_id, idempotency_key, hash, hash_scheme, status, attempts,
+                                 next_attempt_at, locked_until, created_at, updated_at,
+                                 inventor_account, inventor_public_key, inventor_signature, previous_hash)
+         VALUES ($1, $2, $3, $4, 'pending', 0, $5, $5, $5, $5, $6, $7, $8, $9)
+         ON CONFLICT (summary_id) DO UPDATE
+            SET idempotency_key = COALESCE(proof_jobs.idempotency_key, EXCLUDED.idempotency_key),
+                -- Une tâche relancée reprend l'empreinte du moment, une révision antérieure a pu être ancrée entre-temps
+                hash = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.hash ELSE proof_jobs.hash END,
+                hash_scheme = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.hash_scheme
+                                   ELSE proof_jobs.hash_scheme END,
+                previous_hash = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.previous_hash
+                                     ELSE proof_jobs.previous_hash END,
+                -- La co-signature d'une tâche en file n'est plus modifiée ; une tâche relancée prend la nouvelle
+                inventor_account = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.inventor_account
+                                        ELSE proof_jobs.inventor_account END,
+                inventor_public_key = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.inventor_public_key
+                                           ELSE proof_jobs.inventor_public_key END,
+                inventor_signature = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.inventor_signature
+                                          ELSE proof_jobs.inventor_signature END,
+                -- La transaction ou le lot d'une autre empreinte n'est jamais repris
+                transaction_id = CASE WHEN proof_jobs.hash = EXCLUDED.hash OR proof_jobs.status <> 'failed'
+                                      THEN proof_jobs.transaction_id END,
+                batch_message = CASE WHEN proof_jobs.hash = EXCLUDED.hash OR proof_jobs.status <> 'failed'
+                                     THEN proof_jobs.batch_message END,
+                merkle_root = CASE WHEN proof_jobs.hash = EXCLUDED.hash OR proof_jobs.status <> 'failed'
+                                   THEN proof_jobs.merkle_root END,
+                merkle_leaf_index = CASE WHEN proof_jobs.hash = EXCLUDED.hash OR proof_jobs.status <> 'failed'
+                                         THEN proof_jobs.merkle_leaf_index END,
+                merkle_path = CASE WHEN proof_jobs.hash = EXCLUDED.hash OR proof_jobs.status <> 'failed'
+                                   THEN proof_jobs.merkle_path END,
+                status = CASE WHEN proof_jobs.status = 'failed' THEN 'pending' ELSE proof_jobs.status END,
+                attempts = CASE WHEN proof_jobs.status = 'failed' THEN 0 ELSE proof_jobs.attempts END,
+                next_attempt_at = CASE WHEN proof_jobs.status = 'failed' THEN EXCLUDED.next_attempt_at
+                                       ELSE proof_jobs.next_attempt_at END,
+                updated_at = EXCLUDED.updated_at
+         RETURNING *",
         summary_id,
+        idempotency_key,
         patent_hash,
-        hedera_tx_id,
-        summary.created_at,
-        Utc::now()
+        CURRENT_HASH_SCHEME.as_str(),
+        now,
+        inventor.as_ref().map(|i| i.account.clone()),
+        inventor.as_ref().map(|i| i.public_key.clone()),
+        inventor.as_ref().map(|i| i.signature.clone()),
+        previous_hash
     )
-    .execute(pool.as_ref())
-    .await
-    {
-        eprintln!("Erreur insertion preuve: {}", e);
-        return Ok(HttpResponse::InternalServerError().json(json!({"message": "Échec stockage preuve"})));
-    }
+    .fetch_one(&mut *tx)
+    .await?;
 
-    Ok(HttpResponse::Ok().json(json!({
-        "transaction_id": hedera_tx_id,
-        "timestamp": summary.created_at.to_rfc3339(),
-        "status": "registered",
-        "message": "Preuve enregistrée sur Hedera avec succès"
-    })))
+    tx.commit().await?;
+    Ok(Ok(job))
 }
 
-// ✅ Fonction 4: Récupérer le certificat
+// ✅ Fonction 4: Récupérer le certificat, signé par la plateforme (JWS détachée dans `jws`)
 pub async fn get_certificate(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    signer: web::Data<CertificateSigner>,
+) -> AppResult<HttpResponse> {
+    let mut certificate = certificate_for(pool.as_ref(), vault.as_ref(), &caller, path.into_inner()).await?;
+
+    // La signature porte sur le certificat sans son champ `jws`
+    let jws = signer.sign_detached(&certificate).map_err(AppError::internal("error.server"))?;
+    certificate.jws = Some(jws);
+
+    Ok(HttpResponse::Ok().json(certificate))
+}
+
+// ✅ Certificat en JWS compact, vérifiable hors ligne avec `/.well-known/jwks.json`
+pub async fn get_certificate_jws(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    signer: web::Data<CertificateSigner>,
+) -> AppResult<HttpResponse> {
+    let certificate = certificate_for(pool.as_ref(), vault.as_ref(), &caller, path.into_inner()).await?;
+    let jws = signer.sign_compact(&certificate).map_err(AppError::internal("error.server"))?;
+    Ok(HttpResponse::Ok().content_type("application/jose").body(jws))
+}
+
+// Contenu du certificat d'une preuve de l'utilisateur, avant signature
+async fn certificate_for(
+    pool: &PgPool,
+    vault: &Vault,
+    caller: &AuthenticatedUser,
+    summary_id: Uuid,
+) -> AppResult<CertificateResponse> {
+    owned_by(store::summary_owner(pool, summary_id).await, caller, "error.proof_not_found")?;
+
+    let proof = sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE summary_id = $1", summary_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.proof_not_found".into()))?;
+
+    // Le résumé actuel doit toujours reproduire l'empreinte ancrée, quel que soit son schéma
+    let (summary, claims) = store::fetch_summary_with_claims(pool, vault, summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+    let mut document = SummaryDocument::from_summary(&summary, claims);
+    document.previous_hash = proof.previous_hash.clone();
+    let (revision, document_verified) =
+        (summary.revision, proof_document::matches(&proof.hash_scheme, &document, &proof.hash));
+
+    let explorer_url = network::explorer_url(&proof.network, &proof.hedera_tx_id);
+
+    let merkle = anchoring::merkle_proof(&proof);
+
+    let inventor = match (proof.inventor_account, proof.inventor_public_key, proof.inventor_signature) {
+        (Some(account), Some(public_key), Some(signature)) => Some(InventorCoSignature {
+            verified: inventor_signature::verify(&proof.hash, &public_key, &signature),
+            account,
+            public_key,
+            signature,
+        }),
+        _ => None,
+    };
+
+    Ok(CertificateResponse {
+        summary_id,
+        proof_id: proof.id,
+        issuer: public_url(""),
+        issued_at: Utc::now().to_rfc3339(),
+        verification_url: verification_url(proof.id),
+        revision,
+        hash: proof.hash,
+        hash_scheme: proof.hash_scheme,
+        previous_hash: proof.previous_hash,
+        document_verified,
+        timestamp: proof.timestamp.to_rfc3339(),
+        consensus_timestamp: proof.consensus_timestamp.map(|t| t.to_rfc3339()),
+        topic_sequence_number: proof.topic_sequence_number,
+        running_hash: proof.running_hash,
+        reconciliation_status: proof.reconciliation_status,
+        network: proof.network,
+        hedera_tx_id: proof.hedera_tx_id,
+        explorer_url,
+        merkle,
+        inventor,
+        jws: None,
+    })
+}
+
+// ✅ Clés publiques de signature des certificats (JWKS), y compris les clés retirées
+pub async fn get_jwks(pool: web::Data<PgPool>) -> AppResult<HttpResponse> {
+    let jwks = certificate_signing::jwks(pool.as_ref()).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/jwk-set+json")
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(jwks))
+}
+
+// Adresse publique du serveur pour les liens imprimés (`PUBLIC_BASE_URL`, défaut http://127.0.0.1:8080)
+fn public_url(path: &str) -> String {
+    let base = std::env::var("PUBLIC_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string());
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+fn verification_url(proof_id: Uuid) -> String {
+    public_url(&format!("/verify/{}", proof_id))
+}
+
+// ✅ Fonction 4 bis : certificat PDF téléchargeable
+pub async fn get_certificate_pdf(
+    caller: AuthenticatedUser,
     path: web::Path<Uuid>,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
+    vault: web::Data<Vault>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
     let summary_id = path.into_inner();
 
-    // ✅ Correction 3 : Utiliser match au lieu de ? après map_err
-    let proof_row = match sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE summary_id = $1", summary_id)
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.proof_not_found")?;
+
+    let proof = sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE summary_id = $1", summary_id)
         .fetch_optional(pool.as_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.proof_not_found".into()))?;
+
+    let (summary, claims) = store::fetch_summary_with_claims(pool.as_ref(), vault.as_ref(), summary_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.summary_not_found".into()))?;
+    let revision = summary.revision;
+    let mut document = SummaryDocument::from_summary(&summary, claims);
+    document.previous_hash = proof.previous_hash.clone();
+
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", caller.0)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+    let data = CertificateData {
+        document_verified: proof_document::matches(&proof.hash_scheme, &document, &proof.hash),
+        inventor_name: user.full_name,
+        inventor_wallet: user.wallet_address,
+        claims: document.claim_set().into_owned(),
+        revision,
+        previous_hash: document.previous_hash,
+        title: document.title,
+        problem: document.problem,
+        solution: document.solution,
+        cpc_code: document.cpc_code,
+        explorer_url: network::explorer_url(&proof.network, &proof.hedera_tx_id),
+        verification_url: verification_url(proof.id),
+        hash: proof.hash,
+        hash_scheme: proof.hash_scheme,
+        transaction_id: proof.hedera_tx_id,
+        consensus_time: proof.consensus_timestamp.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+        registered_at: proof.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        network: proof.network,
+        topic_sequence_number: proof.topic_sequence_number,
+        merkle_root: proof.merkle_root,
+        inventor_signature: proof.inventor_signature,
+        locale: lang.0,
+    };
+
+    // Rendu hors du runtime async : la génération est purement CPU
+    let generated_at = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let pdf = web::block(move || certificate_pdf::render(&data, &generated_at))
         .await
+        .map_err(AppError::internal("error.certificate_generation_failed"))?
+        .map_err(AppError::internal("error.certificate_generation_failed"))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"certificat-brevetchain-{}.pdf\"", summary_id),
+        ))
+        .body(pdf))
+}
+
+// ✅ Vérification publique d'une preuve (document d'origine ou empreinte brute)
+pub async fn verify_proof(
+    data: web::Json<VerifyRequest>,
+    pool: web::Data<PgPool>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    let data = data.into_inner();
+
+    let supplied_hash = match data.hash {
+        Some(h) if proof_document::is_sha256_hex(h.trim()) => Some(h.trim().to_ascii_lowercase()),
+        Some(_) => return Err(AppError::InvalidRequest("error.invalid_hash".into())),
+        None => None,
+    };
+
+    // Empreintes candidates : une par schéma connu pour un document, sinon l'empreinte fournie
+    let hashes: Vec<String> = match (&data.document, &supplied_hash) {
+        (Some(document), _) => proof_document::candidate_hashes(document).into_iter().map(|(_, h)| h).collect(),
+        (None, Some(hash)) => vec![hash.clone()],
+        (None, None) => return Err(AppError::InvalidRequest("error.document_or_hash_required".into())),
+    };
+
+    // Empreinte de référence renvoyée quand aucune preuve ne correspond
+    let (reference_hash, reference_scheme) = match &data.document {
+        Some(document) => (
+            proof_document::hash_document(CURRENT_HASH_SCHEME, document),
+            Some(CURRENT_HASH_SCHEME.as_str().to_string()),
+        ),
+        None => (hashes[0].clone(), None),
+    };
+
+    if let (Some(_), Some(hash)) = (&data.document, &supplied_hash)
+        && !hashes.contains(hash)
     {
-        Ok(row) => row,
-        Err(e) => {
-            eprintln!("Erreur récupération preuve: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(json!({"message": "Erreur serveur"})));
-        }
+        return Ok(HttpResponse::Ok().json(VerifyResponse {
+            verdict: VerifyVerdict::HashMismatch,
+            hash: reference_hash,
+            hash_scheme: reference_scheme,
+            anchor: None,
+            message: lang.t("message.verify_hash_mismatch"),
+        }));
+    }
+
+    let proofs = sqlx::query_as!(
+        Proof,
+        "SELECT * FROM proofs WHERE hash = ANY($1) ORDER BY created_at",
+        &hashes
+    )
+    .fetch_all(pool.as_ref())
+    .await?;
+
+    // Pour un document, la preuve doit avoir été hachée avec le schéma qui reproduit l'empreinte
+    let anchored = proofs.into_iter().find(|proof| match &data.document {
+        Some(document) => proof_document::matches(&proof.hash_scheme, document, &proof.hash),
+        None => true,
+    });
+
+    let response = match anchored {
+        Some(proof) => VerifyResponse {
+            verdict: VerifyVerdict::Match,
+            hash: proof.hash.clone(),
+            hash_scheme: Some(proof.hash_scheme.clone()),
+            anchor: Some(AnchorDetails {
+                proof_id: proof.id,
+                merkle: anchoring::merkle_proof(&proof),
+                explorer_url: network::explorer_url(&proof.network, &proof.hedera_tx_id),
+                hedera_tx_id: proof.hedera_tx_id,
+                consensus_timestamp: proof.consensus_timestamp.map(|t| t.to_rfc3339()),
+                topic_sequence_number: proof.topic_sequence_number,
+                reconciliation_status: proof.reconciliation_status,
+                network: proof.network,
+            }),
+            message: lang.t("message.verify_match"),
+        },
+        None => VerifyResponse {
+            verdict: VerifyVerdict::NoMatch,
+            hash: reference_hash,
+            hash_scheme: reference_scheme,
+            anchor: None,
+            message: lang.t("message.verify_no_match"),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+// Fiche publique d'une preuve ; le titre, déchiffré, n'y figure qu'avec l'accord de l'inventeur
+async fn public_proof(pool: &PgPool, vault: &Vault, proof_id: Uuid) -> CryptoResult<Option<PublicProofResponse>> {
+    let Some(proof) = sqlx::query_as!(Proof, "SELECT * FROM proofs WHERE id = $1", proof_id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
     };
 
-    let proof = match proof_row {
-        Some(p) => p,
-        None => return Ok(HttpResponse::NotFound().json(json!({"message": "Preuve non trouvée"}))),
+    let title = if proof.title_disclosed {
+        store::fetch_summary(pool, vault, proof.summary_id).await?.map(|summary| summary.title)
+    } else {
+        None
     };
 
-    let explorer_url = format!("https://hashscan.io/testnet/transaction/{}", proof.hedera_tx_id);
+    // Preuve de la révision antérieure, quand elle est déjà ancrée
+    let previous_proof_id = match &proof.previous_hash {
+        Some(previous_hash) => sqlx::query_scalar!(
+            "SELECT p.id FROM proofs p
+             JOIN summaries s ON s.id = p.summary_id
+             JOIN summaries current ON current.original_id = s.original_id
+             WHERE current.id = $1 AND p.hash = $2",
+            proof.summary_id,
+            previous_hash
+        )
+        .fetch_optional(pool)
+        .await?,
+        None => None,
+    };
 
-    Ok(HttpResponse::Ok().json(CertificateResponse {
+    Ok(Some(PublicProofResponse {
+        proof_id,
+        title,
+        merkle: anchoring::merkle_proof(&proof),
+        explorer_url: network::explorer_url(&proof.network, &proof.hedera_tx_id),
+        inventor_signed: proof.inventor_signature.is_some(),
+        verification_url: verification_url(proof_id),
+        previous_hash: proof.previous_hash,
+        previous_proof_id,
         hash: proof.hash,
-        timestamp: proof.timestamp.to_rfc3339(),
+        hash_scheme: proof.hash_scheme,
+        registered_at: proof.timestamp.to_rfc3339(),
+        consensus_timestamp: proof.consensus_timestamp.map(|t| t.to_rfc3339()),
+        topic_sequence_number: proof.topic_sequence_number,
+        reconciliation_status: proof.reconciliation_status,
+        network: proof.network,
         hedera_tx_id: proof.hedera_tx_id,
-        explorer_url,
+    }))
+}
+
+// ✅ Page publique de vérification (cible du QR code du certificat)
+pub async fn get_verification_page(
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+    lang: Lang,
+) -> AppResult<HttpResponse> {
+    match public_proof(pool.as_ref(), vault.as_ref(), path.into_inner()).await {
+        Ok(Some(proof)) => Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .insert_header(("Content-Language", lang.0.code()))
+            .body(verification_page::render(&proof, lang.0))),
+        Ok(None) => Ok(HttpResponse::NotFound()
+            .content_type("text/html; charset=utf-8")
+            .insert_header(("Content-Language", lang.0.code()))
+            .body(verification_page::not_found(lang.0))),
+        Err(e) => {
+            eprintln!("Erreur page de vérification: {}", e);
+            Ok(HttpResponse::InternalServerError()
+                .content_type("text/plain; charset=utf-8")
+                .body(lang.t("error.server")))
+        }
+    }
+}
+
+// ✅ Même fiche en JSON
+pub async fn get_proof_verification(
+    path: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+    vault: web::Data<Vault>,
+) -> AppResult<HttpResponse> {
+    let proof = public_proof(pool.as_ref(), vault.as_ref(), path.into_inner())
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.proof_not_found".into()))?;
+    Ok(HttpResponse::Ok().json(proof))
+}
+
+// ✅ L'inventeur choisit d'afficher ou non le titre sur la page publique
+pub async fn set_title_disclosure(
+    caller: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    data: web::Json<TitleDisclosureRequest>,
+    pool: web::Data<PgPool>,
+) -> AppResult<HttpResponse> {
+    let summary_id = path.into_inner();
+
+    owned_by(store::summary_owner(pool.as_ref(), summary_id).await, &caller, "error.proof_not_found")?;
+
+    let proof_id = sqlx::query_scalar!(
+        "UPDATE proofs SET title_disclosed = $2 WHERE summary_id = $1 RETURNING id",
+        summary_id,
+        data.title_disclosed
+    )
+    .fetch_optional(pool.as_ref())
+    .await?
+    .ok_or_else(|| AppError::NotFound("error.proof_not_found".into()))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "proof_id": proof_id,
+        "title_disclosed": data.title_disclosed,
+        "verification_url": verification_url(proof_id),
+    })))
+}
+
+// ✅ Relecture d'un message ancré, directement depuis le registre
+pub async fn get_anchor(
+    path: web::Path<String>,
+    anchoring: web::Data<Anchoring>,
+) -> AppResult<HttpResponse> {
+    let transaction_id = path.into_inner();
+    let ledger = anchoring.ledger();
+
+    let message = ledger
+        .fetch_message(&transaction_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.unknown_transaction".into()))?;
+
+    Ok(HttpResponse::Ok().json(AnchoredMessageResponse {
+        ledger: ledger.name().to_string(),
+        network: ledger.network().to_string(),
+        transaction_id: message.transaction_id,
+        topic_id: message.topic_id,
+        consensus_timestamp: message.consensus_timestamp.to_rfc3339(),
+        sequence_number: message.sequence_number,
+        running_hash: message.running_hash,
+        message: String::from_utf8_lossy(&message.contents).into_owned(),
     }))
 }
 
 // ✅ Fonction 5: Vérifier le statut
 pub async fn get_status(
+    caller: AuthenticatedUser,
     path: web::Path<Uuid>,
     pool: web::Data<PgPool>,
-) -> ActixResult<HttpResponse> {
+    lang: Lang,
+) -> AppResult<HttpResponse> {
     let idea_id = path.into_inner();
 
+    owned_by(store::idea_owner(pool.as_ref(), idea_id).await, &caller, "error.idea_not_found")?;
+
     // ✅ Correction 4 : Remplacer SELECT 1 par SELECT true AS exists
     let idea_exists = sqlx::query!("SELECT true AS exists FROM ideas WHERE id = $1", idea_id)
         .fetch_optional(pool.as_ref())
-        .await
-        .map(|r| r.is_some())
-        .unwrap_or(false);
+        .await?
+        .is_some();
 
     let summary = sqlx::query!("SELECT true AS exists FROM summaries WHERE idea_id = $1", idea_id)
         .fetch_optional(pool.as_ref())
-        .await
-        .map(|r| r.is_some())
-        .unwrap_or(false);
+        .await?
+        .is_some();
 
     let proof = sqlx::query!(
         "SELECT true AS exists FROM proofs p JOIN summaries s ON p.summary_id = s.id WHERE s.idea_id = $1",
         idea_id
     )
     .fetch_optional(pool.as_ref())
-    .await
-    .map(|r| r.is_some())
-    .unwrap_or(false);
+    .await?
+    .is_some();
+
+    // Dernière génération de résumé demandée pour l'idée
+    let summary_job = summary_worker::latest_job(pool.as_ref(), idea_id)
+        .await?
+        .map(|job| summary_job_response(job, lang.0, Vec::new()));
+
+    // État de la tâche d'ancrage : pending | anchored | failed
+    let job = sqlx::query!(
+        "SELECT j.status, j.last_error FROM proof_jobs j JOIN summaries s ON j.summary_id = s.id
+         WHERE s.idea_id = $1 ORDER BY j.created_at DESC LIMIT 1",
+        idea_id
+    )
+    .fetch_optional(pool.as_ref())
+    .await?;
+
+    let (proof_status, proof_error) = match job {
+        Some(job) => (Some(job.status), job.last_error),
+        // Preuves antérieures à la boîte d'envoi
+        None if proof => (Some("anchored".to_string()), None),
+        None => (None, None),
+    };
 
     Ok(HttpResponse::Ok().json(StatusResponse {
         idea_received: idea_exists,
         ia_summary_ready: summary,
+        summary_job_id: summary_job.as_ref().map(|job| job.job_id),
+        summary_status: summary_job.as_ref().map(|job| job.status.clone()),
+        summary_error: summary_job.and_then(|job| job.error).map(|error| error.message),
         hedera_proof_registered: proof,
+        proof_status,
+        proof_error,
         agent_validated: false, // Placeholder
         office_submitted: false, // Placeholder
     }))
 }
 
 // ✅ Fonction 7: Health check
-pub async fn health() -> ActixResult<HttpResponse> {
+pub async fn health() -> AppResult<HttpResponse> {
     let services = vec!["database".to_string(), "hedera".to_string(), "ai".to_string()];
     Ok(HttpResponse::Ok().json(HealthResponse {
         status: "ok".to_string(),
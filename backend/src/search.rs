@@ -0,0 +1,481 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+use std::ops::Bound;
+use std::sync::Mutex;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{
+    AllQuery, BooleanQuery, ConstScoreQuery, MoreLikeThisQuery, Occur, Query, QueryParser, RangeQuery, TermQuery, TermSetQuery,
+};
+use tantivy::schema::{
+    Field, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED, STORED, STRING,
+};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::tokenizer::{AsciiFoldingFilter, LowerCaser, RemoveLongFilter, SimpleTokenizer, TextAnalyzer};
+use tantivy::{doc, DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+use uuid::Uuid;
+use crate::encryption::{CryptoResult, Vault};
+use crate::models::{Claim, Summary};
+use crate::store;
+
+// Recherche d'antériorités parmi les résumés déjà déposés sur la plateforme : index plein texte
+// (titre, problème, solution, revendications) avec filtres CPC et dates.
+// L'index est tenu en mémoire : le texte des résumés est chiffré en base et ne doit pas se retrouver
+// en clair sur disque. Chaque instance a le sien : construit au démarrage, mis à jour à chaque résumé
+// qu'elle enregistre et rafraîchi périodiquement depuis la base pour ceux des autres instances ;
+// seule la dernière révision de chaque résumé y figure.
+// Un inventeur ne trouve que ses résumés et, parmi ceux des autres, les résumés ancrés dont le titre
+// est divulgué : cette visibilité est lue en base à chaque recherche et filtre les résultats avant
+// comptage et classement. Le texte d'un résumé des autres n'est cherché que dans son titre, seule
+// partie divulguée : ni sa présence ni son rang ne révèlent le reste.
+
+// Minuscules et accents retirés : « énergie » trouve « energie », quelle que soit la langue du résumé
+const TOKENIZER: &str = "brevet";
+const WRITER_MEMORY: usize = 50_000_000;
+const SNIPPET_CHARS: usize = 200;
+// Marge du rafraîchissement : un résumé horodaté avant le passage précédent mais validé après est repris
+const REFRESH_OVERLAP: chrono::TimeDelta = chrono::TimeDelta::minutes(1);
+
+/// Critères d'une recherche ; sans texte, seuls les filtres s'appliquent.
+pub struct SearchQuery<'a> {
+    pub text: &'a str,
+    pub cpc_prefixes: &'a [String],
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: usize,
+}
+
+/// Résumés visibles d'un lecteur : les siens, et ceux des autres une fois ancrés avec titre divulgué.
+pub struct Visibility {
+    viewer: Uuid,
+    public: Vec<Uuid>,
+}
+
+impl Visibility {
+    pub async fn of(pool: &PgPool, viewer: Uuid) -> Result<Self, sqlx::Error> {
+        let public = sqlx::query_scalar!("SELECT summary_id FROM proofs WHERE title_disclosed")
+            .fetch_all(pool)
+            .await?;
+        Ok(Visibility { viewer, public })
+    }
+}
+
+/// Résumé trouvé, texte en clair : aux routes de n'en montrer que ce que le lecteur peut voir.
+pub struct Hit {
+    pub summary_id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub cpc_code: String,
+    pub created_at: DateTime<Utc>,
+    pub score: f32,
+    pub snippet: String,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    summary_id: Field,
+    original_id: Field,
+    user_id: Field,
+    title: Field,
+    problem: Field,
+    solution: Field,
+    claims: Field,
+    cpc: Field,
+    cpc_code: Field,
+    created_at: Field,
+}
+
+struct Writer {
+    writer: IndexWriter,
+    // Révision indexée de chaque résumé : une révision plus ancienne (reconstruction concurrente) est ignorée
+    revisions: HashMap<Uuid, i32>,
+    // Début du dernier rafraîchissement depuis la base
+    refreshed_at: Option<DateTime<Utc>>,
+}
+
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<Writer>,
+    fields: Fields,
+}
+
+impl SearchIndex {
+    pub fn new() -> tantivy::Result<Self> {
+        let text = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(TOKENIZER)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored();
+
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            summary_id: builder.add_text_field("summary_id", STRING | STORED),
+            original_id: builder.add_text_field("original_id", STRING),
+            user_id: builder.add_text_field("user_id", STRING | STORED),
+            title: builder.add_text_field("title", text.clone()),
+            problem: builder.add_text_field("problem", text.clone()),
+            solution: builder.add_text_field("solution", text.clone()),
+            claims: builder.add_text_field("claims", text),
+            // Code complet et ses préfixes (section, classe, sous-classe) : « H02 » trouve « H02J »
+            cpc: builder.add_text_field("cpc", STRING),
+            cpc_code: builder.add_text_field("cpc_code", STORED),
+            created_at: builder.add_date_field("created_at", INDEXED | STORED | FAST),
+        };
+
+        let index = Index::create_in_ram(builder.build());
+        index.tokenizers().register(
+            TOKENIZER,
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .filter(AsciiFoldingFilter)
+                .build(),
+        );
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        let writer = Writer { writer: index.writer(WRITER_MEMORY)?, revisions: HashMap::new(), refreshed_at: None };
+        Ok(SearchIndex { index, reader, writer: Mutex::new(writer), fields })
+    }
+
+    /// Indexe un résumé, en remplacement de sa révision précédente.
+    pub fn upsert(&self, user_id: Uuid, summary: &Summary, claims: &[Claim]) -> tantivy::Result<()> {
+        self.upsert_all(&[(user_id, summary.clone(), claims.to_vec())])
+    }
+
+    fn upsert_all(&self, summaries: &[(Uuid, Summary, Vec<Claim>)]) -> tantivy::Result<()> {
+        let f = self.fields;
+        let mut state = self.lock();
+        for (user_id, summary, claims) in summaries {
+            if state.revisions.get(&summary.original_id).is_some_and(|&indexed| indexed >= summary.revision) {
+                continue;
+            }
+            state.revisions.insert(summary.original_id, summary.revision);
+
+            let original_id = summary.original_id.to_string();
+            state.writer.delete_term(Term::from_field_text(f.original_id, &original_id));
+            let mut document = doc!(
+                f.summary_id => summary.id.to_string(),
+                f.original_id => original_id,
+                f.user_id => user_id.to_string(),
+                f.title => summary.title.as_str(),
+                f.problem => summary.problem.as_str(),
+                f.solution => summary.solution.as_str(),
+                f.cpc_code => summary.cpc_code.as_str(),
+                f.created_at => tantivy::DateTime::from_timestamp_micros(summary.created_at.timestamp_micros()),
+            );
+            for claim in claims {
+                document.add_text(f.claims, &claim.text);
+            }
+            for prefix in cpc_prefixes(&summary.cpc_code) {
+                document.add_text(f.cpc, prefix);
+            }
+            state.writer.add_document(document)?;
+        }
+        state.writer.commit()?;
+        self.reader.reload()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Writer> {
+        self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Indexe la dernière révision des résumés enregistrés depuis le passage précédent (tous au premier
+    /// passage), y compris par d'autres instances ; renvoie le nombre de résumés indexés.
+    pub async fn refresh(&self, pool: &PgPool, vault: &Vault) -> CryptoResult<usize> {
+        let started = Utc::now();
+        let since = self.lock().refreshed_at.map(|at| at - REFRESH_OVERLAP);
+        let latest = sqlx::query!(
+            "SELECT DISTINCT ON (s.original_id) s.id, s.original_id, s.revision, i.user_id
+             FROM summaries s JOIN ideas i ON i.id = s.idea_id
+             WHERE $1::timestamptz IS NULL OR s.created_at > $1
+             ORDER BY s.original_id, s.revision DESC",
+            since
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut summaries = Vec::with_capacity(latest.len());
+        for row in latest {
+            // Déjà indexé par cette instance : rien à déchiffrer
+            if self.lock().revisions.get(&row.original_id).is_some_and(|&indexed| indexed >= row.revision) {
+                continue;
+            }
+            if let Some((summary, claims)) = store::fetch_summary_with_claims(pool, vault, row.id).await? {
+                summaries.push((row.user_id, summary, claims));
+            }
+        }
+        self.upsert_all(&summaries)?;
+        self.lock().refreshed_at = Some(started);
+        Ok(summaries.len())
+    }
+
+    // Résumés du lecteur trouvés par `all_fields`, ou publics trouvés par `title_only` ; la visibilité
+    // est sans effet sur le score
+    fn visible(&self, all_fields: Box<dyn Query>, title_only: Box<dyn Query>, visibility: &Visibility) -> Box<dyn Query> {
+        let f = self.fields;
+        let own = || -> Box<dyn Query> {
+            let term = Term::from_field_text(f.user_id, &visibility.viewer.to_string());
+            Box::new(ConstScoreQuery::new(Box::new(TermQuery::new(term, IndexRecordOption::Basic)), 0.0))
+        };
+        let public = TermSetQuery::new(visibility.public.iter().map(|id| Term::from_field_text(f.summary_id, &id.to_string())));
+        let own_match = BooleanQuery::new(vec![(Occur::Must, all_fields), (Occur::Must, own())]);
+        let public_match = BooleanQuery::new(vec![
+            (Occur::Must, title_only),
+            (Occur::Must, Box::new(ConstScoreQuery::new(Box::new(public), 0.0))),
+            (Occur::MustNot, own()),
+        ]);
+        Box::new(BooleanQuery::new(vec![
+            (Occur::Should, Box::new(own_match) as Box<dyn Query>),
+            (Occur::Should, Box::new(public_match)),
+        ]))
+    }
+
+    pub fn search(&self, query: &SearchQuery, visibility: &Visibility) -> tantivy::Result<(usize, Vec<Hit>)> {
+        let f = self.fields;
+        let searcher = self.reader.searcher();
+
+        let mut parser = QueryParser::for_index(&self.index, vec![f.title, f.problem, f.solution, f.claims]);
+        parser.set_field_boost(f.title, 2.0);
+        let title_parser = QueryParser::for_index(&self.index, vec![f.title]);
+        // Syntaxe tolérante : une requête mal formée cherche ce qui peut l'être plutôt que d'échouer
+        let (text, title): (Box<dyn Query>, Box<dyn Query>) = match query.text.trim() {
+            "" => (Box::new(AllQuery), Box::new(AllQuery)),
+            text => (parser.parse_query_lenient(text).0, title_parser.parse_query_lenient(text).0),
+        };
+
+        let mut clauses = vec![(Occur::Must, self.visible(text.box_clone(), title, visibility))];
+        if !query.cpc_prefixes.is_empty() {
+            let cpc: Vec<(Occur, Box<dyn Query>)> = query
+                .cpc_prefixes
+                .iter()
+                .map(|prefix| {
+                    let term = Term::from_field_text(f.cpc, &prefix.trim().to_uppercase());
+                    (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>)
+                })
+                .collect();
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(cpc))));
+        }
+        if query.from.is_some() || query.to.is_some() {
+            let bound = |date: Option<DateTime<Utc>>| match date {
+                Some(date) => Bound::Included(Term::from_field_date(
+                    f.created_at,
+                    tantivy::DateTime::from_timestamp_micros(date.timestamp_micros()),
+                )),
+                None => Bound::Unbounded,
+            };
+            clauses.push((Occur::Must, Box::new(RangeQuery::new(bound(query.from), bound(query.to)))));
+        }
+        let full_query = BooleanQuery::new(clauses);
+
+        let (total, top) = searcher.search(&full_query, &(Count, TopDocs::with_limit(query.limit)))?;
+        let mut snippets = SnippetGenerator::create(&searcher, text.as_ref(), f.solution)?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        let hits = top
+            .into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher.doc(address)?;
+                let snippet = snippets.snippet_from_doc(&document);
+                Ok(self.hit(&document, score, snippet.fragment().to_string()))
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        Ok((total, hits.into_iter().flatten().collect()))
+    }
+
+    /// Résumés visibles les plus proches d'un résumé indexé, hors révisions du même résumé.
+    pub fn similar(&self, summary_id: Uuid, visibility: &Visibility, limit: usize) -> tantivy::Result<Vec<Hit>> {
+        let f = self.fields;
+        let searcher = self.reader.searcher();
+        let Some(address) = self.address(summary_id)? else {
+            return Ok(Vec::new());
+        };
+        let document: TantivyDocument = searcher.doc(address)?;
+
+        // Champs de texte seulement : les identifiants stockés rapprocheraient les résumés d'un même inventeur
+        let values = |field| -> Vec<OwnedValue> {
+            document.get_all(field).filter_map(|value| value.as_str()).map(|text| OwnedValue::Str(text.to_string())).collect()
+        };
+        let like = |fields: Vec<(Field, Vec<OwnedValue>)>| -> Box<dyn Query> {
+            Box::new(
+                MoreLikeThisQuery::builder()
+                    .with_min_doc_frequency(1)
+                    .with_min_term_frequency(1)
+                    .with_min_word_length(3)
+                    .with_document_fields(fields),
+            )
+        };
+        let text_fields = [f.title, f.problem, f.solution, f.claims];
+        let all_fields = like(text_fields.into_iter().map(|field| (field, values(field))).collect());
+        // Tout le texte du résumé rapproché du seul titre des résumés des autres
+        let title_only = like(vec![(f.title, text_fields.into_iter().flat_map(values).collect())]);
+        let itself = TermQuery::new(
+            Term::from_field_text(f.summary_id, &summary_id.to_string()),
+            IndexRecordOption::Basic,
+        );
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, self.visible(all_fields, title_only, visibility)),
+            (Occur::MustNot, Box::new(itself) as Box<dyn Query>),
+        ]);
+
+        let top = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        let hits = top
+            .into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher.doc(address)?;
+                let snippet = text_of(&document, f.solution).chars().take(SNIPPET_CHARS).collect();
+                Ok(self.hit(&document, score, snippet))
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        Ok(hits.into_iter().flatten().collect())
+    }
+
+    fn address(&self, summary_id: Uuid) -> tantivy::Result<Option<DocAddress>> {
+        let query = TermQuery::new(
+            Term::from_field_text(self.fields.summary_id, &summary_id.to_string()),
+            IndexRecordOption::Basic,
+        );
+        let top = self.reader.searcher().search(&query, &TopDocs::with_limit(1))?;
+        Ok(top.first().map(|(_, address)| *address))
+    }
+
+    fn hit(&self, document: &TantivyDocument, score: f32, snippet: String) -> Option<Hit> {
+        let f = self.fields;
+        let created_at = document.get_first(f.created_at).and_then(|value| value.as_datetime())?;
+        Some(Hit {
+            summary_id: text_of(document, f.summary_id).parse().ok()?,
+            user_id: text_of(document, f.user_id).parse().ok()?,
+            title: text_of(document, f.title).to_string(),
+            cpc_code: text_of(document, f.cpc_code).to_string(),
+            created_at: DateTime::from_timestamp_micros(created_at.into_timestamp_micros())?,
+            score,
+            snippet,
+        })
+    }
+}
+
+fn text_of(document: &TantivyDocument, field: Field) -> &str {
+    document.get_first(field).and_then(|value| value.as_str()).unwrap_or_default()
+}
+
+// « H02J 7/35 » -> H, H02, H02J, H02J 7/35
+fn cpc_prefixes(code: &str) -> Vec<String> {
+    let code = code.trim().to_uppercase();
+    let mut prefixes: Vec<String> = [1, 3, 4]
+        .into_iter()
+        .filter(|&length| code.len() > length && code.is_char_boundary(length))
+        .map(|length| code[..length].to_string())
+        .collect();
+    prefixes.push(code);
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn summary(title: &str, solution: &str, cpc_code: &str, year: i32) -> Summary {
+        let id = Uuid::new_v4();
+        Summary {
+            id,
+            idea_id: Uuid::new_v4(),
+            title: title.to_string(),
+            problem: String::new(),
+            solution: solution.to_string(),
+            claim: String::new(),
+            cpc_code: cpc_code.to_string(),
+            created_at: Utc.with_ymd_and_hms(year, 6, 1, 12, 0, 0).unwrap(),
+            original_id: id,
+            revision: 1,
+            author_id: Uuid::new_v4(),
+        }
+    }
+
+    fn query<'a>(text: &'a str, cpc_prefixes: &'a [String]) -> SearchQuery<'a> {
+        SearchQuery { text, cpc_prefixes, from: None, to: None, limit: 10 }
+    }
+
+    fn ids(hits: &[Hit]) -> Vec<Uuid> {
+        hits.iter().map(|hit| hit.summary_id).collect()
+    }
+
+    #[test]
+    fn filters_by_text_cpc_and_date() {
+        let index = SearchIndex::new().unwrap();
+        let user = Uuid::new_v4();
+        let solar = summary("Panneau solaire orientable", "Suit le soleil grâce à un capteur d'énergie", "H02J", 2025);
+        let pump = summary("Pompe médicale", "Dose l'énergie délivrée au patient", "A61B", 2026);
+        index.upsert(user, &solar, &[]).unwrap();
+        index.upsert(user, &pump, &[]).unwrap();
+        let own = Visibility { viewer: user, public: Vec::new() };
+
+        // Accents ignorés
+        let (total, _) = index.search(&query("energie", &[]), &own).unwrap();
+        assert_eq!(total, 2);
+
+        let (_, hits) = index.search(&query("energie", &["H02".to_string()]), &own).unwrap();
+        assert_eq!(ids(&hits), vec![solar.id]);
+
+        let since_2026 = SearchQuery { from: Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()), ..query("", &[]) };
+        let (_, hits) = index.search(&since_2026, &own).unwrap();
+        assert_eq!(ids(&hits), vec![pump.id]);
+    }
+
+    #[test]
+    fn keeps_only_the_latest_revision_and_finds_similar() {
+        let index = SearchIndex::new().unwrap();
+        let user = Uuid::new_v4();
+        let first = summary("Gourde filtrante", "Filtre l'eau de la gourde", "C02F", 2026);
+        let revised = Summary { id: Uuid::new_v4(), revision: 2, title: "Gourde filtrante connectée".to_string(), ..first.clone() };
+        let other = summary("Carafe filtrante", "Filtre l'eau de la carafe", "C02F", 2026);
+        index.upsert(user, &revised, &[]).unwrap();
+        index.upsert(user, &first, &[]).unwrap(); // Révision plus ancienne, ignorée
+        index.upsert(user, &other, &[]).unwrap();
+        let own = Visibility { viewer: user, public: Vec::new() };
+
+        let (_, hits) = index.search(&query("gourde", &[]), &own).unwrap();
+        assert_eq!(ids(&hits), vec![revised.id]);
+
+        let similar = index.similar(revised.id, &own, 5).unwrap();
+        assert_eq!(ids(&similar), vec![other.id]);
+    }
+
+    #[test]
+    fn hides_other_inventors_summaries_until_public() {
+        let index = SearchIndex::new().unwrap();
+        let (reader, author) = (Uuid::new_v4(), Uuid::new_v4());
+        let mine = summary("Éolienne de balcon", "Une éolienne compacte fixée au garde-corps", "F03D", 2026);
+        let confidential = summary("Éolienne pliante", "Une éolienne qui se replie par grand vent", "F03D", 2026);
+        let public = summary("Éolienne verticale", "Une éolienne à axe vertical silencieuse", "F03D", 2026);
+        index.upsert(reader, &mine, &[]).unwrap();
+        index.upsert(author, &confidential, &[]).unwrap();
+        index.upsert(author, &public, &[]).unwrap();
+        let visibility = Visibility { viewer: reader, public: vec![public.id] };
+
+        // Ni compté ni classé : seul un résumé ancré au titre divulgué apparaît
+        let (total, hits) = index.search(&query("eolienne", &[]), &visibility).unwrap();
+        assert_eq!(total, 2);
+        let mut found = ids(&hits);
+        found.sort();
+        let mut expected = vec![mine.id, public.id];
+        expected.sort();
+        assert_eq!(found, expected);
+
+        let (total, hits) = index.search(&query("replie grand vent", &[]), &visibility).unwrap();
+        assert_eq!((total, hits.len()), (0, 0));
+
+        // Seul le titre d'un résumé public est cherché, pas sa solution
+        let (total, hits) = index.search(&query("verticale silencieuse", &[]), &visibility).unwrap();
+        assert_eq!(ids(&hits), vec![public.id]);
+        assert_eq!(total, 1);
+        let (total, hits) = index.search(&query("silencieuse", &[]), &visibility).unwrap();
+        assert_eq!((total, hits.len()), (0, 0));
+        let (_, hits) = index.search(&query("compacte garde-corps", &[]), &visibility).unwrap();
+        assert_eq!(ids(&hits), vec![mine.id]);
+
+        assert_eq!(ids(&index.similar(mine.id, &visibility, 5).unwrap()), vec![public.id]);
+    }
+}
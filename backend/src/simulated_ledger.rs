@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha384};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use crate::ledger::{LedgerAnchor, LedgerError, LedgerMessage, LedgerReceipt, LedgerResult, LedgerSubmission, ReceiptStatus};
+
+// Registre en mémoire, déterministe : même suite de messages => mêmes horodatages,
+// numéros de séquence et running hashes. Optionnellement persisté en JSON lines.
+
+const SIMULATED_TOPIC_ID: &str = "0.0.1001";
+const SIMULATED_PAYER_ID: &str = "0.0.2";
+
+pub struct SimulatedLedger {
+    genesis: DateTime<Utc>,
+    path: Option<PathBuf>,
+    messages: Mutex<Vec<LedgerMessage>>,
+    // Date de validité du dernier identifiant émis
+    last_valid_start: Mutex<DateTime<Utc>>,
+}
+
+impl SimulatedLedger {
+    /// `SIMULATED_LEDGER_PATH` active la persistance, `SIMULATED_LEDGER_GENESIS` (RFC 3339) fixe l'horloge de consensus.
+    pub fn from_env() -> LedgerResult<Self> {
+        let genesis = match env::var("SIMULATED_LEDGER_GENESIS") {
+            Ok(value) => DateTime::parse_from_rfc3339(&value)
+                .map_err(|e| LedgerError::Config(format!("SIMULATED_LEDGER_GENESIS: {}", e)))?
+                .with_timezone(&Utc),
+            Err(_) => DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(1_700_000_000),
+        };
+        Self::open(genesis, env::var("SIMULATED_LEDGER_PATH").ok().map(PathBuf::from))
+    }
+
+    pub fn open(genesis: DateTime<Utc>, path: Option<PathBuf>) -> LedgerResult<Self> {
+        let mut messages = Vec::new();
+        if let Some(path) = &path
+            && path.exists()
+        {
+            for line in fs::read_to_string(path)?.lines().filter(|l| !l.trim().is_empty()) {
+                messages.push(serde_json::from_str::<LedgerMessage>(line)?);
+            }
+        }
+        let last_valid_start = messages
+            .iter()
+            .filter_map(|m| valid_start(&m.transaction_id))
+            .max()
+            .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+        Ok(SimulatedLedger { genesis, path, messages: Mutex::new(messages), last_valid_start: Mutex::new(last_valid_start) })
+    }
+
+    fn persist(&self, message: &LedgerMessage) -> LedgerResult<()> {
+        if let Some(path) = &self.path {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(message)?)?;
+        }
+        Ok(())
+    }
+}
+
+// Running hash façon HCS : chaîne SHA-384 sur le hash précédent et les métadonnées du message
+fn next_running_hash(previous: &[u8], sequence: u64, timestamp: &DateTime<Utc>, contents: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha384::new();
+    hasher.update(previous);
+    hasher.update(SIMULATED_TOPIC_ID.as_bytes());
+    hasher.update(timestamp.timestamp().to_be_bytes());
+    hasher.update(timestamp.timestamp_subsec_nanos().to_be_bytes());
+    hasher.update(sequence.to_be_bytes());
+    hasher.update(Sha384::digest(contents));
+    hasher.finalize().to_vec()
+}
+
+// Date de validité d'un identifiant `payeur@secondes.nanosecondes`
+fn valid_start(transaction_id: &str) -> Option<DateTime<Utc>> {
+    let (seconds, nanos) = transaction_id.split_once('@')?.1.split_once('.')?;
+    DateTime::from_timestamp(seconds.parse().ok()?, nanos.parse().ok()?)
+}
+
+fn poisoned() -> LedgerError {
+    LedgerError::Unavailable("registre simulé corrompu".to_string())
+}
+
+#[async_trait]
+impl LedgerAnchor for SimulatedLedger {
+    fn name(&self) -> &'static str {
+        "simulated"
+    }
+
+    fn network(&self) -> &str {
+        "simulated"
+    }
+
+    // Date de validité à l'horloge, strictement croissante : les identifiants sont émis avant la soumission,
+    // un identifiant émis puis perdu par un redémarrage ne doit jamais être réattribué
+    fn new_transaction_id(&self) -> LedgerResult<String> {
+        let mut last = self.last_valid_start.lock().map_err(|_| poisoned())?;
+        let valid_start = Utc::now().max(*last + Duration::nanoseconds(1));
+        *last = valid_start;
+        Ok(format!(
+            "{}@{}.{:09}",
+            SIMULATED_PAYER_ID,
+            valid_start.timestamp(),
+            valid_start.timestamp_subsec_nanos()
+        ))
+    }
+
+    async fn submit_message(&self, transaction_id: &str, message: Vec<u8>) -> LedgerResult<LedgerSubmission> {
+        let record = {
+            let mut messages = self.messages.lock().map_err(|_| poisoned())?;
+            // Même identifiant => même transaction : le registre ne l'enregistre qu'une fois
+            if let Some(existing) = messages.iter().find(|m| m.transaction_id == transaction_id) {
+                return Ok(LedgerSubmission {
+                    transaction_id: existing.transaction_id.clone(),
+                    topic_sequence_number: existing.sequence_number,
+                });
+            }
+            let sequence = messages.len() as u64 + 1;
+            let consensus_timestamp = self.genesis + Duration::seconds(sequence as i64);
+            let previous = match messages.last() {
+                Some(last) => hex::decode(&last.running_hash)?,
+                None => vec![0u8; 48],
+            };
+            let record = LedgerMessage {
+                transaction_id: transaction_id.to_string(),
+                topic_id: SIMULATED_TOPIC_ID.to_string(),
+                consensus_timestamp,
+                sequence_number: sequence,
+                running_hash: hex::encode(next_running_hash(&previous, sequence, &consensus_timestamp, &message)),
+                contents: message,
+            };
+            messages.push(record.clone());
+            record
+        };
+        self.persist(&record)?;
+
+        Ok(LedgerSubmission {
+            transaction_id: record.transaction_id,
+            topic_sequence_number: record.sequence_number,
+        })
+    }
+
+    async fn fetch_receipt(&self, transaction_id: &str) -> LedgerResult<LedgerReceipt> {
+        let message = self.fetch_message(transaction_id).await?;
+        Ok(match message {
+            Some(m) => LedgerReceipt {
+                transaction_id: m.transaction_id,
+                status: ReceiptStatus::Success,
+                topic_sequence_number: m.sequence_number,
+            },
+            None => LedgerReceipt {
+                transaction_id: transaction_id.to_string(),
+                status: ReceiptStatus::NotFound,
+                topic_sequence_number: 0,
+            },
+        })
+    }
+
+    async fn fetch_message(&self, transaction_id: &str) -> LedgerResult<Option<LedgerMessage>> {
+        let messages = self.messages.lock().map_err(|_| poisoned())?;
+        Ok(messages.iter().find(|m| m.transaction_id == transaction_id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genesis() -> DateTime<Utc> {
+        DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(1_700_000_000)
+    }
+
+    #[tokio::test]
+    async fn submits_dedupes_and_reads_back() {
+        let ledger = SimulatedLedger::open(genesis(), None).unwrap();
+        let first = ledger.new_transaction_id().unwrap();
+        let second = ledger.new_transaction_id().unwrap();
+        assert_ne!(first, second);
+
+        let submission = ledger.submit_message(&first, b"empreinte 1".to_vec()).await.unwrap();
+        assert_eq!(submission.topic_sequence_number, 1);
+        // Même identifiant : la transaction n'est pas enregistrée une seconde fois
+        let again = ledger.submit_message(&first, b"autre contenu".to_vec()).await.unwrap();
+        assert_eq!(again.topic_sequence_number, 1);
+        assert_eq!(ledger.submit_message(&second, b"empreinte 2".to_vec()).await.unwrap().topic_sequence_number, 2);
+
+        let message = ledger.fetch_message(&first).await.unwrap().unwrap();
+        assert_eq!(message.contents, b"empreinte 1");
+        assert_eq!(message.consensus_timestamp, genesis() + Duration::seconds(1));
+        assert_eq!(ledger.fetch_receipt(&second).await.unwrap().status, ReceiptStatus::Success);
+        assert_eq!(ledger.fetch_receipt("0.0.2@1.000000000").await.unwrap().status, ReceiptStatus::NotFound);
+    }
+
+    #[test]
+    fn transaction_ids_are_never_reissued() {
+        let ledger = SimulatedLedger::open(genesis(), None).unwrap();
+        let ids: Vec<_> = (0..100).map(|_| ledger.new_transaction_id().unwrap()).collect();
+        assert!(ids.windows(2).all(|pair| valid_start(&pair[0]) < valid_start(&pair[1])));
+
+        // Un registre rouvert n'a pas vu les identifiants émis sans être soumis
+        let reopened = SimulatedLedger::open(genesis(), None).unwrap();
+        assert!(valid_start(&reopened.new_transaction_id().unwrap()) > valid_start(ids.last().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn reopening_restores_the_same_topic() {
+        let path = env::temp_dir().join(format!("simulated-ledger-{}.jsonl", uuid::Uuid::new_v4()));
+        let (first, second) = {
+            let ledger = SimulatedLedger::open(genesis(), Some(path.clone())).unwrap();
+            let first = ledger.new_transaction_id().unwrap();
+            let second = ledger.new_transaction_id().unwrap();
+            ledger.submit_message(&first, b"empreinte 1".to_vec()).await.unwrap();
+            ledger.submit_message(&second, b"empreinte 2".to_vec()).await.unwrap();
+            (ledger.fetch_message(&first).await.unwrap().unwrap(), ledger.fetch_message(&second).await.unwrap().unwrap())
+        };
+
+        let reopened = SimulatedLedger::open(genesis(), Some(path.clone())).unwrap();
+        let restored = reopened.fetch_message(&second.transaction_id).await.unwrap().unwrap();
+        assert_eq!(restored.running_hash, second.running_hash);
+        assert_eq!(restored.sequence_number, 2);
+
+        // Le chaînage reprend après le dernier message persisté
+        let third = reopened.new_transaction_id().unwrap();
+        assert!(third != first.transaction_id && third != second.transaction_id);
+        assert_eq!(reopened.submit_message(&third, b"empreinte 3".to_vec()).await.unwrap().topic_sequence_number, 3);
+        fs::remove_file(&path).unwrap();
+    }
+}
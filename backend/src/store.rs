@@ -0,0 +1,435 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use crate::encryption::{self, CryptoResult, DataKey, Vault};
+use crate::models::{AiEvaluation, AiResponse, Claim, Idea, Summary};
+
+// Accès aux idées, aux résumés et aux évaluations de l'IA : chiffrement à l'écriture, déchiffrement à la lecture.
+// Les routes et le worker d'ancrage ne manipulent que du texte clair.
+
+// Chaque valeur chiffrée est liée à sa table, sa colonne et sa ligne
+fn field_aad(table: &str, column: &str, id: Uuid) -> String {
+    format!("{}.{}:{}", table, column, id)
+}
+
+// Texte d'une revendication, lié à son résumé et à son numéro
+fn claim_aad(summary_id: Uuid, number: i32) -> String {
+    format!("claims.text:{}:{}", summary_id, number)
+}
+
+fn seal_summary(key: &DataKey, summary: &Summary) -> CryptoResult<[String; 5]> {
+    let id = summary.id;
+    Ok([
+        key.seal(&field_aad("summaries", "title", id), &summary.title)?,
+        key.seal(&field_aad("summaries", "problem", id), &summary.problem)?,
+        key.seal(&field_aad("summaries", "solution", id), &summary.solution)?,
+        key.seal(&field_aad("summaries", "claim", id), &summary.claim)?,
+        key.seal(&field_aad("summaries", "cpc_code", id), &summary.cpc_code)?,
+    ])
+}
+
+fn open_summary(key: &DataKey, stored: Summary) -> CryptoResult<Summary> {
+    let id = stored.id;
+    Ok(Summary {
+        title: key.open(&field_aad("summaries", "title", id), &stored.title)?,
+        problem: key.open(&field_aad("summaries", "problem", id), &stored.problem)?,
+        solution: key.open(&field_aad("summaries", "solution", id), &stored.solution)?,
+        claim: key.open(&field_aad("summaries", "claim", id), &stored.claim)?,
+        cpc_code: key.open(&field_aad("summaries", "cpc_code", id), &stored.cpc_code)?,
+        ..stored
+    })
+}
+
+pub async fn insert_idea(
+    pool: &PgPool,
+    vault: &Vault,
+    idea_id: Uuid,
+    user_id: Uuid,
+    raw_idea: &str,
+    created_at: DateTime<Utc>,
+) -> CryptoResult<()> {
+    let key = vault.data_key(pool, user_id).await?;
+    sqlx::query!(
+        "INSERT INTO ideas (id, user_id, raw_idea, created_at) VALUES ($1, $2, $3, $4)",
+        idea_id,
+        user_id,
+        key.seal(&field_aad("ideas", "raw_idea", idea_id), raw_idea)?,
+        created_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn fetch_idea(pool: &PgPool, vault: &Vault, idea_id: Uuid) -> CryptoResult<Option<Idea>> {
+    let Some(idea) = sqlx::query_as!(Idea, "SELECT * FROM ideas WHERE id = $1", idea_id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let key = vault.data_key(pool, idea.user_id).await?;
+    Ok(Some(Idea {
+        raw_idea: key.open(&field_aad("ideas", "raw_idea", idea.id), &idea.raw_idea)?,
+        ..idea
+    }))
+}
+
+/// Enregistre une révision de résumé en clair et son jeu de revendications ; ils sont chiffrés
+/// avec la clé de l'auteur de l'idée.
+pub async fn insert_summary(
+    pool: &PgPool,
+    vault: &Vault,
+    user_id: Uuid,
+    summary: &Summary,
+    claims: &[Claim],
+) -> CryptoResult<()> {
+    let key = vault.data_key(pool, user_id).await?;
+    let mut tx = pool.begin().await?;
+    insert_summary_in(&mut tx, &key, summary, claims).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Enregistre un résumé et ses revendications dans la transaction de l'appelant, avec la clé de l'auteur.
+pub async fn insert_summary_in(conn: &mut PgConnection, key: &DataKey, summary: &Summary, claims: &[Claim]) -> CryptoResult<()> {
+    let [title, problem, solution, claim, cpc_code] = seal_summary(key, summary)?;
+    sqlx::query!(
+        r#"INSERT INTO summaries (id, idea_id, title, problem, solution, claim, cpc_code, created_at,
+                                  original_id, revision, author_id)
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+        summary.id,
+        summary.idea_id,
+        title,
+        problem,
+        solution,
+        claim,
+        cpc_code,
+        summary.created_at,
+        summary.original_id,
+        summary.revision,
+        summary.author_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    // Dans l'ordre des numéros : la clé étrangère exige que la revendication parente existe déjà
+    for claim in claims {
+        sqlx::query!(
+            "INSERT INTO claims (summary_id, number, depends_on, text) VALUES ($1, $2, $3, $4)",
+            summary.id,
+            claim.number,
+            claim.depends_on,
+            key.seal(&claim_aad(summary.id, claim.number), &claim.text)?
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+// Résumé encore chiffré et clé de données de son auteur
+async fn fetch_sealed_summary(pool: &PgPool, vault: &Vault, summary_id: Uuid) -> CryptoResult<Option<(Summary, DataKey)>> {
+    let Some(summary) = sqlx::query_as!(Summary, "SELECT * FROM summaries WHERE id = $1", summary_id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let user_id = sqlx::query_scalar!("SELECT user_id FROM ideas WHERE id = $1", summary.idea_id)
+        .fetch_one(pool)
+        .await?;
+    let key = vault.data_key(pool, user_id).await?;
+    Ok(Some((summary, key)))
+}
+
+pub async fn fetch_summary(pool: &PgPool, vault: &Vault, summary_id: Uuid) -> CryptoResult<Option<Summary>> {
+    let Some((summary, key)) = fetch_sealed_summary(pool, vault, summary_id).await? else {
+        return Ok(None);
+    };
+    Ok(Some(open_summary(&key, summary)?))
+}
+
+/// Résumé et revendications dans l'ordre des numéros ; liste vide pour un résumé antérieur aux
+/// jeux de revendications.
+pub async fn fetch_summary_with_claims(
+    pool: &PgPool,
+    vault: &Vault,
+    summary_id: Uuid,
+) -> CryptoResult<Option<(Summary, Vec<Claim>)>> {
+    let Some((summary, key)) = fetch_sealed_summary(pool, vault, summary_id).await? else {
+        return Ok(None);
+    };
+    let claims = sqlx::query_as!(
+        Claim,
+        "SELECT number, depends_on, text FROM claims WHERE summary_id = $1 ORDER BY number",
+        summary_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|claim| {
+        Ok(Claim {
+            text: key.open(&claim_aad(summary_id, claim.number), &claim.text)?,
+            ..claim
+        })
+    })
+    .collect::<CryptoResult<Vec<_>>>()?;
+    Ok(Some((open_summary(&key, summary)?, claims)))
+}
+
+/// Enregistre une évaluation de l'IA dans la transaction de l'appelant ; la réponse du service est chiffrée
+/// avec la clé de l'auteur de l'idée.
+pub async fn insert_ai_evaluation(
+    conn: &mut PgConnection,
+    key: &DataKey,
+    evaluation: &AiEvaluation,
+    response: &AiResponse,
+) -> CryptoResult<()> {
+    let response = key.seal(&field_aad("ai_evaluations", "response", evaluation.id), &serde_json::to_string(response)?)?;
+    sqlx::query!(
+        r#"INSERT INTO ai_evaluations (id, idea_id, job_id, summary_id, provider, model, novelty_score, novelty_threshold,
+                                     latency_ms, accepted, rejection_reason, rejection_message, response, created_at)
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)"#,
+        evaluation.id,
+        evaluation.idea_id,
+        evaluation.job_id,
+        evaluation.summary_id,
+        evaluation.provider,
+        evaluation.model,
+        evaluation.novelty_score,
+        evaluation.novelty_threshold,
+        evaluation.latency_ms,
+        evaluation.accepted,
+        evaluation.rejection_reason,
+        evaluation.rejection_message,
+        response,
+        evaluation.created_at
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Évaluations d'une idée, de la plus récente à la plus ancienne, avec la réponse déchiffrée.
+pub async fn fetch_ai_evaluations(
+    pool: &PgPool,
+    vault: &Vault,
+    idea_id: Uuid,
+) -> CryptoResult<Vec<(AiEvaluation, AiResponse)>> {
+    let evaluations = sqlx::query_as!(
+        AiEvaluation,
+        "SELECT * FROM ai_evaluations WHERE idea_id = $1 ORDER BY created_at DESC",
+        idea_id
+    )
+    .fetch_all(pool)
+    .await?;
+    let Some(user_id) = idea_owner(pool, idea_id).await? else {
+        return Ok(Vec::new());
+    };
+    let key = vault.data_key(pool, user_id).await?;
+    evaluations
+        .into_iter()
+        .map(|evaluation| {
+            let response = key.open(&field_aad("ai_evaluations", "response", evaluation.id), &evaluation.response)?;
+            Ok((evaluation, serde_json::from_str(&response)?))
+        })
+        .collect()
+}
+
+/// Auteur d'une idée, `None` si elle n'existe pas.
+pub async fn idea_owner(pool: &PgPool, idea_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT user_id FROM ideas WHERE id = $1", idea_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Idée et auteur d'une évaluation de l'IA, `None` si elle n'existe pas.
+pub async fn evaluation_owner(pool: &PgPool, evaluation_id: Uuid) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+    Ok(sqlx::query!(
+        "SELECT e.idea_id, i.user_id FROM ai_evaluations e JOIN ideas i ON i.id = e.idea_id WHERE e.id = $1",
+        evaluation_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|row| (row.idea_id, row.user_id)))
+}
+
+/// Auteur de l'idée d'un résumé, `None` si le résumé n'existe pas.
+pub async fn summary_owner(pool: &PgPool, summary_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT i.user_id FROM summaries s JOIN ideas i ON i.id = s.idea_id WHERE s.id = $1",
+        summary_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Chiffre les idées et résumés enregistrés en clair avant l'activation du chiffrement.
+pub async fn encrypt_legacy_rows(pool: &PgPool, vault: &Vault) -> CryptoResult<(u64, u64)> {
+    let mut ideas = 0;
+    for idea in sqlx::query_as!(Idea, "SELECT * FROM ideas WHERE raw_idea NOT LIKE 'enc:v1:%'")
+        .fetch_all(pool)
+        .await?
+    {
+        let key = vault.data_key(pool, idea.user_id).await?;
+        // La condition sur l'ancienne valeur évite d'écraser une écriture concurrente
+        ideas += sqlx::query!(
+            "UPDATE ideas SET raw_idea = $2 WHERE id = $1 AND raw_idea = $3",
+            idea.id,
+            key.seal(&field_aad("ideas", "raw_idea", idea.id), &idea.raw_idea)?,
+            idea.raw_idea
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    let mut summaries = 0;
+    for summary in sqlx::query_as!(
+        Summary,
+        "SELECT * FROM summaries
+         WHERE title NOT LIKE 'enc:v1:%' OR problem NOT LIKE 'enc:v1:%' OR solution NOT LIKE 'enc:v1:%'
+            OR claim NOT LIKE 'enc:v1:%' OR cpc_code NOT LIKE 'enc:v1:%'"
+    )
+    .fetch_all(pool)
+    .await?
+    {
+        let user_id = sqlx::query_scalar!("SELECT user_id FROM ideas WHERE id = $1", summary.idea_id)
+            .fetch_one(pool)
+            .await?;
+        let key = vault.data_key(pool, user_id).await?;
+        let id = summary.id;
+        let seal_if_plain = |column: &str, value: &str| -> CryptoResult<String> {
+            if encryption::is_sealed(value) {
+                Ok(value.to_string())
+            } else {
+                key.seal(&field_aad("summaries", column, id), value)
+            }
+        };
+        summaries += sqlx::query!(
+            "UPDATE summaries SET title = $2, problem = $3, solution = $4, claim = $5, cpc_code = $6
+             WHERE id = $1 AND title = $7 AND problem = $8 AND solution = $9 AND claim = $10 AND cpc_code = $11",
+            id,
+            seal_if_plain("title", &summary.title)?,
+            seal_if_plain("problem", &summary.problem)?,
+            seal_if_plain("solution", &summary.solution)?,
+            seal_if_plain("claim", &summary.claim)?,
+            seal_if_plain("cpc_code", &summary.cpc_code)?,
+            summary.title,
+            summary.problem,
+            summary.solution,
+            summary.claim,
+            summary.cpc_code
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok((ideas, summaries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    async fn setup() -> (PgPool, Vault) {
+        dotenvy::dotenv().ok();
+        let pool = PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL")).await.unwrap();
+        let vault = Vault::from_env(&pool).await.unwrap();
+        (pool, vault)
+    }
+
+    // Idée chiffrée d'un nouvel utilisateur et son résumé, avec deux revendications
+    async fn stored_summary(pool: &PgPool, vault: &Vault) -> (Summary, Vec<Claim>) {
+        let (user_id, idea_id, summary_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query!(
+            "INSERT INTO users (id, full_name, email, wallet_address) VALUES ($1, 'Test chiffrement', $2, $3)",
+            user_id,
+            format!("store-{}@test.invalid", user_id),
+            format!("0.0.{}", user_id.as_u128() % 1_000_000_000_000)
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        insert_idea(pool, vault, idea_id, user_id, "Idée confidentielle", Utc::now()).await.unwrap();
+        let summary = Summary {
+            id: summary_id,
+            idea_id,
+            title: "Titre secret".to_string(),
+            problem: "Problème".to_string(),
+            solution: "Solution".to_string(),
+            claim: "Revendication principale".to_string(),
+            cpc_code: "G06F".to_string(),
+            created_at: Utc::now(),
+            original_id: summary_id,
+            revision: 1,
+            author_id: user_id,
+        };
+        let claims = vec![
+            Claim { number: 1, depends_on: None, text: "Revendication principale".to_string() },
+            Claim { number: 2, depends_on: Some(1), text: "Revendication dépendante".to_string() },
+        ];
+        insert_summary(pool, vault, user_id, &summary, &claims).await.unwrap();
+        (summary, claims)
+    }
+
+    #[tokio::test]
+    async fn ideas_summaries_and_claims_are_sealed_at_rest() {
+        let (pool, vault) = setup().await;
+        let (summary, claims) = stored_summary(&pool, &vault).await;
+
+        let stored = sqlx::query!("SELECT title, solution, cpc_code FROM summaries WHERE id = $1", summary.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!([&stored.title, &stored.solution, &stored.cpc_code].iter().all(|v| encryption::is_sealed(v)));
+        assert!(!stored.title.contains("secret"));
+        let texts = sqlx::query_scalar!("SELECT text FROM claims WHERE summary_id = $1", summary.id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(texts.iter().all(|t| encryption::is_sealed(t)));
+
+        let (opened, opened_claims) = fetch_summary_with_claims(&pool, &vault, summary.id).await.unwrap().unwrap();
+        assert_eq!((opened.title.as_str(), opened.cpc_code.as_str()), ("Titre secret", "G06F"));
+        assert_eq!(opened_claims, claims);
+        let idea = fetch_idea(&pool, &vault, summary.idea_id).await.unwrap().unwrap();
+        assert_eq!(idea.raw_idea, "Idée confidentielle");
+    }
+
+    #[tokio::test]
+    async fn sealed_values_swapped_between_columns_or_claims_do_not_open() {
+        let (pool, vault) = setup().await;
+        let (summary, _) = stored_summary(&pool, &vault).await;
+        sqlx::query!("UPDATE summaries SET problem = title WHERE id = $1", summary.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(fetch_summary(&pool, &vault, summary.id).await.is_err());
+
+        let (summary, _) = stored_summary(&pool, &vault).await;
+        sqlx::query!(
+            "UPDATE claims SET text = (SELECT text FROM claims WHERE summary_id = $1 AND number = 1)
+             WHERE summary_id = $1 AND number = 2",
+            summary.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(fetch_summary(&pool, &vault, summary.id).await.is_ok());
+        assert!(fetch_summary_with_claims(&pool, &vault, summary.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn legacy_plaintext_rows_are_still_readable() {
+        let (pool, vault) = setup().await;
+        let (summary, _) = stored_summary(&pool, &vault).await;
+        sqlx::query!("UPDATE summaries SET title = 'Titre en clair' WHERE id = $1", summary.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(fetch_summary(&pool, &vault, summary.id).await.unwrap().unwrap().title, "Titre en clair");
+    }
+}
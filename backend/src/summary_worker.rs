@@ -0,0 +1,560 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, broadcast};
+use uuid::Uuid;
+use crate::ai_client::{AiPartial, AiProvider};
+use crate::claims;
+use crate::encryption::Vault;
+use crate::evaluations;
+use crate::error::{AppError, AppResult};
+use crate::i18n::{Locale, Message};
+use crate::models::{AiEvaluation, Summary, SummaryJob};
+use crate::search::SearchIndex;
+use crate::store;
+
+// Pool de workers de génération des résumés : la route n'écrit qu'une tâche `summary_jobs`,
+// l'appel au service IA et l'insertion du résumé se font ici, avec reprise après redémarrage.
+
+// Bail d'une tâche en cours, supérieur au délai maximal d'une génération.
+// Une tâche dont le bail expire (processus arrêté) est reprise par le premier worker libre.
+const LEASE_SECS: i64 = 300;
+const JOB_TIMEOUT: Duration = Duration::from_secs(240);
+// Une tâche interrompue autant de fois est abandonnée
+const MAX_ATTEMPTS: i32 = 3;
+// Événements en attente par abonné ; un abonné trop lent relit l'état en base
+const EVENT_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+struct WorkerConfig {
+    workers: usize,
+    poll_interval: Duration,
+}
+
+impl WorkerConfig {
+    // SUMMARY_WORKERS (4), SUMMARY_WORKER_POLL_MS (1000)
+    fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        WorkerConfig {
+            workers: var("SUMMARY_WORKERS", 4usize).max(1),
+            poll_interval: Duration::from_millis(var("SUMMARY_WORKER_POLL_MS", 1000u64).max(100)),
+        }
+    }
+}
+
+/// Étape d'une génération, diffusée aux clients qui suivent la tâche.
+#[derive(Debug, Clone)]
+pub enum Progress {
+    Queued,
+    CallingAi,
+    Partial(AiPartial),
+    Validating,
+    Persisted { summary_id: Uuid },
+    Failed { code: String, message: Message },
+}
+
+impl Progress {
+    /// Nom de l'événement SSE.
+    pub fn event(&self) -> &'static str {
+        match self {
+            Progress::Queued => "queued",
+            Progress::CallingAi => "calling_ai",
+            Progress::Partial(_) => "partial",
+            Progress::Validating => "validating",
+            Progress::Persisted { .. } => "persisted",
+            Progress::Failed { .. } => "failed",
+        }
+    }
+
+    /// Étape atteinte d'après l'état enregistré de la tâche (reprise d'un suivi, tâche déjà terminée).
+    pub fn of_job(job: &SummaryJob) -> Progress {
+        match (job.status.as_str(), job.summary_id) {
+            ("succeeded", Some(summary_id)) => Progress::Persisted { summary_id },
+            ("failed", _) => Progress::Failed {
+                code: job.error_code.clone().unwrap_or_else(|| "internal_error".to_string()),
+                message: job.error_message.as_ref().and_then(Message::from_json).unwrap_or_else(|| Message::new("error.server")),
+            },
+            ("running", _) => Progress::CallingAi,
+            _ => Progress::Queued,
+        }
+    }
+
+    /// Dernière étape d'une génération
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Progress::Persisted { .. } | Progress::Failed { .. })
+    }
+
+    /// Données de l'événement, messages rendus dans `locale`.
+    pub fn data(&self, job_id: Uuid, locale: Locale) -> Value {
+        match self {
+            Progress::Partial(partial) => json!({ "job_id": job_id, "field": partial.field, "delta": partial.delta }),
+            Progress::Persisted { summary_id } => json!({ "job_id": job_id, "summary_id": summary_id }),
+            Progress::Failed { code, message } => json!({ "job_id": job_id, "code": code, "message": message.render(locale) }),
+            _ => json!({ "job_id": job_id }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobEvent {
+    pub job_id: Uuid,
+    pub progress: Progress,
+}
+
+/// File des générations de résumé, partagée par les routes et les workers.
+pub struct SummaryQueue {
+    wake: Notify,
+    events: broadcast::Sender<JobEvent>,
+}
+
+impl SummaryQueue {
+    fn new() -> Self {
+        SummaryQueue { wake: Notify::new(), events: broadcast::channel(EVENT_CAPACITY).0 }
+    }
+
+    /// Abonnement aux étapes de toutes les générations (à filtrer par tâche).
+    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
+        self.events.subscribe()
+    }
+
+    // Sans abonné, l'événement est simplement perdu
+    fn publish(&self, job_id: Uuid, progress: Progress) {
+        let _ = self.events.send(JobEvent { job_id, progress });
+    }
+
+    /// Met en file la génération d'un résumé. Une génération déjà en cours pour l'idée est renvoyée telle quelle.
+    pub async fn enqueue(&self, pool: &PgPool, idea_id: Uuid, user_id: Uuid) -> Result<SummaryJob, sqlx::Error> {
+        let job = sqlx::query_as!(
+            SummaryJob,
+            "INSERT INTO summary_jobs (id, idea_id, user_id) VALUES ($1, $2, $3)
+             ON CONFLICT (idea_id) WHERE status IN ('queued', 'running')
+             DO UPDATE SET updated_at = summary_jobs.updated_at
+             RETURNING *",
+            Uuid::new_v4(),
+            idea_id,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+        self.wake.notify_one();
+        Ok(job)
+    }
+}
+
+/// Dernière génération demandée pour une idée.
+pub async fn latest_job(pool: &PgPool, idea_id: Uuid) -> Result<Option<SummaryJob>, sqlx::Error> {
+    sqlx::query_as!(
+        SummaryJob,
+        "SELECT * FROM summary_jobs WHERE idea_id = $1 ORDER BY created_at DESC LIMIT 1",
+        idea_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Démarre les workers de génération en tâche de fond.
+pub fn spawn_from_env(pool: PgPool, vault: Arc<Vault>, ai: Arc<dyn AiProvider>, index: Arc<SearchIndex>) -> Arc<SummaryQueue> {
+    let config = WorkerConfig::from_env();
+    let queue = Arc::new(SummaryQueue::new());
+    for _ in 0..config.workers {
+        let (pool, vault, ai, index) = (pool.clone(), vault.clone(), ai.clone(), index.clone());
+        let (queue, config) = (queue.clone(), config.clone());
+        tokio::spawn(async move {
+            loop {
+                match claim_next(&pool).await {
+                    Ok(Some(job)) => run_job(&pool, &vault, ai.as_ref(), &index, &queue, job).await,
+                    // File vide : réveil à la prochaine mise en file, ou au passage suivant pour les baux expirés
+                    Ok(None) => {
+                        tokio::select! {
+                            _ = queue.wake.notified() => {}
+                            _ = tokio::time::sleep(config.poll_interval) => {}
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Erreur worker de génération: {}", e);
+                        tokio::time::sleep(config.poll_interval).await;
+                    }
+                }
+            }
+        });
+    }
+    queue
+}
+
+// Réclame la plus ancienne tâche en file ou interrompue ; SKIP LOCKED permet plusieurs workers et instances
+async fn claim_next(pool: &PgPool) -> Result<Option<SummaryJob>, sqlx::Error> {
+    let now = Utc::now();
+    sqlx::query_as!(
+        SummaryJob,
+        "UPDATE summary_jobs
+         SET status = 'running', attempts = attempts + 1, locked_until = $1, updated_at = $2
+         WHERE id = (
+             SELECT id FROM summary_jobs
+             WHERE status = 'queued' OR (status = 'running' AND locked_until <= $2)
+             ORDER BY created_at
+             LIMIT 1
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING *",
+        now + chrono::Duration::seconds(LEASE_SECS),
+        now
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn run_job(pool: &PgPool, vault: &Vault, ai: &dyn AiProvider, index: &SearchIndex, queue: &SummaryQueue, job: SummaryJob) {
+    let outcome = if job.attempts > MAX_ATTEMPTS {
+        Err(AppError::internal("error.summary_job_interrupted")(format!("{} tentatives interrompues", MAX_ATTEMPTS)))
+    } else {
+        match tokio::time::timeout(JOB_TIMEOUT, generate(pool, vault, ai, index, queue, &job)).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(AppError::internal("error.summary_job_timeout")("délai de génération dépassé")),
+        }
+    };
+    // Une génération menée à terme a enregistré son issue avec le résumé et l'évaluation
+    let recorded = match outcome {
+        Ok(recorded) => Ok(recorded),
+        Err(e) => record_outcome(pool, &job, Err(e)).await,
+    };
+    match recorded {
+        Ok(Some(progress)) => queue.publish(job.id, progress),
+        Ok(None) => eprintln!("Tâche de génération {} reprise par un autre worker, issue ignorée", job.id),
+        Err(e) => eprintln!("Erreur mise à jour tâche de génération {}: {}", job.id, e),
+    }
+}
+
+async fn generate(
+    pool: &PgPool,
+    vault: &Vault,
+    ai: &dyn AiProvider,
+    index: &SearchIndex,
+    queue: &SummaryQueue,
+    job: &SummaryJob,
+) -> AppResult<Option<Progress>> {
+    let idea = store::fetch_idea(pool, vault, job.idea_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("error.idea_not_found".into()))?;
+
+    let threshold = evaluations::novelty_threshold(pool, idea.user_id, idea.id).await?;
+
+    queue.publish(job.id, Progress::CallingAi);
+    let started = Instant::now();
+    let ai_response = ai
+        .structure_streaming(&idea.raw_idea, &|partial| queue.publish(job.id, Progress::Partial(partial)))
+        .await?;
+    let latency = started.elapsed();
+    queue.publish(job.id, Progress::Validating);
+
+    // Le verdict est enregistré avec la réponse, que l'idée soit retenue ou non
+    let verdict = if i16::from(ai_response.novelty_score) < threshold {
+        Err((
+            evaluations::NOVELTY_BELOW_THRESHOLD,
+            AppError::Unprocessable(
+                Message::new("error.not_patentable")
+                    .with("score", ai_response.novelty_score)
+                    .with("threshold", threshold),
+            ),
+        ))
+    } else {
+        claims::from_ai(ai_response.claims.clone(), &ai_response.claim).map_err(|e| {
+            (
+                evaluations::INVALID_CLAIMS,
+                AppError::AiInvalidResponse(Message::new("error.invalid_ai_claims").with_message("detail", e)),
+            )
+        })
+    };
+
+    let id = Uuid::new_v4();
+    let evaluation = AiEvaluation {
+        id: Uuid::new_v4(),
+        idea_id: idea.id,
+        job_id: Some(job.id),
+        summary_id: verdict.is_ok().then_some(id),
+        provider: ai.name().to_string(),
+        model: ai_response.model.clone().unwrap_or_else(|| ai.name().to_string()),
+        novelty_score: ai_response.novelty_score.into(),
+        novelty_threshold: threshold,
+        latency_ms: i32::try_from(latency.as_millis()).unwrap_or(i32::MAX),
+        accepted: verdict.is_ok(),
+        rejection_reason: verdict.as_ref().err().map(|(reason, _)| reason.to_string()),
+        rejection_message: verdict.as_ref().err().map(|(_, e)| e.message().to_json()),
+        response: String::new(), // Chiffrée à l'enregistrement
+        review_status: None,
+        review_requested_at: None,
+        created_at: Utc::now(),
+        reviewed_at: None,
+    };
+
+    // Résumé, évaluation et issue de la tâche dans une seule transaction : une tâche relancée après
+    // expiration du bail ne trouve rien de la tentative interrompue, et une tentative qui a perdu son
+    // bail n'enregistre rien
+    let key = vault.data_key(pool, idea.user_id).await.map_err(AppError::internal("error.summary_store_failed"))?;
+    let mut tx = pool.begin().await?;
+    let (accepted, outcome) = match verdict {
+        Ok(claims) => {
+            let summary = Summary {
+                id,
+                idea_id: idea.id,
+                title: ai_response.title.clone(),
+                problem: ai_response.problem.clone(),
+                solution: ai_response.solution.clone(),
+                claim: claims[0].text.clone(),
+                cpc_code: ai_response.cpc_code.clone(),
+                created_at: Utc::now(),
+                original_id: id,
+                revision: 1,
+                author_id: job.user_id,
+            };
+            store::insert_summary_in(&mut tx, &key, &summary, &claims)
+                .await
+                .map_err(AppError::internal("error.summary_store_failed"))?;
+            (Some((summary, claims)), Ok(id))
+        }
+        Err((_, e)) => (None, Err(e)),
+    };
+    store::insert_ai_evaluation(&mut tx, &key, &evaluation, &ai_response)
+        .await
+        .map_err(AppError::internal("error.evaluation_store_failed"))?;
+    let Some(progress) = record_outcome(&mut *tx, job, outcome).await? else {
+        return Ok(None);
+    };
+    tx.commit().await?;
+
+    // Le résumé est enregistré : un échec d'indexation ne fait pas échouer la génération
+    if let Some((summary, claims)) = accepted
+        && let Err(e) = index.upsert(idea.user_id, &summary, &claims)
+    {
+        eprintln!("Erreur indexation du résumé {}: {}", id, e);
+    }
+    Ok(Some(progress))
+}
+
+// Enregistre l'issue de la tâche et renvoie l'étape finale à diffuser ; rien si la tentative a perdu son
+// bail (tâche reprise par un autre worker après expiration)
+async fn record_outcome(
+    conn: impl sqlx::PgExecutor<'_>,
+    job: &SummaryJob,
+    outcome: AppResult<Uuid>,
+) -> Result<Option<Progress>, sqlx::Error> {
+    match outcome {
+        Ok(summary_id) => {
+            println!("🤖 Résumé {} généré pour l'idée {}", summary_id, job.idea_id);
+            let updated = sqlx::query!(
+                "UPDATE summary_jobs
+                 SET status = 'succeeded', summary_id = $2, error_code = NULL, error_message = NULL, updated_at = $3
+                 WHERE id = $1 AND status = 'running' AND attempts = $4",
+                job.id,
+                summary_id,
+                Utc::now(),
+                job.attempts
+            )
+            .execute(conn)
+            .await?;
+            Ok((updated.rows_affected() > 0).then_some(Progress::Persisted { summary_id }))
+        }
+        Err(e) => {
+            match std::error::Error::source(&e) {
+                Some(source) => eprintln!("Échec génération pour l'idée {} ({}): {}: {}", job.idea_id, e.code(), e, source),
+                None => eprintln!("Échec génération pour l'idée {} ({}): {}", job.idea_id, e.code(), e),
+            }
+            let updated = sqlx::query!(
+                "UPDATE summary_jobs
+                 SET status = 'failed', error_code = $2, error_message = $3, updated_at = $4
+                 WHERE id = $1 AND status = 'running' AND attempts = $5",
+                job.id,
+                e.code(),
+                e.message().to_json(),
+                Utc::now(),
+                job.attempts
+            )
+            .execute(conn)
+            .await?;
+            Ok((updated.rows_affected() > 0).then(|| Progress::Failed { code: e.code().to_string(), message: e.message() }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_client::MockAiProvider;
+    use crate::search::{self, SearchQuery};
+
+    // Base de `DATABASE_URL` (migrations appliquées), clé maître de `MASTER_KEY_FILE`
+    async fn setup() -> (PgPool, Vault) {
+        dotenvy::dotenv().ok();
+        let pool = PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL")).await.unwrap();
+        let vault = Vault::from_env(&pool).await.unwrap();
+        (pool, vault)
+    }
+
+    // Idée d'un nouvel utilisateur et sa tâche, déjà réclamée : un serveur lancé sur la même base ne la prend pas
+    async fn running_job(pool: &PgPool, vault: &Vault, raw_idea: &str) -> SummaryJob {
+        let (user_id, idea_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query!(
+            "INSERT INTO users (id, full_name, email, wallet_address) VALUES ($1, 'Test worker', $2, $3)",
+            user_id,
+            format!("worker-{}@test.invalid", user_id),
+            format!("0.0.{}", user_id.as_u128() % 1_000_000_000_000)
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        store::insert_idea(pool, vault, idea_id, user_id, raw_idea, Utc::now()).await.unwrap();
+        sqlx::query_as!(
+            SummaryJob,
+            "INSERT INTO summary_jobs (id, idea_id, user_id, status, attempts, locked_until)
+             VALUES ($1, $2, $3, 'running', 1, $4)
+             RETURNING *",
+            Uuid::new_v4(),
+            idea_id,
+            user_id,
+            Utc::now() + chrono::Duration::seconds(LEASE_SECS)
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn reload(pool: &PgPool, job: &SummaryJob) -> SummaryJob {
+        sqlx::query_as!(SummaryJob, "SELECT * FROM summary_jobs WHERE id = $1", job.id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn generates_and_persists_a_summary_with_the_mock() {
+        let (pool, vault) = setup().await;
+        let (queue, index) = (SummaryQueue::new(), SearchIndex::new().unwrap());
+        let job = running_job(&pool, &vault, "un hamac chauffant. Il se règle par logiciel. Il se plie").await;
+        let mut events = queue.subscribe();
+
+        run_job(&pool, &vault, &MockAiProvider::new(80), &index, &queue, job.clone()).await;
+
+        let done = reload(&pool, &job).await;
+        assert_eq!(done.status, "succeeded");
+        let summary_id = done.summary_id.expect("résumé relié à la tâche");
+        let (summary, claims) = store::fetch_summary_with_claims(&pool, &vault, summary_id).await.unwrap().unwrap();
+        assert_eq!(summary.title, "Un hamac chauffant");
+        assert_eq!(summary.cpc_code, "G06F");
+        assert_eq!(claims.len(), 3);
+
+        let evaluation = sqlx::query!("SELECT accepted, summary_id, model FROM ai_evaluations WHERE job_id = $1", job.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(evaluation.accepted);
+        assert_eq!(evaluation.summary_id, Some(summary_id));
+        assert_eq!(evaluation.model, "mock-v1");
+
+        let query = SearchQuery { text: "hamac", cpc_prefixes: &[], from: None, to: None, limit: 10 };
+        let visibility = search::Visibility::of(&pool, job.user_id).await.unwrap();
+        assert_eq!(index.search(&query, &visibility).unwrap().1.first().map(|hit| hit.summary_id), Some(summary_id));
+
+        let mut last = None;
+        while let Ok(event) = events.try_recv() {
+            last = Some(event.progress);
+        }
+        assert!(matches!(last, Some(Progress::Persisted { summary_id: id }) if id == summary_id));
+    }
+
+    #[tokio::test]
+    async fn records_a_rejected_idea_without_summary() {
+        let (pool, vault) = setup().await;
+        let (queue, index) = (SummaryQueue::new(), SearchIndex::new().unwrap());
+        let job = running_job(&pool, &vault, "une cuillère. Elle est en bois").await;
+
+        run_job(&pool, &vault, &MockAiProvider::new(10), &index, &queue, job.clone()).await;
+
+        let done = reload(&pool, &job).await;
+        assert_eq!((done.status.as_str(), done.summary_id), ("failed", None));
+        assert_eq!(done.error_code.as_deref(), Some("unprocessable"));
+        let evaluation = sqlx::query!("SELECT accepted, rejection_reason FROM ai_evaluations WHERE job_id = $1", job.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!evaluation.accepted);
+        assert_eq!(evaluation.rejection_reason.as_deref(), Some(evaluations::NOVELTY_BELOW_THRESHOLD));
+    }
+
+    #[tokio::test]
+    async fn a_stale_attempt_records_nothing_after_its_lease_is_taken_over() {
+        let (pool, vault) = setup().await;
+        let (queue, index) = (SummaryQueue::new(), SearchIndex::new().unwrap());
+        let stale = running_job(&pool, &vault, "un vélo pliant. Il tient dans un sac").await;
+        // Bail expiré : un autre worker a réclamé la tâche pendant que la première tentative tournait
+        let current = sqlx::query_as!(
+            SummaryJob,
+            "UPDATE summary_jobs SET attempts = attempts + 1 WHERE id = $1 RETURNING *",
+            stale.id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        run_job(&pool, &vault, &MockAiProvider::new(80), &index, &queue, stale.clone()).await;
+        let pending = reload(&pool, &stale).await;
+        assert_eq!((pending.status.as_str(), pending.summary_id), ("running", None));
+
+        run_job(&pool, &vault, &MockAiProvider::new(80), &index, &queue, current).await;
+        let done = reload(&pool, &stale).await;
+        assert_eq!(done.status, "succeeded");
+        let summaries = sqlx::query_scalar!("SELECT COUNT(*) FROM summaries WHERE idea_id = $1", stale.idea_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let evaluations = sqlx::query_scalar!("SELECT summary_id FROM ai_evaluations WHERE job_id = $1", stale.id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(summaries, Some(1));
+        assert_eq!(evaluations, vec![done.summary_id]);
+    }
+
+    #[tokio::test]
+    async fn an_approved_review_lifts_the_novelty_threshold() {
+        let (pool, vault) = setup().await;
+        let (queue, index) = (SummaryQueue::new(), SearchIndex::new().unwrap());
+        let rejected = running_job(&pool, &vault, "une tasse. Elle garde le café chaud").await;
+        run_job(&pool, &vault, &MockAiProvider::new(10), &index, &queue, rejected.clone()).await;
+
+        // Décision enregistrée par `review_evaluation approve`, qui remet l'idée en file
+        sqlx::query!(
+            "UPDATE ai_evaluations SET review_status = 'approved', reviewed_at = NOW() WHERE job_id = $1",
+            rejected.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let rerun = sqlx::query_as!(
+            SummaryJob,
+            "INSERT INTO summary_jobs (id, idea_id, user_id, status, attempts, locked_until)
+             VALUES ($1, $2, $3, 'running', 1, $4)
+             RETURNING *",
+            Uuid::new_v4(),
+            rejected.idea_id,
+            rejected.user_id,
+            Utc::now() + chrono::Duration::seconds(LEASE_SECS)
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        run_job(&pool, &vault, &MockAiProvider::new(10), &index, &queue, rerun.clone()).await;
+
+        let done = reload(&pool, &rerun).await;
+        assert_eq!(done.status, "succeeded");
+        let evaluation = sqlx::query!("SELECT accepted, novelty_threshold, summary_id FROM ai_evaluations WHERE job_id = $1", rerun.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(evaluation.accepted);
+        assert_eq!((evaluation.novelty_threshold, evaluation.summary_id), (0, done.summary_id));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::i18n::Locale;
+use crate::models::PublicProofResponse;
+
+// Page publique de vérification d'une preuve, rendue côté serveur (pas de JavaScript) :
+// c'est la cible du QR code imprimé sur le certificat. Toute valeur insérée est échappée,
+// y compris les textes du catalogue (clés `verification.*`).
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+fn layout(locale: Locale, title: &str, body: &str) -> String {
+    let lang = locale.code();
+    let title = escape(title);
+    let subtitle = escape(locale.text("verification.subtitle"));
+    let footer = escape(locale.text("verification.footer"));
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <meta name="theme-color" content="#0a0f2c">
+    <meta name="robots" content="noindex">
+    <title>{title} — BrevetChain</title>
+    <style>
+        body {{ margin: 0; font-family: Inter, Helvetica, Arial, sans-serif; background: #f3f4f6; color: #1f2937; }}
+        header {{ background: #0a0f2c; color: #fff; padding: 1.5rem 1rem; }}
+        header h1 {{ margin: 0; font-size: 1.5rem; }}
+        header p {{ margin: .25rem 0 0; opacity: .8; }}
+        main {{ max-width: 44rem; margin: 2rem auto; padding: 0 1rem; }}
+        .card {{ background: #fff; border-radius: .75rem; padding: 1.5rem; box-shadow: 0 1px 3px rgba(0,0,0,.1); }}
+        .verdict {{ font-size: 1.25rem; font-weight: 700; margin-top: 0; }}
+        .ok {{ color: #10b981; }}
+        .pending {{ color: #d97706; }}
+        .ko {{ color: #dc2626; }}
+        dt {{ font-weight: 600; margin-top: 1rem; }}
+        dd {{ margin: .25rem 0 0; overflow-wrap: anywhere; }}
+        code {{ font-size: .9rem; }}
+        a {{ color: #0a0f2c; }}
+        footer {{ text-align: center; color: #6b7280; font-size: .85rem; margin: 2rem 0; }}
+    </style>
+</head>
+<body>
+    <header>
+        <h1>BrevetChain</h1>
+        <p>{subtitle}</p>
+    </header>
+    <main>
+{body}
+    </main>
+    <footer>{footer}</footer>
+</body>
+</html>
+"##
+    )
+}
+
+/// Fiche d'une preuve ancrée.
+pub fn render(proof: &PublicProofResponse, locale: Locale) -> String {
+    let t = |key| escape(locale.text(key));
+    let (verdict_class, verdict) = match proof.reconciliation_status.as_str() {
+        "confirmed" => ("ok", t("verification.confirmed")),
+        "mismatch" | "not_found" => ("ko", t("verification.not_confirmed")),
+        _ => ("pending", t("verification.pending")),
+    };
+
+    let title = match &proof.title {
+        Some(title) => escape(title),
+        None => format!("<em>{}</em>", t("verification.title_hidden")),
+    };
+    let consensus = match &proof.consensus_timestamp {
+        Some(timestamp) => escape(timestamp),
+        None => escape(&locale.format("verification.consensus_pending", &[("date", &proof.registered_at)])),
+    };
+    let transaction = match &proof.explorer_url {
+        Some(url) => format!(
+            r#"<a href="{}" rel="noopener" target="_blank"><code>{}</code></a>"#,
+            escape(url),
+            escape(&proof.hedera_tx_id)
+        ),
+        None => format!("<code>{}</code>", escape(&proof.hedera_tx_id)),
+    };
+
+    let mut details = format!(
+        r#"            <dt>{title_label}</dt><dd>{title}</dd>
+            <dt>{hash_label}</dt><dd><code>{hash}</code></dd>
+            <dt>{consensus_label}</dt><dd>{consensus}</dd>
+            <dt>{network_label}</dt><dd>{network}</dd>
+            <dt>{transaction_label}</dt><dd>{transaction}</dd>
+"#,
+        title_label = t("verification.title"),
+        hash_label = escape(&locale.format("verification.hash", &[("scheme", &proof.hash_scheme)])),
+        consensus_label = t("verification.consensus_time"),
+        network_label = t("verification.network"),
+        transaction_label = t("verification.transaction"),
+        hash = escape(&proof.hash),
+        network = escape(&proof.network),
+    );
+    match (&proof.previous_hash, proof.previous_proof_id) {
+        (Some(previous_hash), Some(previous_proof_id)) => details.push_str(&format!(
+            "            <dt>{}</dt><dd><a href=\"/verify/{}\"><code>{}</code></a></dd>\n",
+            t("verification.previous_revision"),
+            previous_proof_id,
+            escape(previous_hash)
+        )),
+        (Some(previous_hash), None) => details.push_str(&format!(
+            "            <dt>{}</dt><dd><code>{}</code></dd>\n",
+            t("verification.previous_revision"),
+            escape(previous_hash)
+        )),
+        _ => {}
+    }
+    if let Some(sequence) = proof.topic_sequence_number {
+        details.push_str(&format!(
+            "            <dt>{}</dt><dd>{}</dd>\n",
+            t("verification.topic_sequence"),
+            sequence
+        ));
+    }
+    if let Some(merkle) = &proof.merkle {
+        details.push_str(&format!(
+            "            <dt>{}</dt><dd><code>{}</code> ({})</dd>\n",
+            t("verification.merkle_root"),
+            escape(&merkle.root),
+            t(if merkle.verified { "verification.membership_verified" } else { "verification.membership_unverified" })
+        ));
+    }
+    details.push_str(&format!(
+        "            <dt>{}</dt><dd>{}</dd>\n",
+        t("verification.inventor_signature"),
+        t(if proof.inventor_signed { "verification.yes" } else { "verification.platform_only" })
+    ));
+
+    let body = format!(
+        r#"        <div class="card">
+            <p class="verdict {verdict_class}">{verdict}</p>
+            <dl>
+{details}            </dl>
+            <p><a href="/api/v1/verify/{proof_id}">{json_link}</a></p>
+        </div>"#,
+        proof_id = proof.proof_id,
+        json_link = t("verification.json_link"),
+    );
+    layout(locale, locale.text("verification.page_title"), &body)
+}
+
+/// Page renvoyée pour un identifiant de preuve inconnu.
+pub fn not_found(locale: Locale) -> String {
+    let body = format!(
+        r#"        <div class="card">
+            <p class="verdict ko">{}</p>
+            <p>{}</p>
+        </div>"#,
+        escape(locale.text("verification.not_found")),
+        escape(locale.text("verification.not_found_hint")),
+    );
+    layout(locale, locale.text("verification.not_found_title"), &body)
+}
@@ -0,0 +1,213 @@
+use hedera::PublicKey;
+use sha3::{Digest, Keccak256};
+use crate::i18n::Message;
+use crate::network::HederaNetwork;
+
+// Adresses de wallet acceptées et leur forme canonique :
+//   `0.0.123` ou `0.0.123-vfmkw` (somme de contrôle HIP-15)  -> hedera_account, `0.0.123`
+//   `0x000000000000000000000000000000000000007b` (long-zero) -> evm_long_zero, `0.0.123`
+//   `0x` + 20 octets quelconques (alias, EIP-55 si casse mixte) -> evm_alias, forme EIP-55
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletKind {
+    HederaAccount,
+    EvmLongZero,
+    EvmAlias,
+}
+
+impl WalletKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletKind::HederaAccount => "hedera_account",
+            WalletKind::EvmLongZero => "evm_long_zero",
+            WalletKind::EvmAlias => "evm_alias",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletAddress {
+    pub kind: WalletKind,
+    pub canonical: String,
+}
+
+// Identifiant du registre utilisé par la somme de contrôle ; les réseaux locaux n'en ont pas de fixe
+fn ledger_id(network: HederaNetwork) -> Option<&'static [u8]> {
+    match network {
+        HederaNetwork::Mainnet => Some(&[0]),
+        HederaNetwork::Testnet => Some(&[1]),
+        HederaNetwork::Previewnet => Some(&[2]),
+        HederaNetwork::Local => None,
+    }
+}
+
+/// Analyse et normalise une adresse. La somme de contrôle HIP-15 est vérifiée pour `network`
+/// (ignorée sur un réseau local ou simulé, `None`).
+pub fn parse(input: &str, network: Option<HederaNetwork>) -> Result<WalletAddress, Message> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(Message::new("wallet.empty"));
+    }
+    match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex_part) => parse_evm(hex_part),
+        None => parse_entity_id(input, network),
+    }
+}
+
+fn parse_entity_id(input: &str, network: Option<HederaNetwork>) -> Result<WalletAddress, Message> {
+    let (entity, checksum) = match input.split_once('-') {
+        Some((entity, checksum)) => (entity, Some(checksum)),
+        None => (input, None),
+    };
+
+    let parts: Vec<&str> = entity.split('.').collect();
+    let numbers: Vec<u64> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+    if parts.len() != 3 || numbers.len() != 3 || parts.iter().any(|p| !p.bytes().all(|b| b.is_ascii_digit())) {
+        return Err(Message::new("wallet.invalid_account_id").with("address", input));
+    }
+    // Forme sans zéros superflus : c'est sur elle que porte la somme de contrôle
+    let canonical = format!("{}.{}.{}", numbers[0], numbers[1], numbers[2]);
+
+    if let Some(checksum) = checksum {
+        if checksum.len() != 5 || !checksum.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err(Message::new("wallet.malformed_checksum").with("checksum", checksum));
+        }
+        if let Some(ledger) = network.and_then(ledger_id)
+            && entity_checksum(&canonical, ledger) != checksum
+        {
+            return Err(Message::new("wallet.invalid_checksum")
+                .with("address", &canonical)
+                .with("network", network.map(|n| n.as_str()).unwrap_or("")));
+        }
+    }
+
+    Ok(WalletAddress { kind: WalletKind::HederaAccount, canonical })
+}
+
+fn parse_evm(hex_part: &str) -> Result<WalletAddress, Message> {
+    if hex_part.len() != 40 || !hex_part.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Message::new("wallet.invalid_evm_address").with("address", format!("0x{}", hex_part)));
+    }
+    let mut bytes = [0u8; 20];
+    hex::decode_to_slice(hex_part, &mut bytes).map_err(|_| Message::new("wallet.invalid_evm_address").with("address", format!("0x{}", hex_part)))?;
+
+    // Casse mixte = somme de contrôle EIP-55 à respecter ; tout en minuscules ou majuscules est accepté
+    let mixed_case = hex_part.bytes().any(|b| b.is_ascii_lowercase()) && hex_part.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed_case && to_checksum_address(&bytes) != format!("0x{}", hex_part) {
+        return Err(Message::new("wallet.invalid_eip55_checksum"));
+    }
+
+    // Long-zero : 4 octets de shard et 8 de realm à zéro, puis le numéro de compte
+    if bytes[..12].iter().all(|b| *b == 0) {
+        let num = u64::from_be_bytes(bytes[12..].try_into().expect("8 octets"));
+        return Ok(WalletAddress { kind: WalletKind::EvmLongZero, canonical: format!("0.0.{}", num) });
+    }
+
+    Ok(WalletAddress { kind: WalletKind::EvmAlias, canonical: to_checksum_address(&bytes) })
+}
+
+/// Somme de contrôle HIP-15 de `shard.realm.num` pour un identifiant de registre.
+fn entity_checksum(entity: &str, ledger_id: &[u8]) -> String {
+    const P3: u64 = 26 * 26 * 26;
+    const P5: u64 = 26 * 26 * 26 * 26 * 26;
+    const M: u64 = 1_000_003;
+    const W: u64 = 31;
+
+    // Chiffres, avec 10 pour chaque point : "0.0.123" -> [0, 10, 0, 10, 1, 2, 3]
+    let digits = entity.chars().map(|c| if c == '.' { 10 } else { c.to_digit(10).unwrap_or(0) as u64 });
+    let (mut s, mut s0, mut s1) = (0, 0, 0);
+    for (i, digit) in digits.enumerate() {
+        s = (W * s + digit) % P3;
+        if i % 2 == 0 {
+            s0 = (s0 + digit) % 11;
+        } else {
+            s1 = (s1 + digit) % 11;
+        }
+    }
+    let sh = ledger_id.iter().chain([0u8; 6].iter()).fold(0, |sh, b| (W * sh + *b as u64) % P5);
+
+    let mut c = (((((entity.len() as u64 % 5) * 11 + s0) * 11 + s1) * P3 + s + sh) % P5) * M % P5;
+    let mut answer = [0u8; 5];
+    for slot in answer.iter_mut().rev() {
+        *slot = b'a' + (c % 26) as u8;
+        c /= 26;
+    }
+    String::from_utf8(answer.to_vec()).expect("lettres ASCII")
+}
+
+/// Forme EIP-55 d'une adresse EVM.
+pub fn to_checksum_address(bytes: &[u8; 20]) -> String {
+    let lower = hex::encode(bytes);
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 { c.to_ascii_uppercase() } else { c }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// Adresse EVM (alias) dérivée d'une clé publique ECDSA secp256k1 ; `None` pour une clé ED25519.
+pub fn evm_address_from_public_key(key: &PublicKey) -> Option<String> {
+    let address = key.to_evm_address()?;
+    let bytes: [u8; 20] = hex::decode(address.to_string().trim_start_matches("0x")).ok()?.try_into().ok()?;
+    Some(to_checksum_address(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hip15_checksum_depends_on_the_network() {
+        assert_eq!(entity_checksum("0.0.123", ledger_id(HederaNetwork::Mainnet).unwrap()), "vfmkw");
+        assert_eq!(entity_checksum("0.0.123", ledger_id(HederaNetwork::Testnet).unwrap()), "esxsf");
+
+        let parsed = parse("0.0.123-vfmkw", Some(HederaNetwork::Mainnet)).unwrap();
+        assert_eq!(parsed, WalletAddress { kind: WalletKind::HederaAccount, canonical: "0.0.123".to_string() });
+        assert!(parse("0.0.123-esxsf", Some(HederaNetwork::Testnet)).is_ok());
+        assert!(parse("0.0.123-vfmkw", Some(HederaNetwork::Testnet)).is_err());
+        assert!(parse("0.0.123-vfmkx", Some(HederaNetwork::Mainnet)).is_err());
+        assert!(parse("0.0.123-VFMKW", Some(HederaNetwork::Mainnet)).is_err());
+        // Réseau local ou simulé : la somme de contrôle n'est pas vérifiée
+        assert!(parse("0.0.123-vfmkx", None).is_ok());
+    }
+
+    #[test]
+    fn eip55_reference_addresses() {
+        for address in [
+            "0x52908400098527886E0F7030069857D2E4169EE7",
+            "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+            "0xde709f2102306220921060314715629080e2fb77",
+            "0x27b1fdb04752bbc536007a920d24acb045561c26",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let parsed = parse(address, None).unwrap();
+            assert_eq!(parsed.kind, WalletKind::EvmAlias);
+            assert!(parsed.canonical.eq_ignore_ascii_case(address));
+            assert_eq!(parse(&address.to_ascii_lowercase(), None).unwrap().canonical, parsed.canonical);
+        }
+        assert_eq!(
+            parse("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", None).unwrap().canonical,
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+    }
+
+    #[test]
+    fn rejects_a_bad_eip55_checksum() {
+        assert!(parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", None).is_err());
+        assert!(parse("0xfb6916095ca1df60bB79Ce92cE3Ea74c37c5d359", None).is_err());
+    }
+
+    #[test]
+    fn long_zero_addresses_become_account_ids() {
+        let parsed = parse("0x000000000000000000000000000000000000007b", None).unwrap();
+        assert_eq!(parsed, WalletAddress { kind: WalletKind::EvmLongZero, canonical: "0.0.123".to_string() });
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use hedera::PublicKey;
+use std::str::FromStr;
+use crate::ledger::LedgerResult;
+use crate::mirror_node::MirrorNodeClient;
+use crate::wallet::{self, WalletAddress, WalletKind};
+
+// Connexion par signature de wallet : le serveur émet un défi à usage unique, le wallet le signe
+// avec la clé ED25519 ou ECDSA (secp256k1) du compte, et la signature est vérifiée avec la clé
+// publique que le mirror node associe au compte.
+
+pub const CHALLENGE_TTL_SECS: i64 = 300;
+
+// Préfixe ajouté par les wallets Hedera (`hedera_signMessage`) avant de signer un texte
+const SIGNED_MESSAGE_PREFIX: &str = "\x19Hedera Signed Message:\n";
+
+/// Texte à signer : lisible dans le wallet, lié au compte, au nonce et à l'expiration.
+pub fn challenge_message(wallet: &WalletAddress, nonce: &str, expires_at: DateTime<Utc>) -> String {
+    format!(
+        "BrevetChain — connexion\nCompte : {}\nNonce : {}\nExpire : {}",
+        wallet.canonical,
+        nonce,
+        expires_at.to_rfc3339()
+    )
+}
+
+pub enum KeySource {
+    /// Clé publiée par le mirror node pour le compte
+    Mirror(PublicKey),
+    /// Registre sans mirror node (simulé) : clé mémorisée sur le compte à la première connexion
+    Stored(PublicKey),
+    /// Registre sans mirror node, compte sans clé mémorisée : la clé fournie par le client est retenue
+    Supplied(PublicKey),
+}
+
+impl KeySource {
+    pub fn key(&self) -> &PublicKey {
+        match self {
+            KeySource::Mirror(key) | KeySource::Stored(key) | KeySource::Supplied(key) => key,
+        }
+    }
+}
+
+// Une clé illisible ne peut correspondre à aucun compte
+fn parse_key(value: Option<&str>) -> Result<Option<PublicKey>, ()> {
+    value.map(|value| PublicKey::from_str(value.trim()).map_err(|_| ())).transpose()
+}
+
+fn same_key(a: &PublicKey, b: &PublicKey) -> bool {
+    a.to_bytes_raw() == b.to_bytes_raw()
+}
+
+/// Clé publique du compte. Quand le mirror node connaît le compte, une clé fournie doit lui être identique.
+/// Sans mirror node, la clé mémorisée sur le compte (`stored`) fait foi : une clé fournie n'est retenue
+/// qu'identique, ou pour un compte qui n'en a pas encore. Pour un alias EVM, la clé doit en outre
+/// dériver vers l'adresse.
+pub async fn resolve_public_key(
+    ledger_network: &str,
+    wallet: &WalletAddress,
+    stored: Option<&str>,
+    supplied: Option<&str>,
+) -> LedgerResult<Option<KeySource>> {
+    let (Ok(stored), Ok(supplied)) = (parse_key(stored), parse_key(supplied)) else {
+        return Ok(None);
+    };
+    if wallet.kind == WalletKind::EvmAlias
+        && supplied
+            .as_ref()
+            .is_some_and(|key| wallet::evm_address_from_public_key(key).as_deref() != Some(wallet.canonical.as_str()))
+    {
+        return Ok(None);
+    }
+
+    let Some(mirror) = MirrorNodeClient::for_network(ledger_network) else {
+        return Ok(match (stored, supplied) {
+            (Some(stored), Some(supplied)) if !same_key(&stored, &supplied) => None,
+            (Some(stored), _) => Some(KeySource::Stored(stored)),
+            (None, supplied) => supplied.map(KeySource::Supplied),
+        });
+    };
+
+    // Le mirror node accepte aussi bien `0.0.x` qu'une adresse EVM
+    match mirror.account_public_key(&wallet.canonical).await? {
+        Some(key) if supplied.as_ref().is_none_or(|s| same_key(s, &key)) => {
+            Ok(Some(KeySource::Mirror(key)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Signature hex (64 octets) du message brut ou du message préfixé par le wallet.
+pub fn verify_signature(key: &PublicKey, message: &str, signature_hex: &str) -> bool {
+    let Ok(signature) = hex::decode(signature_hex.trim().trim_start_matches("0x")) else {
+        return false;
+    };
+    let prefixed = format!("{}{}{}", SIGNED_MESSAGE_PREFIX, message.len(), message);
+    key.verify(message.as_bytes(), &signature).is_ok() || key.verify(prefixed.as_bytes(), &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hedera::PrivateKey;
+
+    fn account() -> WalletAddress {
+        WalletAddress { kind: WalletKind::HederaAccount, canonical: "0.0.4242".to_string() }
+    }
+
+    fn message() -> String {
+        challenge_message(&account(), "nonce", Utc::now())
+    }
+
+    fn prefixed(message: &str) -> String {
+        format!("{}{}{}", SIGNED_MESSAGE_PREFIX, message.len(), message)
+    }
+
+    #[test]
+    fn accepts_raw_and_prefixed_signatures_for_both_key_types() {
+        for key in [PrivateKey::generate_ed25519(), PrivateKey::generate_ecdsa()] {
+            let message = message();
+            let raw = hex::encode(key.sign(message.as_bytes()));
+            assert!(verify_signature(&key.public_key(), &message, &raw));
+            assert!(verify_signature(&key.public_key(), &message, &format!("0x{}", raw)));
+
+            let wallet = hex::encode(key.sign(prefixed(&message).as_bytes()));
+            assert!(verify_signature(&key.public_key(), &message, &wallet));
+        }
+    }
+
+    #[test]
+    fn rejects_another_key_another_message_or_garbage() {
+        for (key, other) in [
+            (PrivateKey::generate_ed25519(), PrivateKey::generate_ed25519()),
+            (PrivateKey::generate_ecdsa(), PrivateKey::generate_ecdsa()),
+        ] {
+            let message = message();
+            let signature = hex::encode(key.sign(message.as_bytes()));
+            assert!(!verify_signature(&other.public_key(), &message, &signature));
+            assert!(!verify_signature(&key.public_key(), &format!("{} ", message), &signature));
+            assert!(!verify_signature(&key.public_key(), &message, "pas de l'hexadécimal"));
+        }
+    }
+
+    #[tokio::test]
+    async fn without_a_mirror_the_stored_key_is_authoritative() {
+        let (stored, other) = (PrivateKey::generate_ed25519().public_key(), PrivateKey::generate_ed25519().public_key());
+        let (stored_hex, other_hex) = (stored.to_string(), other.to_string());
+
+        // Première connexion : la clé fournie est retenue
+        let source = resolve_public_key("simulated", &account(), None, Some(&stored_hex)).await.unwrap().unwrap();
+        assert!(matches!(source, KeySource::Supplied(_)) && same_key(source.key(), &stored));
+
+        // Ensuite seule la clé mémorisée fait foi
+        let source = resolve_public_key("simulated", &account(), Some(&stored_hex), None).await.unwrap().unwrap();
+        assert!(matches!(source, KeySource::Stored(_)) && same_key(source.key(), &stored));
+        let source = resolve_public_key("simulated", &account(), Some(&stored_hex), Some(&stored_hex)).await.unwrap();
+        assert!(matches!(source, Some(KeySource::Stored(_))));
+        assert!(resolve_public_key("simulated", &account(), Some(&stored_hex), Some(&other_hex)).await.unwrap().is_none());
+
+        // Aucune clé, ou une clé illisible
+        assert!(resolve_public_key("simulated", &account(), None, None).await.unwrap().is_none());
+        assert!(resolve_public_key("simulated", &account(), None, Some("clé")).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn an_evm_alias_only_accepts_the_key_it_derives_from() {
+        let key = PrivateKey::generate_ecdsa().public_key();
+        let alias = WalletAddress {
+            kind: WalletKind::EvmAlias,
+            canonical: wallet::evm_address_from_public_key(&key).unwrap(),
+        };
+        let source = resolve_public_key("simulated", &alias, None, Some(&key.to_string())).await.unwrap();
+        assert!(source.is_some_and(|source| same_key(source.key(), &key)));
+
+        let other = PrivateKey::generate_ecdsa().public_key().to_string();
+        assert!(resolve_public_key("simulated", &alias, None, Some(&other)).await.unwrap().is_none());
+        let ed25519 = PrivateKey::generate_ed25519().public_key().to_string();
+        assert!(resolve_public_key("simulated", &alias, None, Some(&ed25519)).await.unwrap().is_none());
+    }
+}
@@ -6,14 +6,17 @@ const submitIdeaSection = document.getElementById('submit-idea');
 const aiSummarySection = document.getElementById('ai-summary');
 const certificateSection = document.getElementById('certificate');
 const registerForm = document.getElementById('register-form');
+const loginForm = document.getElementById('login-form');
 const submitIdeaBtn = document.getElementById('submit-idea-btn');
 const registerProofBtn = document.getElementById('register-proof-btn');
 const checkStatusBtn = document.getElementById('check-status-btn');
 const recordingStatus = document.getElementById('recording-status');
 
 let currentUser = null;
+let sessionToken = sessionStorage.getItem('sessionToken');
 let currentIdeaId = null;
 let currentSummaryId = null;
+let currentClaims = [];
 
 // Hide loader after 2s
 window.addEventListener('load', () => {
@@ -33,6 +36,21 @@ startBtn.addEventListener('click', () => {
     }, 500);
 });
 
+// Session token sent with every protected API call
+function authHeaders(extra = {}) {
+    return { ...extra, 'Authorization': `Bearer ${sessionToken}` };
+}
+
+function startSession(result) {
+    currentUser = result.user_id;
+    sessionToken = result.token;
+    sessionStorage.setItem('sessionToken', sessionToken);
+    registerSection.classList.remove('active');
+    setTimeout(() => {
+        submitIdeaSection.classList.add('active');
+    }, 500);
+}
+
 // Register Form
 registerForm.addEventListener('submit', async (e) => {
     e.preventDefault();
@@ -40,6 +58,7 @@ registerForm.addEventListener('submit', async (e) => {
     const userData = {
         full_name: document.getElementById('full_name').value,
         email: document.getElementById('email').value,
+        password: document.getElementById('password').value,
         phone: document.getElementById('phone').value || null,
         country: document.getElementById('country').value || null,
         wallet_address: document.getElementById('wallet_address').value
@@ -55,12 +74,35 @@ registerForm.addEventListener('submit', async (e) => {
         const result = await response.json();
         
         if (response.ok) {
-            currentUser = result.user_id;
             showToast('✅ Compte créé avec succès !');
-            registerSection.classList.remove('active');
-            setTimeout(() => {
-                submitIdeaSection.classList.add('active');
-            }, 500);
+            startSession(result);
+        } else {
+            throw new Error(result.message || 'Erreur inconnue');
+        }
+    } catch (error) {
+        alert('Erreur : ' + error.message);
+    }
+});
+
+// Login Form
+loginForm.addEventListener('submit', async (e) => {
+    e.preventDefault();
+
+    try {
+        const response = await fetch('/api/v1/login', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                email: document.getElementById('login_email').value,
+                password: document.getElementById('login_password').value
+            })
+        });
+
+        const result = await response.json();
+
+        if (response.ok) {
+            showToast('✅ Connexion réussie !');
+            startSession(result);
         } else {
             throw new Error(result.message || 'Erreur inconnue');
         }
@@ -81,9 +123,8 @@ submitIdeaBtn.addEventListener('click', async () => {
     try {
         const response = await fetch('/api/v1/submit-idea', {
             method: 'POST',
-            headers: { 'Content-Type': 'application/json' },
+            headers: authHeaders({ 'Content-Type': 'application/json' }),
             body: JSON.stringify({
-                user_id: currentUser,
                 raw_idea: rawIdea
             })
         });
@@ -111,51 +152,213 @@ submitIdeaBtn.addEventListener('click', async () => {
 async function generateSummary() {
     try {
         const response = await fetch(`/api/v1/generate-summary/${currentIdeaId}`, {
-            method: 'POST'
+            method: 'POST',
+            headers: authHeaders()
         });
 
         const result = await response.json();
         
         if (response.ok) {
-            currentSummaryId = result.summary_id;
+            // 202 : la génération se fait en arrière-plan, on suit la tâche en direct
+            currentSummaryId = await streamSummary(result.job_id);
             
             // Fetch summary data
-            const summaryResponse = await fetch(`/api/v1/summary/${currentSummaryId}`);
+            const summaryResponse = await fetch(`/api/v1/summary/${currentSummaryId}`, { headers: authHeaders() });
             const summaryData = await summaryResponse.json();
             
-            // Populate UI
-            document.querySelector('#title span').textContent = summaryData.title;
-            document.querySelector('#problem').textContent = summaryData.problem;
-            document.querySelector('#solution').textContent = summaryData.solution;
-            document.querySelector('#claim').textContent = summaryData.claim;
-            document.querySelector('#cpc_code').textContent = summaryData.cpc_code;
-            
+            showSummary(summaryData);
             aiSummarySection.classList.add('active');
+
+            const jobResponse = await fetch(`/api/v1/jobs/${result.job_id}`, { headers: authHeaders() });
+            if (jobResponse.ok) showSimilar((await jobResponse.json()).similar);
         } else {
             throw new Error(result.message);
         }
     } catch (error) {
         alert('Erreur IA : ' + error.message);
+        await offerHumanReview();
+    }
+}
+
+// After a rejection, show the AI evaluation and offer a human review instead of a dead end
+async function offerHumanReview() {
+    const response = await fetch(`/api/v1/ideas/${currentIdeaId}/evaluations`, { headers: authHeaders() });
+    if (!response.ok) return;
+    const [evaluation] = await response.json();
+    if (!evaluation || evaluation.accepted || evaluation.review_status) return;
+
+    const details = `Score de nouveauté : ${evaluation.novelty_score}/100 (seuil ${evaluation.novelty_threshold})\n` +
+        `Titre proposé : ${evaluation.response.title}\n\nDemander une revue humaine de cette idée ?`;
+    if (!confirm(details)) return;
+
+    const review = await fetch(`/api/v1/evaluations/${evaluation.evaluation_id}/review`, {
+        method: 'POST',
+        headers: authHeaders()
+    });
+    const result = await review.json();
+    if (review.ok) {
+        showToast('🧑‍⚖️ ' + result.message);
+    } else {
+        alert('Erreur : ' + result.message);
     }
 }
 
+const generationStages = {
+    queued: '⏳ Génération en file d\'attente...',
+    calling_ai: '🤖 Analyse de l\'idée par l\'IA...',
+    validating: '🔎 Vérification du résumé...'
+};
+
+// Follow the generation over Server-Sent Events (fetch, since EventSource cannot send the session token).
+// Partial text fills the summary card as it is produced; polling takes over if the stream is unavailable.
+async function streamSummary(jobId) {
+    let response;
+    try {
+        response = await fetch(`/api/v1/generate-summary/${currentIdeaId}/stream`, { headers: authHeaders() });
+    } catch (error) {
+        return waitForSummary(jobId);
+    }
+    if (!response.ok || !response.body) return waitForSummary(jobId);
+
+    const fields = { title: document.querySelector('#title span'), problem: document.querySelector('#problem'), solution: document.querySelector('#solution') };
+    const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+    let buffer = '';
+    let streaming = false;
+    for (;;) {
+        const { value, done } = await reader.read();
+        if (done) return waitForSummary(jobId);
+        buffer += value;
+        let end;
+        while ((end = buffer.indexOf('\n\n')) !== -1) {
+            const frame = buffer.slice(0, end);
+            buffer = buffer.slice(end + 2);
+            const event = frame.match(/^event: (.*)$/m);
+            const data = frame.match(/^data: (.*)$/m);
+            if (!event || !data) continue; // keep-alive
+            const payload = JSON.parse(data[1]);
+            if (payload.job_id !== jobId) continue;
+            switch (event[1]) {
+                case 'partial':
+                    if (!streaming) {
+                        streaming = true;
+                        Object.values(fields).forEach(field => { field.textContent = ''; });
+                        aiSummarySection.classList.add('active');
+                    }
+                    if (fields[payload.field]) fields[payload.field].textContent += payload.delta;
+                    break;
+                case 'persisted':
+                    reader.cancel();
+                    return payload.summary_id;
+                case 'failed':
+                    reader.cancel();
+                    throw new Error(payload.message);
+                default:
+                    if (generationStages[event[1]]) showToast(generationStages[event[1]]);
+            }
+        }
+    }
+}
+
+// Wait until the summary job succeeds (summary id) or fails (error message)
+async function waitForSummary(jobId) {
+    for (;;) {
+        const response = await fetch(`/api/v1/jobs/${jobId}`, { headers: authHeaders() });
+        const job = await response.json();
+        if (!response.ok) throw new Error(job.message);
+        if (job.status === 'succeeded') return job.summary_id;
+        if (job.status === 'failed') throw new Error(job.error.message);
+        await new Promise(resolve => setTimeout(resolve, 1000));
+    }
+}
+
+// Closest existing summaries; other inventors' summaries only show what they made public
+function showSimilar(similar) {
+    const list = document.getElementById('similar-list');
+    list.replaceChildren();
+    similar.forEach(hit => {
+        const item = document.createElement('li');
+        const date = new Date(hit.created_at).toLocaleDateString('fr-FR');
+        item.textContent = `${hit.title || 'Résumé confidentiel'} — CPC ${hit.cpc_code}, ${date}${hit.own ? ' (vous)' : ''}`;
+        if (hit.proof_id) {
+            const link = document.createElement('a');
+            link.href = `/verify/${hit.proof_id}`;
+            link.target = '_blank';
+            link.textContent = ' Voir la preuve';
+            item.appendChild(link);
+        }
+        list.appendChild(item);
+    });
+    document.getElementById('similar-summaries').hidden = similar.length === 0;
+}
+
+// Populate the editable summary card with one revision
+function showSummary(summaryData) {
+    document.querySelector('#title span').textContent = summaryData.title;
+    document.querySelector('#problem').textContent = summaryData.problem;
+    document.querySelector('#solution').textContent = summaryData.solution;
+    currentClaims = summaryData.claims;
+    const claimList = document.querySelector('#claims');
+    claimList.replaceChildren();
+    currentClaims.forEach(claim => {
+        const item = document.createElement('li');
+        const text = document.createElement('span');
+        text.className = 'claim-text';
+        text.contentEditable = 'true';
+        text.textContent = claim.text;
+        item.appendChild(text);
+        if (claim.depends_on) {
+            item.append(` (selon la revendication ${claim.depends_on})`);
+        }
+        claimList.appendChild(item);
+    });
+    document.querySelector('#cpc_code').textContent = summaryData.cpc_code;
+    document.getElementById('revision-label').textContent = `Révision ${summaryData.revision}`;
+}
+
+// Save the edited summary as a new revision
+document.getElementById('save-revision-btn').addEventListener('click', async () => {
+    try {
+        const claimTexts = document.querySelectorAll('#claims .claim-text');
+        const response = await fetch(`/api/v1/summary/${currentSummaryId}/revisions`, {
+            method: 'POST',
+            headers: authHeaders({ 'Content-Type': 'application/json' }),
+            body: JSON.stringify({
+                title: document.querySelector('#title span').textContent,
+                problem: document.querySelector('#problem').textContent,
+                solution: document.querySelector('#solution').textContent,
+                cpc_code: document.querySelector('#cpc_code').textContent,
+                claims: currentClaims.map((claim, index) => ({ ...claim, text: claimTexts[index].textContent }))
+            })
+        });
+        const result = await response.json();
+        if (!response.ok) throw new Error(result.message);
+        currentSummaryId = result.summary_id;
+        showSummary(result);
+    } catch (error) {
+        alert('Erreur révision : ' + error.message);
+    }
+});
+
 // Register Proof on Hedera
 registerProofBtn.addEventListener('click', async () => {
     try {
         const response = await fetch(`/api/v1/register-proof/${currentSummaryId}`, {
-            method: 'POST'
+            method: 'POST',
+            headers: authHeaders()
         });
 
         const result = await response.json();
         
         if (response.ok) {
-            showToast('🔐 Preuve enregistrée sur Hedera !');
             aiSummarySection.classList.remove('active');
             
-            // Simulate blockchain delay
-            setTimeout(async () => {
-                await getCertificate();
-            }, 3000);
+            // 202 : l'ancrage se fait en arrière-plan, on attend qu'il soit confirmé
+            if (response.status === 202) {
+                showToast('⏳ Preuve en file d\'ancrage sur Hedera...');
+                await waitForAnchoring();
+            }
+            showToast('🔐 Preuve enregistrée sur Hedera !');
+            await getCertificate();
         } else {
             throw new Error(result.message);
         }
@@ -164,16 +367,39 @@ registerProofBtn.addEventListener('click', async () => {
     }
 });
 
+// Wait until the proof job leaves the "pending" state
+async function waitForAnchoring() {
+    for (;;) {
+        const response = await fetch(`/api/v1/status/${currentIdeaId}`, { headers: authHeaders() });
+        const statusData = await response.json();
+        if (statusData.proof_status === 'anchored') return;
+        if (statusData.proof_status === 'failed') {
+            throw new Error(statusData.proof_error || 'Échec de l\'ancrage');
+        }
+        await new Promise(resolve => setTimeout(resolve, 2000));
+    }
+}
+
 // Get Certificate
 async function getCertificate() {
     try {
-        const response = await fetch(`/api/v1/certificate/${currentSummaryId}`);
+        const response = await fetch(`/api/v1/certificate/${currentSummaryId}`, { headers: authHeaders() });
         const certData = await response.json();
         
         document.getElementById('cert-hash').textContent = certData.hash;
         document.getElementById('cert-timestamp').textContent = certData.timestamp;
-        document.getElementById('cert-link').href = certData.explorer_url;
-        document.getElementById('cert-link').textContent = 'Voir sur HashScan';
+        document.getElementById('cert-inventor').textContent = certData.inventor
+            ? `${certData.inventor.account} — ${certData.inventor.verified ? 'signature vérifiée ✅' : 'signature invalide ❌'}`
+            : 'aucune (signée par la plateforme uniquement)';
+        const certLink = document.getElementById('cert-link');
+        if (certData.explorer_url) {
+            certLink.href = certData.explorer_url;
+            certLink.textContent = `Voir sur HashScan (${certData.network})`;
+        } else {
+            certLink.removeAttribute('href');
+            certLink.textContent = `Réseau ${certData.network} — pas d'explorateur public`;
+        }
+        document.getElementById('cert-verify-link').href = certData.verification_url;
         
         certificateSection.classList.add('active');
     } catch (error) {
@@ -181,10 +407,45 @@ async function getCertificate() {
     }
 }
 
+// Download the PDF certificate (authenticated request, then a temporary link)
+document.getElementById('download-cert-btn').addEventListener('click', async () => {
+    try {
+        const response = await fetch(`/api/v1/certificate/${currentSummaryId}.pdf`, { headers: authHeaders() });
+        if (!response.ok) {
+            const result = await response.json();
+            throw new Error(result.message);
+        }
+        const url = URL.createObjectURL(await response.blob());
+        const link = document.createElement('a');
+        link.href = url;
+        link.download = `certificat-brevetchain-${currentSummaryId}.pdf`;
+        link.click();
+        URL.revokeObjectURL(url);
+    } catch (error) {
+        alert('Erreur certificat : ' + error.message);
+    }
+});
+
+// Allow or withdraw the title on the public verification page
+document.getElementById('disclose-title').addEventListener('change', async (event) => {
+    try {
+        const response = await fetch(`/api/v1/disclosure/${currentSummaryId}`, {
+            method: 'PUT',
+            headers: authHeaders({ 'Content-Type': 'application/json' }),
+            body: JSON.stringify({ title_disclosed: event.target.checked })
+        });
+        const result = await response.json();
+        if (!response.ok) throw new Error(result.message);
+    } catch (error) {
+        event.target.checked = !event.target.checked;
+        alert('Erreur : ' + error.message);
+    }
+});
+
 // Check Status
 checkStatusBtn.addEventListener('click', async () => {
     try {
-        const response = await fetch(`/api/v1/status/${currentIdeaId}`);
+        const response = await fetch(`/api/v1/status/${currentIdeaId}`, { headers: authHeaders() });
         const statusData = await response.json();
         
         // Update status steps (already completed in MVP flow)
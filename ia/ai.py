@@ -3,7 +3,7 @@ from fastapi.middleware.cors import CORSMiddleware
 from pydantic import BaseModel
 import random
 import re
-from typing import List, Set
+from typing import List, Optional, Set
 import uvicorn
 
 app = FastAPI(title="Enhanced MVP AI Service for Patent Platform")
@@ -19,13 +19,19 @@ app.add_middleware(
 class AiRequest(BaseModel):
     raw_idea: str
 
+class AiClaim(BaseModel):
+    text: str
+    depends_on: Optional[int] = None  # Numéro de la revendication parente (numérotation à partir de 1)
+
 class AiResponse(BaseModel):
     title: str
     problem: str
     solution: str
-    claim: str  # ✅ MVP: une seule revendication principale
+    claim: str  # Revendication principale (= claims[0].text), pour les clients de l'ancien contrat
+    claims: List[AiClaim]  # ✅ Jeu ordonné : la 1 est indépendante, les suivantes renvoient à une revendication antérieure
     cpc_code: str
     novelty_score: int
+    model: str = "heuristic-v1"  # Enregistré avec chaque évaluation côté backend
 
 # Base de connaissances enrichie pour les codes CPC
 cpc_semantic_map = {
@@ -175,18 +181,42 @@ def generate_solution(keywords: List[str]) -> str:
         return f"L'invention propose une approche basée sur {main_keyword} qui permet de {verb} la {noun} par l'intégration de composants techniques optimisés et d'un procédé de contrôle précis."
 
 def generate_claim(title: str, keywords: List[str]) -> str:
-    """Génère UNE revendication principale avec structure juridique correcte."""
+    """Génère la revendication principale (indépendante) avec structure juridique correcte."""
     if not keywords:
-        return "1. Dispositif technique caractérisé par des moyens configurés pour optimiser les performances opérationnelles tout en réduisant les coûts de fabrication."
+        return "Dispositif technique caractérisé par des moyens configurés pour optimiser les performances opérationnelles tout en réduisant les coûts de fabrication."
     
     main_keyword = keywords[0]
     if len(keywords) > 1:
         feature = keywords[1]
-        return f"1. Dispositif pour {title.lower()} comprenant au moins un module configuré pour {main_keyword}, caractérisé en ce que ledit module intègre des moyens d'optimisation de {feature} agencés de manière à améliorer la performance globale du système."
+        return f"Dispositif pour {title.lower()} comprenant au moins un module configuré pour {main_keyword}, caractérisé en ce que ledit module intègre des moyens d'optimisation de {feature} agencés de manière à améliorer la performance globale du système."
     else:
         verb = random.choice(technical_verbs)
         noun = random.choice(technical_nouns)
-        return f"1. Système technique comprenant des moyens configurés pour {verb} la {noun}, caractérisé en ce que lesdits moyens sont agencés selon une architecture innovante permettant une amélioration significative par rapport à l'état de la technique."
+        return f"Système technique comprenant des moyens configurés pour {verb} la {noun}, caractérisé en ce que lesdits moyens sont agencés selon une architecture innovante permettant une amélioration significative par rapport à l'état de la technique."
+
+def generate_claims(title: str, keywords: List[str]) -> List[AiClaim]:
+    """Génère le jeu de revendications : la principale, puis des revendications dépendantes.
+
+    Une revendication dépendante renvoie toujours à une revendication de numéro inférieur
+    (jamais à elle-même ni à une suivante), comme l'exige la validation du backend.
+    """
+    claims = [AiClaim(text=generate_claim(title, keywords))]
+    # Une revendication dépendante par caractéristique secondaire, rattachée à la principale
+    for feature in keywords[2:5]:
+        claims.append(AiClaim(
+            text=f"Dispositif selon la revendication 1, caractérisé en ce qu'il comprend en outre des moyens de {feature}.",
+            depends_on=1,
+        ))
+    # Précision de la dernière caractéristique, rattachée à la revendication qui l'introduit
+    if len(claims) > 1:
+        parent = len(claims)
+        verb = random.choice(technical_verbs)
+        noun = random.choice(technical_nouns)
+        claims.append(AiClaim(
+            text=f"Dispositif selon la revendication {parent}, dans lequel lesdits moyens sont configurés pour {verb} la {noun}.",
+            depends_on=parent,
+        ))
+    return claims
 
 def calculate_novelty_score(keywords: List[str]) -> int:
     """Calcule un score de nouveauté plus réaliste basé sur la spécificité de l'idée."""
@@ -221,11 +251,13 @@ async def structure_patent(request: AiRequest):
     try:
         if not request.raw_idea or len(request.raw_idea.strip()) < 3:
             # Génération dégradée mais cohérente pour idées très courtes
+            claim = "Dispositif technique caractérisé par ses moyens d'amélioration des performances."
             return AiResponse(
                 title="Invention Technique",
                 problem="Problème technique général nécessitant une solution innovante.",
                 solution="Solution technique basée sur des principes d'optimisation et d'efficacité.",
-                claim="1. Dispositif technique caractérisé par ses moyens d'amélioration des performances.",
+                claim=claim,
+                claims=[AiClaim(text=claim)],
                 cpc_code="G06F17",
                 novelty_score=55
             )
@@ -236,7 +268,7 @@ async def structure_patent(request: AiRequest):
         title = generate_title(keywords)
         problem = generate_problem(keywords)
         solution = generate_solution(keywords)
-        claim = generate_claim(title, keywords)
+        claims = generate_claims(title, keywords)
         cpc_code = generate_cpc_code(idea)
         novelty_score = calculate_novelty_score(keywords)
 
@@ -244,7 +276,8 @@ async def structure_patent(request: AiRequest):
             title=title,
             problem=problem,
             solution=solution,
-            claim=claim,
+            claim=claims[0].text,
+            claims=claims,
             cpc_code=cpc_code,
             novelty_score=novelty_score
         )